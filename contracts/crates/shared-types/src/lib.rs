@@ -1,5 +1,15 @@
 use anchor_lang::prelude::*;
 
+pub mod math;
+pub mod merkle;
+
+pub use math::{apply_bps, bps_of, mul_div, split_bps, Rounding, BPS_DENOMINATOR};
+pub use merkle::{
+    compute_merkle_root, empty_subtree_root, frontier_append, frontier_root, merkle_depth,
+    merkle_leaf_hash, merkle_node_hash, verify_fixed_depth_proof, verify_merkle_proof,
+    MAX_MERKLE_DEPTH,
+};
+
 // ──────────────────────────────────────────────
 // Enums
 // ──────────────────────────────────────────────
@@ -133,6 +143,45 @@ pub fn count_set_bits(bitmap: u8) -> u8 {
     bitmap.count_ones() as u8
 }
 
+/// Hash that a noumen-core PolicyProposal.change_hash must carry to authorize
+/// a governed parameter change: SHA-256(domain || payload). The domain tag keeps
+/// a proposal for one parameter from being replayed against another.
+pub fn policy_change_hash(domain: &[u8], payload: &[u8]) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[domain, payload]).to_bytes()
+}
+
+/// Pseudonymous donor identifier stored on DonationReceipt (A0-27):
+/// SHA-256("donor_wallet" || salt || wallet). It keeps the raw address out of
+/// receipt state and lets a revealed salt link the receipt to a wallet. It is
+/// not confidential: the donor signs the donating transaction and the salt is
+/// in its instruction data, so anyone reading the ledger can recompute it.
+pub fn donor_wallet_hash(salt: &[u8; 32], wallet: &Pubkey) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[b"donor_wallet", salt, wallet.as_ref()]).to_bytes()
+}
+
+/// DonationReceipt.receipt_hash: SHA-256("donation_receipt" || nonce ||
+/// amount || timestamp || source_wallet_hash || disclosure_mode ||
+/// receipt_status), integers little-endian. Domain-tagged like
+/// policy_change_hash so it cannot collide with hashes of other records.
+pub fn donation_receipt_hash(
+    nonce: u64,
+    amount: u64,
+    timestamp: i64,
+    source_wallet_hash: &[u8; 32],
+    disclosure_mode: u8,
+    receipt_status: u8,
+) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[
+        b"donation_receipt",
+        &nonce.to_le_bytes(),
+        &amount.to_le_bytes(),
+        &timestamp.to_le_bytes(),
+        source_wallet_hash,
+        &[disclosure_mode, receipt_status],
+    ])
+    .to_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,6 +197,47 @@ mod tests {
         assert_eq!(count_set_bits(0b10101), 3);
     }
 
+    #[test]
+    fn test_policy_change_hash_is_domain_separated() {
+        let payload = 1500u16.to_le_bytes();
+        let a = policy_change_hash(b"ccs_bands", &payload);
+        let b = policy_change_hash(b"referral_bps", &payload);
+        assert_ne!(a, b);
+        assert_eq!(a, policy_change_hash(b"ccs_bands", &payload));
+        assert_ne!(a, policy_change_hash(b"ccs_bands", &1501u16.to_le_bytes()));
+    }
+
+    #[test]
+    fn test_donor_wallet_hash_depends_on_salt_and_wallet() {
+        let wallet = Pubkey::new_from_array([7u8; 32]);
+        let other = Pubkey::new_from_array([8u8; 32]);
+        let a = donor_wallet_hash(&[1u8; 32], &wallet);
+        assert_eq!(a, donor_wallet_hash(&[1u8; 32], &wallet));
+        assert_ne!(a, donor_wallet_hash(&[2u8; 32], &wallet));
+        assert_ne!(a, donor_wallet_hash(&[1u8; 32], &other));
+        assert_ne!(&a[..], wallet.as_ref());
+    }
+
+    #[test]
+    fn test_donation_receipt_hash_is_domain_tagged() {
+        let wallet_hash = [3u8; 32];
+        let a = donation_receipt_hash(0, 500, 1_700_000_000, &wallet_hash, 0, 0);
+        assert_eq!(a, donation_receipt_hash(0, 500, 1_700_000_000, &wallet_hash, 0, 0));
+        assert_ne!(a, donation_receipt_hash(1, 500, 1_700_000_000, &wallet_hash, 0, 0));
+        assert_ne!(a, donation_receipt_hash(0, 500, 1_700_000_000, &wallet_hash, 0, 1));
+
+        // Same fields without the tag hash differently
+        let untagged = anchor_lang::solana_program::hash::hashv(&[
+            &0u64.to_le_bytes(),
+            &500u64.to_le_bytes(),
+            &1_700_000_000i64.to_le_bytes(),
+            &wallet_hash,
+            &[0u8, 0u8],
+        ])
+        .to_bytes();
+        assert_ne!(a, untagged);
+    }
+
     #[test]
     fn test_enum_serialization_roundtrip() {
         let agent = AgentType::Evaluator;
@@ -0,0 +1,242 @@
+// ──────────────────────────────────────────────
+// Fixed-point / basis-point math
+// ──────────────────────────────────────────────
+//
+// All economic calculations (revenue splits, budget caps, reserve ratios,
+// pricing multipliers) go through these helpers instead of f64. Every
+// intermediate product is computed in u128 so `value * numerator` can never
+// overflow before the division, and every call site states its rounding
+// direction explicitly.
+
+/// Denominator for basis-point ratios: 10_000 bps = 100%.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Rounding direction applied to the remainder of a division.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// Truncate toward zero. Use when paying out or computing caps/limits.
+    Down,
+    /// Round toward +infinity. Use when computing floors/minimums the
+    /// protocol must never undershoot (reserve requirements, min prices).
+    Up,
+    /// Round to nearest, ties away from zero.
+    HalfUp,
+}
+
+/// Computes `value * numerator / denominator` with a u128 intermediate.
+/// Returns None on a zero denominator or if the result does not fit in u64.
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+
+    let product = (value as u128).checked_mul(numerator as u128)?;
+    let denominator = denominator as u128;
+    let quotient = product / denominator;
+    let remainder = product % denominator;
+
+    let rounded = match rounding {
+        Rounding::Down => quotient,
+        Rounding::Up => {
+            if remainder > 0 {
+                quotient.checked_add(1)?
+            } else {
+                quotient
+            }
+        }
+        Rounding::HalfUp => {
+            // remainder * 2 >= denominator, written to avoid overflow
+            if remainder >= denominator - remainder {
+                quotient.checked_add(1)?
+            } else {
+                quotient
+            }
+        }
+    };
+
+    u64::try_from(rounded).ok()
+}
+
+/// Applies a basis-point ratio to `value`: `value * bps / 10_000`.
+/// Ratios above 10_000 bps act as multipliers (e.g. 15_000 = 1.5x).
+pub fn apply_bps(value: u64, bps: u16, rounding: Rounding) -> Option<u64> {
+    mul_div(value, bps as u64, BPS_DENOMINATOR, rounding)
+}
+
+/// Expresses `part` as basis points of `whole`. Returns None when `whole` is
+/// zero or the ratio does not fit in u64.
+pub fn bps_of(part: u64, whole: u64, rounding: Rounding) -> Option<u64> {
+    mul_div(part, BPS_DENOMINATOR, whole, rounding)
+}
+
+/// Splits `value` into `(share, remainder)` where `share = value * bps / 10_000`
+/// rounded down. `share + remainder == value` always holds, so truncation dust
+/// is never lost. Returns None if `bps` exceeds 10_000.
+pub fn split_bps(value: u64, bps: u16) -> Option<(u64, u64)> {
+    if bps as u64 > BPS_DENOMINATOR {
+        return None;
+    }
+    let share = apply_bps(value, bps, Rounding::Down)?;
+    let remainder = value.checked_sub(share)?;
+    Some((share, remainder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_ROUNDINGS: [Rounding; 3] = [Rounding::Down, Rounding::Up, Rounding::HalfUp];
+
+    #[test]
+    fn test_mul_div_exact() {
+        for r in ALL_ROUNDINGS {
+            assert_eq!(mul_div(100, 3, 4, r), Some(75));
+            assert_eq!(mul_div(0, 123, 7, r), Some(0));
+            assert_eq!(mul_div(123, 0, 7, r), Some(0));
+            assert_eq!(mul_div(10, 10_000, 10_000, r), Some(10));
+        }
+    }
+
+    #[test]
+    fn test_mul_div_rounding_modes() {
+        // 10 / 3 = 3.333..
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down), Some(3));
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up), Some(4));
+        assert_eq!(mul_div(10, 1, 3, Rounding::HalfUp), Some(3));
+
+        // 11 / 3 = 3.666..
+        assert_eq!(mul_div(11, 1, 3, Rounding::Down), Some(3));
+        assert_eq!(mul_div(11, 1, 3, Rounding::Up), Some(4));
+        assert_eq!(mul_div(11, 1, 3, Rounding::HalfUp), Some(4));
+
+        // 5 / 2 = 2.5 (tie goes up)
+        assert_eq!(mul_div(5, 1, 2, Rounding::Down), Some(2));
+        assert_eq!(mul_div(5, 1, 2, Rounding::Up), Some(3));
+        assert_eq!(mul_div(5, 1, 2, Rounding::HalfUp), Some(3));
+
+        // Odd denominator, below the midpoint: 7 * 1 / 5 = 1.4 rounds down
+        assert_eq!(mul_div(7, 1, 5, Rounding::HalfUp), Some(1));
+        // Odd denominator, above the midpoint: 8 * 1 / 5 = 1.6 rounds up
+        assert_eq!(mul_div(8, 1, 5, Rounding::HalfUp), Some(2));
+    }
+
+    #[test]
+    fn test_mul_div_zero_denominator() {
+        for r in ALL_ROUNDINGS {
+            assert_eq!(mul_div(1, 1, 0, r), None);
+            assert_eq!(mul_div(0, 0, 0, r), None);
+        }
+    }
+
+    #[test]
+    fn test_mul_div_u128_intermediate() {
+        // u64::MAX * 10_000 overflows u64 but not u128
+        for r in ALL_ROUNDINGS {
+            assert_eq!(mul_div(u64::MAX, 10_000, 10_000, r), Some(u64::MAX));
+            assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, r), Some(u64::MAX));
+        }
+        assert_eq!(mul_div(u64::MAX, 3, 4, Rounding::Down), Some(13_835_058_055_282_163_711));
+        assert_eq!(mul_div(u64::MAX, 3, 4, Rounding::Up), Some(13_835_058_055_282_163_712));
+    }
+
+    #[test]
+    fn test_mul_div_result_overflow() {
+        for r in ALL_ROUNDINGS {
+            assert_eq!(mul_div(u64::MAX, 2, 1, r), None);
+            assert_eq!(mul_div(u64::MAX, 10_001, 10_000, r), None);
+        }
+        // Exactly u64::MAX, no remainder: every mode fits
+        assert_eq!(mul_div(u64::MAX, 3, 3, Rounding::Up), Some(u64::MAX));
+        assert_eq!(mul_div(u64::MAX, 2, 2, Rounding::HalfUp), Some(u64::MAX));
+        // 31 * 1_190_112_520_884_487_201 = 2^65 - 1 = 2 * u64::MAX + 1:
+        // the quotient is u64::MAX with a remainder of 1/2, so rounding up
+        // (or a half tie going up) would overflow
+        let b = 1_190_112_520_884_487_201;
+        assert_eq!(mul_div(31, b, 2, Rounding::Down), Some(u64::MAX));
+        assert_eq!(mul_div(31, b, 2, Rounding::Up), None);
+        assert_eq!(mul_div(31, b, 2, Rounding::HalfUp), None);
+    }
+
+    #[test]
+    fn test_apply_bps() {
+        assert_eq!(apply_bps(1_000_000_000, 4000, Rounding::Down), Some(400_000_000));
+        assert_eq!(apply_bps(1_000_000_000, 4500, Rounding::Down), Some(450_000_000));
+        assert_eq!(apply_bps(1_000_000_000, 0, Rounding::Down), Some(0));
+        assert_eq!(apply_bps(1_000_000_000, 10_000, Rounding::Down), Some(1_000_000_000));
+
+        // Multipliers above 100%
+        assert_eq!(apply_bps(100_000_000, 12_000, Rounding::Down), Some(120_000_000));
+        assert_eq!(apply_bps(100, 15_000, Rounding::Down), Some(150));
+
+        // Truncation dust: 1 lamport * 40%
+        assert_eq!(apply_bps(1, 4000, Rounding::Down), Some(0));
+        assert_eq!(apply_bps(1, 4000, Rounding::Up), Some(1));
+        assert_eq!(apply_bps(1, 4000, Rounding::HalfUp), Some(0));
+        assert_eq!(apply_bps(1, 5000, Rounding::HalfUp), Some(1));
+
+        // 333 lamports * 25% = 83.25
+        assert_eq!(apply_bps(333, 2500, Rounding::Down), Some(83));
+        assert_eq!(apply_bps(333, 2500, Rounding::Up), Some(84));
+        assert_eq!(apply_bps(333, 2500, Rounding::HalfUp), Some(83));
+
+        // u16::MAX bps on u64::MAX overflows the u64 result
+        assert_eq!(apply_bps(u64::MAX, u16::MAX, Rounding::Down), None);
+    }
+
+    #[test]
+    fn test_apply_bps_matches_reference_for_small_values() {
+        // Exhaustive cross-check against straightforward integer math
+        for value in 0u64..=2_000 {
+            for bps in [0u16, 1, 99, 300, 1500, 2500, 4000, 4500, 9999, 10_000, 12_000] {
+                let product = value * bps as u64;
+                let down = product / 10_000;
+                let up = product.div_ceil(10_000);
+                let half = (product + 5_000) / 10_000;
+                assert_eq!(apply_bps(value, bps, Rounding::Down), Some(down));
+                assert_eq!(apply_bps(value, bps, Rounding::Up), Some(up));
+                assert_eq!(apply_bps(value, bps, Rounding::HalfUp), Some(half));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bps_of() {
+        assert_eq!(bps_of(25, 100, Rounding::Down), Some(2500));
+        assert_eq!(bps_of(1, 3, Rounding::Down), Some(3333));
+        assert_eq!(bps_of(1, 3, Rounding::Up), Some(3334));
+        assert_eq!(bps_of(2, 3, Rounding::HalfUp), Some(6667));
+        assert_eq!(bps_of(0, 100, Rounding::Down), Some(0));
+        assert_eq!(bps_of(200, 100, Rounding::Down), Some(20_000));
+        assert_eq!(bps_of(1, 0, Rounding::Down), None);
+        assert_eq!(bps_of(u64::MAX, 1, Rounding::Down), None);
+    }
+
+    #[test]
+    fn test_split_bps_conserves_value() {
+        for value in [0u64, 1, 2, 3, 7, 999, 1_000_001, u64::MAX / 2, u64::MAX] {
+            for bps in [0u16, 1, 1500, 4000, 4500, 5000, 9999, 10_000] {
+                let (share, rest) = split_bps(value, bps).unwrap();
+                assert_eq!(share.checked_add(rest), Some(value));
+                assert_eq!(Some(share), apply_bps(value, bps, Rounding::Down));
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_bps_rejects_over_100_percent() {
+        assert_eq!(split_bps(100, 10_001), None);
+        assert_eq!(split_bps(0, u16::MAX), None);
+    }
+
+    #[test]
+    fn test_three_way_revenue_split_is_exact() {
+        // 40% operations / 45% reserve / remainder creator
+        for amount in [1u64, 3, 10, 999, 1_200_000, 9_000_000, 90_000_000, u64::MAX] {
+            let ops = apply_bps(amount, 4000, Rounding::Down).unwrap();
+            let reserve = apply_bps(amount, 4500, Rounding::Down).unwrap();
+            let creator = amount - ops - reserve;
+            assert_eq!(ops + reserve + creator, amount);
+            assert!(creator >= apply_bps(amount, 1500, Rounding::Down).unwrap());
+        }
+    }
+}
@@ -0,0 +1,379 @@
+// ──────────────────────────────────────────────
+// Merkle inclusion proofs
+// ──────────────────────────────────────────────
+//
+// Shared definition of the batch Merkle tree committed by
+// `noumen-proof::submit_batch_proof`, so the on-chain verifier and off-chain
+// batch builders hash exactly the same way.
+//
+// Tree shape: leaves are padded with `EMPTY_LEAF` up to the next power of two
+// and the tree is perfectly balanced, so a tree over `leaf_count` leaves has
+// depth `ceil(log2(leaf_count))` and every proof carries exactly that many
+// siblings. Leaf and interior hashes use distinct one-byte prefixes (as in
+// RFC 6962) so an interior node can never be passed off as a leaf.
+
+use anchor_lang::solana_program::hash::hashv;
+
+/// Domain prefix for leaf hashes.
+pub const MERKLE_LEAF_PREFIX: &[u8] = &[0x00];
+/// Domain prefix for interior node hashes.
+pub const MERKLE_NODE_PREFIX: &[u8] = &[0x01];
+/// Filler for the padding slots past `leaf_count`.
+pub const EMPTY_LEAF: [u8; 32] = [0u8; 32];
+/// A u32 leaf_count can never need more siblings than this.
+pub const MAX_MERKLE_DEPTH: usize = 32;
+
+/// Hashes raw leaf data: `sha256(0x00 || data)`.
+pub fn merkle_leaf_hash(data: &[u8]) -> [u8; 32] {
+    hashv(&[MERKLE_LEAF_PREFIX, data]).to_bytes()
+}
+
+/// Hashes two child nodes: `sha256(0x01 || left || right)`.
+pub fn merkle_node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[MERKLE_NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Depth of the padded tree over `leaf_count` leaves. None for an empty tree.
+pub fn merkle_depth(leaf_count: u32) -> Option<usize> {
+    if leaf_count == 0 {
+        return None;
+    }
+    Some((u32::BITS - (leaf_count - 1).leading_zeros()) as usize)
+}
+
+/// Folds a sibling path into a root, starting from an already-hashed leaf.
+/// Bit `i` of `index` says whether the running node is the right child at level `i`.
+pub fn compute_merkle_root(leaf_hash: [u8; 32], index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut node = leaf_hash;
+    let mut position = index as u64;
+    for sibling in proof {
+        node = if position & 1 == 0 {
+            merkle_node_hash(&node, sibling)
+        } else {
+            merkle_node_hash(sibling, &node)
+        };
+        position >>= 1;
+    }
+    node
+}
+
+/// Returns true iff `leaf_data` sits at `index` in the tree of `leaf_count`
+/// leaves committed to by `root`. Rejects out-of-range indices and proofs
+/// whose length does not match the tree depth.
+pub fn verify_merkle_proof(
+    root: &[u8; 32],
+    leaf_data: &[u8],
+    index: u32,
+    leaf_count: u32,
+    proof: &[[u8; 32]],
+) -> bool {
+    let depth = match merkle_depth(leaf_count) {
+        Some(depth) => depth,
+        None => return false,
+    };
+    if index >= leaf_count || proof.len() != depth {
+        return false;
+    }
+    compute_merkle_root(merkle_leaf_hash(leaf_data), index, proof) == *root
+}
+
+/// Builds the root over `leaves` (raw leaf data) with the padding rules above.
+/// Returns None for an empty set. Intended for tests and off-chain tooling.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let leaf_count = u32::try_from(leaves.len()).ok()?;
+    let depth = merkle_depth(leaf_count)?;
+
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(|l| merkle_leaf_hash(l)).collect();
+    level.resize(1usize << depth, EMPTY_LEAF);
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| merkle_node_hash(&pair[0], &pair[1]))
+            .collect();
+    }
+    Some(level[0])
+}
+
+/// Sibling path for `leaves[index]`, matching `merkle_root`.
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let depth = merkle_depth(u32::try_from(leaves.len()).ok()?)?;
+
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(|l| merkle_leaf_hash(l)).collect();
+    level.resize(1usize << depth, EMPTY_LEAF);
+    let mut position = index;
+    let mut proof = Vec::with_capacity(depth);
+    while level.len() > 1 {
+        proof.push(level[position ^ 1]);
+        level = level
+            .chunks(2)
+            .map(|pair| merkle_node_hash(&pair[0], &pair[1]))
+            .collect();
+        position >>= 1;
+    }
+    Some(proof)
+}
+
+// ──────────────────────────────────────────────
+// Fixed-depth incremental trees
+// ──────────────────────────────────────────────
+//
+// Append-only form of the same tree for on-chain archives that cannot keep
+// their leaves (e.g. the noumen-treasury donation receipt archive). The depth
+// is fixed up front, unused slots hold `EMPTY_LEAF`, and only the frontier
+// (the latest left-hand node at each level) is stored. Over `leaf_count`
+// leaves it commits to the same root as a full rebuild padded to `2^depth`
+// leaves, so every proof carries exactly `depth` siblings. The last slot is
+// never filled: a full tree's root would need one more frontier level, so the
+// tree holds at most `2^depth - 1` leaves.
+
+/// Root of a subtree of height `height` holding only `EMPTY_LEAF`.
+pub fn empty_subtree_root(height: usize) -> [u8; 32] {
+    let mut node = EMPTY_LEAF;
+    for _ in 0..height {
+        node = merkle_node_hash(&node, &node);
+    }
+    node
+}
+
+/// Appends an already-hashed leaf at position `leaf_count` of the tree of
+/// depth `frontier.len()`. Returns false (frontier untouched) when it already
+/// holds `2^depth - 1` leaves.
+pub fn frontier_append(frontier: &mut [[u8; 32]], leaf_count: u64, leaf_hash: [u8; 32]) -> bool {
+    let depth = frontier.len();
+    if depth > MAX_MERKLE_DEPTH || leaf_count >= (1u64 << depth) - 1 {
+        return false;
+    }
+    let mut node = leaf_hash;
+    let mut position = leaf_count;
+    for slot in frontier.iter_mut() {
+        if position & 1 == 0 {
+            *slot = node;
+            break;
+        }
+        node = merkle_node_hash(slot, &node);
+        position >>= 1;
+    }
+    true
+}
+
+/// Root of the tree of depth `frontier.len()` holding `leaf_count` leaves.
+pub fn frontier_root(frontier: &[[u8; 32]], leaf_count: u64) -> [u8; 32] {
+    let mut node = EMPTY_LEAF;
+    let mut empty = EMPTY_LEAF;
+    let mut size = leaf_count;
+    for left in frontier {
+        node = if size & 1 == 1 {
+            merkle_node_hash(left, &node)
+        } else {
+            merkle_node_hash(&node, &empty)
+        };
+        empty = merkle_node_hash(&empty, &empty);
+        size >>= 1;
+    }
+    node
+}
+
+/// Builds the root of the tree of depth `depth` over already-hashed leaves by
+/// full rebuild. Returns None when they do not fit. Intended for tests and
+/// off-chain tooling.
+pub fn fixed_depth_merkle_root(leaf_hashes: &[[u8; 32]], depth: usize) -> Option<[u8; 32]> {
+    fixed_depth_levels(leaf_hashes, depth).map(|levels| levels[depth][0])
+}
+
+/// Sibling path for `leaf_hashes[index]`, matching `fixed_depth_merkle_root`.
+pub fn fixed_depth_merkle_proof(
+    leaf_hashes: &[[u8; 32]],
+    depth: usize,
+    index: usize,
+) -> Option<Vec<[u8; 32]>> {
+    if index >= leaf_hashes.len() {
+        return None;
+    }
+    let levels = fixed_depth_levels(leaf_hashes, depth)?;
+    Some(
+        (0..depth)
+            .map(|level| levels[level][(index >> level) ^ 1])
+            .collect(),
+    )
+}
+
+/// Returns true iff `leaf_data` sits at `index` in the tree of depth `depth`
+/// holding `leaf_count` leaves committed to by `root`.
+pub fn verify_fixed_depth_proof(
+    root: &[u8; 32],
+    leaf_data: &[u8],
+    index: u64,
+    leaf_count: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    let depth = proof.len();
+    if depth > MAX_MERKLE_DEPTH || index >= leaf_count || leaf_count > 1u64 << depth {
+        return false;
+    }
+    compute_merkle_root(merkle_leaf_hash(leaf_data), index as u32, proof) == *root
+}
+
+/// Every level of the padded tree, leaves first.
+fn fixed_depth_levels(leaf_hashes: &[[u8; 32]], depth: usize) -> Option<Vec<Vec<[u8; 32]>>> {
+    if depth > MAX_MERKLE_DEPTH || leaf_hashes.len() as u64 > 1u64 << depth {
+        return None;
+    }
+    let mut level = leaf_hashes.to_vec();
+    level.resize(1usize << depth, EMPTY_LEAF);
+    let mut levels = vec![level];
+    for _ in 0..depth {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| merkle_node_hash(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    Some(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<[u8; 32]> {
+        (0..n).map(|i| [i.wrapping_add(1); 32]).collect()
+    }
+
+    #[test]
+    fn test_leaf_and_node_hashes_are_domain_separated() {
+        let a = [1u8; 32];
+        let b = [2u8; 32];
+        let mut concat = [0u8; 64];
+        concat[..32].copy_from_slice(&a);
+        concat[32..].copy_from_slice(&b);
+        // An interior node must not be forgeable as a leaf over the same bytes
+        assert_ne!(merkle_leaf_hash(&concat), merkle_node_hash(&a, &b));
+        assert_ne!(merkle_leaf_hash(&a), hashv(&[&a]).to_bytes());
+        assert_ne!(merkle_node_hash(&a, &b), merkle_node_hash(&b, &a));
+    }
+
+    #[test]
+    fn test_merkle_depth() {
+        assert_eq!(merkle_depth(0), None);
+        assert_eq!(merkle_depth(1), Some(0));
+        assert_eq!(merkle_depth(2), Some(1));
+        assert_eq!(merkle_depth(3), Some(2));
+        assert_eq!(merkle_depth(4), Some(2));
+        assert_eq!(merkle_depth(5), Some(3));
+        assert_eq!(merkle_depth(u32::MAX), Some(MAX_MERKLE_DEPTH));
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_leaf_hash() {
+        let l = leaves(1);
+        let root = merkle_root(&l).unwrap();
+        assert_eq!(root, merkle_leaf_hash(&l[0]));
+        assert!(verify_merkle_proof(&root, &l[0], 0, 1, &[]));
+        assert_eq!(merkle_root(&[]), None);
+    }
+
+    #[test]
+    fn test_every_leaf_verifies_for_uneven_trees() {
+        for n in 1..=9u8 {
+            let l = leaves(n);
+            let root = merkle_root(&l).unwrap();
+            for (i, leaf) in l.iter().enumerate() {
+                let proof = merkle_proof(&l, i).unwrap();
+                assert!(verify_merkle_proof(&root, leaf, i as u32, n as u32, &proof));
+                assert_eq!(compute_merkle_root(merkle_leaf_hash(leaf), i as u32, &proof), root);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_leaf_index_or_path() {
+        let l = leaves(5);
+        let root = merkle_root(&l).unwrap();
+        let proof = merkle_proof(&l, 2).unwrap();
+
+        assert!(!verify_merkle_proof(&root, &l[3], 2, 5, &proof));
+        assert!(!verify_merkle_proof(&root, &l[2], 3, 5, &proof));
+
+        let mut tampered = proof.clone();
+        tampered[0][0] ^= 1;
+        assert!(!verify_merkle_proof(&root, &l[2], 2, 5, &tampered));
+
+        // Wrong proof length or out-of-range index
+        assert!(!verify_merkle_proof(&root, &l[2], 2, 5, &proof[..2]));
+        assert!(!verify_merkle_proof(&root, &l[2], 2, 4, &proof));
+        assert!(!verify_merkle_proof(&root, &l[2], 5, 5, &proof));
+        assert!(!verify_merkle_proof(&root, &l[2], 2, 0, &[]));
+    }
+
+    #[test]
+    fn test_padding_slots_hold_empty_leaf() {
+        // The last real leaf of an uneven tree is paired with the raw filler,
+        // not with a leaf hash, so padding can never verify as a leaf.
+        let l = leaves(5);
+        let proof = merkle_proof(&l, 4).unwrap();
+        assert_eq!(proof.len(), 3);
+        assert_eq!(proof[0], EMPTY_LEAF);
+        assert_eq!(proof[1], merkle_node_hash(&EMPTY_LEAF, &EMPTY_LEAF));
+        assert_ne!(merkle_leaf_hash(&EMPTY_LEAF), EMPTY_LEAF);
+    }
+
+    #[test]
+    fn test_frontier_root_matches_full_rebuild() {
+        const DEPTH: usize = 4;
+        let hashes: Vec<[u8; 32]> = leaves(15).iter().map(|l| merkle_leaf_hash(l)).collect();
+        let mut frontier = [[0u8; 32]; DEPTH];
+
+        assert_eq!(frontier_root(&frontier, 0), empty_subtree_root(DEPTH));
+        assert_eq!(fixed_depth_merkle_root(&[], DEPTH), Some(empty_subtree_root(DEPTH)));
+        for n in 1..=hashes.len() {
+            assert!(frontier_append(&mut frontier, n as u64 - 1, hashes[n - 1]));
+            let root = frontier_root(&frontier, n as u64);
+            assert_eq!(Some(root), fixed_depth_merkle_root(&hashes[..n], DEPTH));
+        }
+
+        // Full: the frontier is left as is
+        let before = frontier;
+        assert!(!frontier_append(&mut frontier, 15, [9u8; 32]));
+        assert_eq!(frontier, before);
+        assert_eq!(fixed_depth_merkle_root(&[[0u8; 32]; 17], DEPTH), None);
+    }
+
+    #[test]
+    fn test_fixed_depth_root_matches_merkle_root_at_natural_depth() {
+        for n in 1..=9u8 {
+            let l = leaves(n);
+            let hashes: Vec<[u8; 32]> = l.iter().map(|leaf| merkle_leaf_hash(leaf)).collect();
+            let depth = merkle_depth(n as u32).unwrap();
+            assert_eq!(fixed_depth_merkle_root(&hashes, depth), merkle_root(&l));
+        }
+    }
+
+    #[test]
+    fn test_fixed_depth_proofs_verify_against_frontier_root() {
+        const DEPTH: usize = 5;
+        let l = leaves(11);
+        let hashes: Vec<[u8; 32]> = l.iter().map(|leaf| merkle_leaf_hash(leaf)).collect();
+        let mut frontier = [[0u8; 32]; DEPTH];
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(frontier_append(&mut frontier, i as u64, *hash));
+        }
+        let root = frontier_root(&frontier, 11);
+
+        for (i, leaf) in l.iter().enumerate() {
+            let proof = fixed_depth_merkle_proof(&hashes, DEPTH, i).unwrap();
+            assert_eq!(proof.len(), DEPTH);
+            assert!(verify_fixed_depth_proof(&root, leaf, i as u64, 11, &proof));
+        }
+
+        let proof = fixed_depth_merkle_proof(&hashes, DEPTH, 3).unwrap();
+        assert!(!verify_fixed_depth_proof(&root, &l[4], 3, 11, &proof));
+        assert!(!verify_fixed_depth_proof(&root, &l[3], 11, 11, &proof));
+        assert!(!verify_fixed_depth_proof(&root, &l[3], 3, 33, &proof));
+        // Padding slots are not leaves
+        assert_eq!(fixed_depth_merkle_proof(&hashes, DEPTH, 11), None);
+        assert!(!verify_fixed_depth_proof(&root, &EMPTY_LEAF, 11, 11, &proof));
+    }
+}
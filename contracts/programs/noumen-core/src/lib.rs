@@ -1231,6 +1231,15 @@ pub enum CoreError {
 }
 
 // Constants for delay enforcement
+#[cfg(not(feature = "short-policy-delays"))]
 pub const LAYER1_MIN_DELAY: i64 = 259200;  // 72 hours
 pub const LAYER1_MAX_DELAY: i64 = 2592000; // 30 days
+#[cfg(not(feature = "short-policy-delays"))]
 pub const LAYER2_MIN_DELAY: i64 = 86400;   // 24 hours
+
+// Localnet integration builds only (`npm run test:localnet`), so governed
+// instructions can be exercised end to end. Never deploy with this feature.
+#[cfg(feature = "short-policy-delays")]
+pub const LAYER1_MIN_DELAY: i64 = 4;
+#[cfg(feature = "short-policy-delays")]
+pub const LAYER2_MIN_DELAY: i64 = 2;
@@ -38,6 +38,10 @@ pub mod noumen_proof {
     /// - A0-18: evidence_families_bitmap only uses bits 0-4 (5 families)
     /// - A0-17: execution-class decisions require >= 2 independent evidence families
     /// - decision_class must be a valid value (0-3)
+    /// - nonce must be exactly the agent's ProofCursor.last_nonce + 1 (first nonce is 1)
+    ///
+    /// Each log stores the cursor's running chain hash as prev_hash and advances the
+    /// cursor, so an agent's history is a gap-free hash chain that anyone can replay.
     pub fn log_decision(
         ctx: Context<LogDecision>,
         args: LogDecisionArgs,
@@ -63,6 +67,17 @@ pub mod noumen_proof {
             ProofError::InvalidDecisionClass
         );
 
+        // Nonces are strictly sequential per agent: no gaps, no reordering
+        let cursor = &mut ctx.accounts.proof_cursor;
+        let expected_nonce = cursor
+            .last_nonce
+            .checked_add(1)
+            .ok_or(ProofError::MathOverflow)?;
+        require!(
+            args.nonce == expected_nonce,
+            ProofError::NonceOutOfSequence
+        );
+
         let clock = Clock::get()?;
         let log = &mut ctx.accounts.decision_log;
 
@@ -77,7 +92,14 @@ pub mod noumen_proof {
         log.is_execution_class = args.is_execution_class;
         log.execution_confirmed = false;
         log.bump = ctx.bumps.decision_log;
-        log._reserved = [0u8; 48];
+        log.prev_hash = cursor.chain_hash;
+        log._reserved = [0u8; 16];
+
+        let chain_hash = log.chain_hash();
+        cursor.agent_id = args.agent_id;
+        cursor.last_nonce = args.nonce;
+        cursor.chain_hash = chain_hash;
+        cursor.bump = ctx.bumps.proof_cursor;
 
         emit!(DecisionLogged {
             agent_id: args.agent_id,
@@ -86,6 +108,8 @@ pub mod noumen_proof {
             evidence_families_bitmap: args.evidence_families_bitmap,
             decision_class: args.decision_class,
             is_execution_class: args.is_execution_class,
+            prev_hash: log.prev_hash,
+            chain_hash,
             timestamp: clock.unix_timestamp,
         });
 
@@ -208,6 +232,41 @@ pub mod noumen_proof {
 
         Ok(())
     }
+
+    /// Check that a decision is a leaf of a submitted BatchProof.
+    /// Permissionless and read-only: recomputes the root from the leaf and its sibling
+    /// path with the shared-types domain-separated hashing and compares it to the
+    /// stored merkle_root. The result is emitted and also returned, so other programs
+    /// can CPI this instruction and read the answer from the return data.
+    ///
+    /// A malformed proof (index >= leaf_count, or a path whose length does not match
+    /// the tree depth) verifies as false rather than failing.
+    pub fn verify_decision_in_batch(
+        ctx: Context<VerifyDecisionInBatch>,
+        args: VerifyDecisionInBatchArgs,
+    ) -> Result<bool> {
+        let batch = &ctx.accounts.batch_proof;
+
+        let included = verify_merkle_proof(
+            &batch.merkle_root,
+            &args.decision_hash,
+            args.leaf_index,
+            batch.leaf_count,
+            &args.proof,
+        );
+
+        emit!(DecisionInclusionVerified {
+            agent_id: batch.agent_id,
+            batch_nonce: batch.batch_nonce,
+            merkle_root: batch.merkle_root,
+            decision_hash: args.decision_hash,
+            leaf_index: args.leaf_index,
+            included,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(included)
+    }
 }
 
 // ──────────────────────────────────────────────
@@ -235,7 +294,44 @@ pub struct DecisionLog {
     pub is_execution_class: bool,
     pub execution_confirmed: bool,
     pub bump: u8,
-    pub _reserved: [u8; 48],
+    /// ProofCursor.chain_hash before this entry (zero for the agent's first decision)
+    pub prev_hash: [u8; 32],
+    pub _reserved: [u8; 16],
+}
+
+impl DecisionLog {
+    /// Link hash committing to this entry and, through prev_hash, to every earlier
+    /// entry of the agent. execution_confirmed is excluded because it flips later.
+    pub fn chain_hash(&self) -> [u8; 32] {
+        anchor_lang::solana_program::hash::hashv(&[
+            b"decision_chain",
+            &self.prev_hash,
+            &self.agent_id.to_le_bytes(),
+            &self.nonce.to_le_bytes(),
+            &self.input_hash,
+            &self.decision_hash,
+            &self.justification_hash,
+            &[
+                self.evidence_families_bitmap,
+                self.decision_class,
+                self.is_execution_class as u8,
+            ],
+            &self.timestamp.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+/// Per-agent head of the DecisionLog hash chain.
+#[account]
+pub struct ProofCursor {
+    pub agent_id: u16,
+    /// Nonce of the latest DecisionLog (0 until the first decision)
+    pub last_nonce: u64,
+    /// chain_hash of the latest DecisionLog (zero until the first decision)
+    pub chain_hash: [u8; 32],
+    pub bump: u8,
+    pub _reserved: [u8; 32],
 }
 
 #[account]
@@ -304,6 +400,15 @@ pub struct CloseExpiredBatchArgs {
     pub min_age_seconds: i64,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VerifyDecisionInBatchArgs {
+    /// Leaf data as committed in the batch: the decision's decision_hash
+    pub decision_hash: [u8; 32],
+    pub leaf_index: u32,
+    /// Sibling hashes from the leaf level up to the root
+    pub proof: Vec<[u8; 32]>,
+}
+
 // ──────────────────────────────────────────────
 // Account Sizes (8-byte discriminator + fields)
 // ──────────────────────────────────────────────
@@ -311,8 +416,11 @@ pub struct CloseExpiredBatchArgs {
 // ProofConfig: 8 + 32 + 1 + 1 + 32 = 74
 const PROOF_CONFIG_SIZE: usize = 8 + 32 + 1 + 1 + 32;
 
-// DecisionLog: 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1 + 8 + 1 + 1 + 1 + 48 = 175
-const DECISION_LOG_SIZE: usize = 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1 + 8 + 1 + 1 + 1 + 48;
+// DecisionLog: 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1 + 8 + 1 + 1 + 1 + 32 + 16 = 175
+const DECISION_LOG_SIZE: usize = 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1 + 8 + 1 + 1 + 1 + 32 + 16;
+
+// ProofCursor: 8 + 2 + 8 + 32 + 1 + 32 = 83
+const PROOF_CURSOR_SIZE: usize = 8 + 2 + 8 + 32 + 1 + 32;
 
 // ExecutionResult: 8 + 32 + 32 + 1 + 8 + 32 + 1 + 44 = 158
 const EXECUTION_RESULT_SIZE: usize = 8 + 32 + 32 + 1 + 8 + 32 + 1 + 44;
@@ -362,6 +470,18 @@ pub struct LogDecision<'info> {
         bump
     )]
     pub decision_log: Account<'info, DecisionLog>,
+    /// Created on the agent's first decision; nonce sequencing is checked in the handler
+    #[account(
+        init_if_needed,
+        payer = agent_authority,
+        space = PROOF_CURSOR_SIZE,
+        seeds = [
+            b"proof_cursor",
+            args.agent_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub proof_cursor: Account<'info, ProofCursor>,
     #[account(mut)]
     pub agent_authority: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -457,6 +577,19 @@ pub struct CloseExpiredBatch<'info> {
     pub rent_destination: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct VerifyDecisionInBatch<'info> {
+    #[account(
+        seeds = [
+            b"batch",
+            batch_proof.agent_id.to_le_bytes().as_ref(),
+            batch_proof.batch_nonce.to_le_bytes().as_ref(),
+        ],
+        bump = batch_proof.bump,
+    )]
+    pub batch_proof: Account<'info, BatchProof>,
+}
+
 // ──────────────────────────────────────────────
 // Events
 // ──────────────────────────────────────────────
@@ -476,6 +609,8 @@ pub struct DecisionLogged {
     pub evidence_families_bitmap: u8,
     pub decision_class: u8,
     pub is_execution_class: bool,
+    pub prev_hash: [u8; 32],
+    pub chain_hash: [u8; 32],
     pub timestamp: i64,
 }
 
@@ -499,6 +634,17 @@ pub struct BatchProofSubmitted {
     pub submitted_at: i64,
 }
 
+#[event]
+pub struct DecisionInclusionVerified {
+    pub agent_id: u16,
+    pub batch_nonce: u64,
+    pub merkle_root: [u8; 32],
+    pub decision_hash: [u8; 32],
+    pub leaf_index: u32,
+    pub included: bool,
+    pub timestamp: i64,
+}
+
 // ──────────────────────────────────────────────
 // Errors
 // ──────────────────────────────────────────────
@@ -527,4 +673,6 @@ pub enum ProofError {
     Unauthorized,
     #[msg("Math overflow")]
     MathOverflow,
+    #[msg("Decision nonce must be exactly the agent's last nonce + 1")]
+    NonceOutOfSequence,
 }
@@ -55,10 +55,8 @@ pub mod noumen_service {
         require!(service_tier <= 2, NoumenServiceError::InvalidTier);
 
         // A0-8: price >= cost * 12000 / 10000 (i.e., cost + 20% margin minimum)
-        let min_price = cost_lamports
-            .checked_mul(PRICE_MARGIN_MULTIPLIER_BPS as u64)
-            .ok_or(NoumenServiceError::MathOverflow)?
-            .checked_div(10_000)
+        // Rounded up so the floor never undershoots cost * 1.2 by truncation
+        let min_price = apply_bps(cost_lamports, PRICE_MARGIN_MULTIPLIER_BPS, Rounding::Up)
             .ok_or(NoumenServiceError::MathOverflow)?;
 
         require!(
@@ -118,10 +116,7 @@ pub mod noumen_service {
         // H-SVC-1: Authority check moved to account constraint (has_one = aeon_authority)
 
         // A0-8: revalidate margin
-        let min_price = new_cost
-            .checked_mul(PRICE_MARGIN_MULTIPLIER_BPS as u64)
-            .ok_or(NoumenServiceError::MathOverflow)?
-            .checked_div(10_000)
+        let min_price = apply_bps(new_cost, PRICE_MARGIN_MULTIPLIER_BPS, Rounding::Up)
             .ok_or(NoumenServiceError::MathOverflow)?;
 
         require!(
@@ -8,11 +8,13 @@
 // - C3: Deterministic Airdrop (on-chain usage only)
 // - C4: Burn Budget (reserve-protected buyback)
 //
-// Integration: Add `pub mod economic_engine;` to lib.rs
+// Integration: declared in lib.rs as `pub mod economic_engine;`. All ratios
+// are integer basis points via shared_types::math (no f64 on-chain).
 //
 // ══════════════════════════════════════════════════════════════════════════════
 
 use anchor_lang::prelude::*;
+use shared_types::{apply_bps, Rounding, BPS_DENOMINATOR};
 
 // ──────────────────────────────────────────────
 // C1: COST ORACLE SYSTEM
@@ -185,19 +187,25 @@ impl StakingTier {
     /// Seeds: [b"staking_tier", user_wallet.as_ref()]
     pub const SEEDS: &'static [u8] = b"staking_tier";
 
-    /// Tier multipliers for credit allocation
+    /// Tier multipliers for credit allocation, in basis points (10_000 = 1.0x)
     /// Example: Diamond (tier 5) gets 5x credits vs Bronze (tier 1)
-    pub fn get_tier_multiplier(tier: u8) -> f64 {
+    pub fn get_tier_multiplier_bps(tier: u8) -> u16 {
         match tier {
-            0 => 0.0,  // None
-            1 => 1.0,  // Bronze
-            2 => 1.5,  // Silver
-            3 => 2.5,  // Gold
-            4 => 4.0,  // Platinum
-            5 => 5.0,  // Diamond
-            _ => 1.0,
+            0 => 0,       // None
+            1 => 10_000,  // Bronze   1.0x
+            2 => 15_000,  // Silver   1.5x
+            3 => 25_000,  // Gold     2.5x
+            4 => 40_000,  // Platinum 4.0x
+            5 => 50_000,  // Diamond  5.0x
+            _ => 10_000,
         }
     }
+
+    /// Monthly credits for a base allocation scaled by the tier multiplier.
+    /// Rounded down: credits are a payout.
+    pub fn scaled_monthly_credits(base_credit_lamports: u64, tier: u8) -> Option<u64> {
+        apply_bps(base_credit_lamports, Self::get_tier_multiplier_bps(tier), Rounding::Down)
+    }
 }
 
 // ──────────────────────────────────────────────
@@ -271,11 +279,22 @@ impl AirdropEligibility {
     pub const POINTS_PRO: u64 = 100;
     pub const POINTS_INSTITUTIONAL: u64 = 1000;
 
-    /// Seniority bonus: +10% per 90 days since first_seen
-    pub fn calculate_seniority_bonus(&self, now: i64) -> f64 {
-        let days_since_first = (now - self.first_seen_timestamp) / 86400;
-        let quarters = days_since_first / 90;
-        1.0 + (quarters as f64 * 0.10).min(0.50) // Cap at 50% bonus
+    /// Seniority bonus per full 90 days since first_seen (+10%)
+    pub const SENIORITY_BONUS_PER_QUARTER_BPS: u64 = 1_000;
+
+    /// Maximum seniority bonus (+50%)
+    pub const SENIORITY_BONUS_CAP_BPS: u64 = 5_000;
+
+    /// Seniority multiplier in basis points: 10_000 (1.0x) + 10% per 90 days
+    /// since first_seen, capped at 15_000 (1.5x). Clock skew before
+    /// first_seen yields no bonus.
+    pub fn calculate_seniority_bonus_bps(&self, now: i64) -> u16 {
+        let elapsed = now.saturating_sub(self.first_seen_timestamp).max(0) as u64;
+        let quarters = elapsed / 86400 / 90;
+        let bonus = quarters
+            .saturating_mul(Self::SENIORITY_BONUS_PER_QUARTER_BPS)
+            .min(Self::SENIORITY_BONUS_CAP_BPS);
+        (BPS_DENOMINATOR + bonus) as u16
     }
 }
 
@@ -353,37 +372,59 @@ impl BurnBudgetConfig {
 /// Target price: cost × 3.0 (+200% margin). Floor: cost × 2.0 (+100% margin).
 /// These values match the frontend pricing-engine.ts targets.
 pub mod tier_minimums {
-    use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
-
-    pub const BASIC_ANALYSIS: u64 = (LAMPORTS_PER_SOL as f64 * 0.0012) as u64;        // 0.0012 SOL (cost 0.0004 × 3.0)
-    pub const WALLET_SCANNER: u64 = (LAMPORTS_PER_SOL as f64 * 0.009) as u64;         // 0.009 SOL  (cost 0.003 × 3.0)
-    pub const POOL_ANALYZER: u64 = (LAMPORTS_PER_SOL as f64 * 0.0012) as u64;         // 0.0012 SOL (cost 0.0004 × 3.0)
-    pub const TOKEN_DEEP_DIVE: u64 = (LAMPORTS_PER_SOL as f64 * 0.003) as u64;        // 0.003 SOL  (cost 0.001 × 3.0)
-    pub const PROTOCOL_AUDITOR: u64 = (LAMPORTS_PER_SOL as f64 * 0.0024) as u64;      // 0.0024 SOL (cost 0.0008 × 3.0)
-    pub const YIELD_OPTIMIZER: u64 = (LAMPORTS_PER_SOL as f64 * 0.0018) as u64;       // 0.0018 SOL (cost 0.0006 × 3.0)
-    pub const PRO_ANALYSIS: u64 = (LAMPORTS_PER_SOL as f64 * 0.012) as u64;           // 0.012 SOL  (cost 0.004 × 3.0)
-    pub const INSTITUTIONAL: u64 = (LAMPORTS_PER_SOL as f64 * 0.09) as u64;           // 0.09 SOL   (cost 0.03 × 3.0)
+    use shared_types::LAMPORTS_PER_SOL;
+
+    /// 0.0001 SOL; all tier minimums are integer multiples of it.
+    const TENTH_MILLI_SOL: u64 = LAMPORTS_PER_SOL / 10_000;
+
+    pub const BASIC_ANALYSIS: u64 = 12 * TENTH_MILLI_SOL;    // 0.0012 SOL (cost 0.0004 × 3.0)
+    pub const WALLET_SCANNER: u64 = 90 * TENTH_MILLI_SOL;    // 0.009 SOL  (cost 0.003 × 3.0)
+    pub const POOL_ANALYZER: u64 = 12 * TENTH_MILLI_SOL;     // 0.0012 SOL (cost 0.0004 × 3.0)
+    pub const TOKEN_DEEP_DIVE: u64 = 30 * TENTH_MILLI_SOL;   // 0.003 SOL  (cost 0.001 × 3.0)
+    pub const PROTOCOL_AUDITOR: u64 = 24 * TENTH_MILLI_SOL;  // 0.0024 SOL (cost 0.0008 × 3.0)
+    pub const YIELD_OPTIMIZER: u64 = 18 * TENTH_MILLI_SOL;   // 0.0018 SOL (cost 0.0006 × 3.0)
+    pub const PRO_ANALYSIS: u64 = 120 * TENTH_MILLI_SOL;     // 0.012 SOL  (cost 0.004 × 3.0)
+    pub const INSTITUTIONAL: u64 = 900 * TENTH_MILLI_SOL;    // 0.09 SOL   (cost 0.03 × 3.0)
 }
 
 // ──────────────────────────────────────────────
 // VOLUME SCALING MULTIPLIERS
 // ──────────────────────────────────────────────
 
-/// Calculate volume discount multiplier based on monthly query count.
+/// Multiplier per volume discount tier, in basis points (10_000 = full price).
+pub const VOLUME_TIER_MULTIPLIER_BPS: [u16; 4] = [
+    10_000, // tier 0: no discount
+    9_000,  // tier 1: 10% discount
+    8_000,  // tier 2: 20% discount
+    7_000,  // tier 3: 30% discount (max)
+];
+
+/// Volume discount tier for a monthly query count: tier 0 below 10 queries,
+/// 1 below 50, 2 below 100, 3 from then on. The single source of the tier
+/// thresholds, for pricing and for VolumeDiscountTracker.current_discount_tier.
 /// Tiers aligned with frontend pricing-engine.ts.
-/// Never reduces margin below cost × 2.0 (+100% floor).
-pub fn calculate_volume_multiplier(monthly_queries: u32) -> f64 {
-    if monthly_queries < 10 {
-        1.0   // No discount
-    } else if monthly_queries < 50 {
-        0.90  // 10% discount
-    } else if monthly_queries < 100 {
-        0.80  // 20% discount
-    } else {
-        0.70  // 30% discount (max)
+pub fn volume_discount_tier(monthly_queries: u32) -> u8 {
+    match monthly_queries {
+        0..=9 => 0,
+        10..=49 => 1,
+        50..=99 => 2,
+        _ => 3,
     }
 }
 
+/// Calculate volume discount multiplier based on monthly query count,
+/// in basis points (10_000 = full price).
+/// Never reduces margin below cost × 2.0 (+100% floor).
+pub fn calculate_volume_multiplier_bps(monthly_queries: u32) -> u16 {
+    VOLUME_TIER_MULTIPLIER_BPS[volume_discount_tier(monthly_queries) as usize]
+}
+
+/// Applies the volume multiplier to a list price. Rounded up so a discount
+/// never takes more than its stated percentage off the price.
+pub fn apply_volume_discount(price_lamports: u64, monthly_queries: u32) -> Option<u64> {
+    apply_bps(price_lamports, calculate_volume_multiplier_bps(monthly_queries), Rounding::Up)
+}
+
 // ──────────────────────────────────────────────
 // EVENTS
 // ──────────────────────────────────────────────
@@ -433,15 +474,141 @@ pub struct BurnExecuted {
     pub timestamp: i64,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn eligibility(first_seen_timestamp: i64) -> AirdropEligibility {
+        AirdropEligibility {
+            user_wallet: Pubkey::default(),
+            total_points: 0,
+            first_seen_timestamp,
+            total_payments_lamports: 0,
+            proof_count: 0,
+            unique_services_used: 0,
+            allocation_amount: 0,
+            has_claimed: false,
+            claimed_at: 0,
+            advisory_bonus_bps: 0,
+            bump: 0,
+            _reserved: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_tier_multiplier_bps() {
+        assert_eq!(StakingTier::get_tier_multiplier_bps(0), 0);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(1), 10_000);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(2), 15_000);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(3), 25_000);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(4), 40_000);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(5), 50_000);
+        // Unknown tiers fall back to Bronze
+        assert_eq!(StakingTier::get_tier_multiplier_bps(6), 10_000);
+        assert_eq!(StakingTier::get_tier_multiplier_bps(u8::MAX), 10_000);
+    }
+
+    #[test]
+    fn test_scaled_monthly_credits_rounds_down() {
+        assert_eq!(StakingTier::scaled_monthly_credits(1_000, 0), Some(0));
+        assert_eq!(StakingTier::scaled_monthly_credits(1_000, 2), Some(1_500));
+        assert_eq!(StakingTier::scaled_monthly_credits(1_000, 5), Some(5_000));
+        // 3 * 1.5 = 4.5 pays out 4
+        assert_eq!(StakingTier::scaled_monthly_credits(3, 2), Some(4));
+        // 5x of u64::MAX does not fit
+        assert_eq!(StakingTier::scaled_monthly_credits(u64::MAX, 5), None);
+    }
+
+    #[test]
+    fn test_seniority_bonus_per_full_quarter() {
+        let account = eligibility(1_000_000);
+        let now = |days: i64| 1_000_000 + days * DAY;
+        assert_eq!(account.calculate_seniority_bonus_bps(now(0)), 10_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(now(90) - 1), 10_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(now(90)), 11_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(now(179)), 11_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(now(180)), 12_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(now(4 * 90)), 14_000);
+    }
+
+    #[test]
+    fn test_seniority_bonus_cap() {
+        let account = eligibility(0);
+        assert_eq!(account.calculate_seniority_bonus_bps(5 * 90 * DAY), 15_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(6 * 90 * DAY), 15_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(i64::MAX), 15_000);
+        assert_eq!(eligibility(i64::MIN).calculate_seniority_bonus_bps(i64::MAX), 15_000);
+    }
+
+    #[test]
+    fn test_seniority_bonus_negative_clock_skew() {
+        // Clock behind first_seen: no bonus, no underflow
+        let account = eligibility(1_000_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(999_999), 10_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(0), 10_000);
+        assert_eq!(account.calculate_seniority_bonus_bps(i64::MIN), 10_000);
+        assert_eq!(eligibility(i64::MAX).calculate_seniority_bonus_bps(i64::MIN), 10_000);
+    }
+
+    #[test]
+    fn test_volume_discount_tier_boundaries() {
+        assert_eq!(volume_discount_tier(0), 0);
+        assert_eq!(volume_discount_tier(9), 0);
+        assert_eq!(volume_discount_tier(10), 1);
+        assert_eq!(volume_discount_tier(49), 1);
+        assert_eq!(volume_discount_tier(50), 2);
+        assert_eq!(volume_discount_tier(99), 2);
+        assert_eq!(volume_discount_tier(100), 3);
+        assert_eq!(volume_discount_tier(u32::MAX), 3);
+    }
+
+    #[test]
+    fn test_volume_multiplier_bps_boundaries() {
+        for (queries, bps) in [
+            (0, 10_000),
+            (9, 10_000),
+            (10, 9_000),
+            (49, 9_000),
+            (50, 8_000),
+            (99, 8_000),
+            (100, 7_000),
+            (u32::MAX, 7_000),
+        ] {
+            assert_eq!(calculate_volume_multiplier_bps(queries), bps);
+        }
+    }
+
+    #[test]
+    fn test_apply_volume_discount_rounds_up() {
+        // Exact: 10% off 1 SOL
+        assert_eq!(apply_volume_discount(1_000_000_000, 10), Some(900_000_000));
+        assert_eq!(apply_volume_discount(1_000_000_000, 9), Some(1_000_000_000));
+        // 999 * 0.9 = 899.1, 999 * 0.8 = 799.2, 999 * 0.7 = 699.3: the buyer never
+        // gets more than the stated percentage off
+        assert_eq!(apply_volume_discount(999, 10), Some(900));
+        assert_eq!(apply_volume_discount(999, 50), Some(800));
+        assert_eq!(apply_volume_discount(999, 100), Some(700));
+        // 1 lamport stays 1 lamport at every tier
+        for queries in [0, 10, 50, 100] {
+            assert_eq!(apply_volume_discount(1, queries), Some(1));
+        }
+        assert_eq!(apply_volume_discount(0, 100), Some(0));
+        // Discounts never overflow: the multiplier is at most 1.0x
+        assert_eq!(apply_volume_discount(u64::MAX, 0), Some(u64::MAX));
+        assert_eq!(apply_volume_discount(u64::MAX, 100), Some(12_912_720_851_596_686_131));
+    }
+}
+
 // ══════════════════════════════════════════════════════════════════════════════
 // IMPLEMENTATION NOTES
 // ══════════════════════════════════════════════════════════════════════════════
 //
 // To integrate this module:
 //
-// 1. Add to lib.rs:
+// 1. Module is declared in lib.rs; bring items into scope with:
 //    ```rust
-//    pub mod economic_engine;
 //    use economic_engine::*;
 //    ```
 //
@@ -1,16 +1,94 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::stake::{
+    self as native_stake,
+    state::{Authorized, Lockup, StakeStateV2},
+};
+use anchor_lang::solana_program::sysvar;
 use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
 use shared_types::*;
 
+pub mod economic_engine;
+
 declare_id!("EMNF5A4cpqusBuUajMv3FUzjbwR7GQMFyJ7JDi4FjLFu");
 
 // ──────────────────────────────────────────────
 // Revenue split percentages (basis points) — 3-way on NET revenue
-// 40% Operations + 45% Treasury Reserve + 15% Creator = 100%
+// 40% Operations + CCS creator share (<= 15%, A0-28) + Treasury Reserve (remainder, >= 45%)
 // ──────────────────────────────────────────────
 const OPERATIONS_SPLIT_BPS: u16 = 4000;    // 40%
-const TREASURY_RESERVE_BPS: u16 = 4500;    // 45%
-const CREATOR_SPLIT_BPS: u16 = 1500;       // 15%
+
+// A0-28: CCS band thresholds/percentages are Layer 1 with a 30-day delay
+#[cfg(not(feature = "short-policy-delays"))]
+const CCS_BAND_MIN_DELAY: i64 = 2_592_000; // 30 days
+#[cfg(feature = "short-policy-delays")]
+const CCS_BAND_MIN_DELAY: i64 = 4; // localnet integration builds only
+
+// Reserve release: at most 10% of the reserve (as of the start of the yearly
+// window) can be released per 365 days
+const RESERVE_RELEASE_ANNUAL_CAP_BPS: u16 = 1000; // 10%
+const RESERVE_RELEASE_WINDOW_SECONDS: i64 = 31_536_000; // 365 days
+
+// Native staking: at most half of the reserve can be delegated at any time
+const MAX_STAKED_RESERVE_BPS: u16 = 5000; // 50%
+// Stake config account still required by the stake program's DelegateStake
+#[allow(deprecated)]
+const STAKE_CONFIG_ID: Pubkey = native_stake::config::ID;
+
+// Refunds: a payment can be refunded (and its record is kept) for 7 days
+const REFUND_WINDOW_SECONDS: i64 = 604_800; // 7 days
+
+// SPL rail: between governed rate changes the keeper may track the market rate,
+// at most once per hour and within ±20% of the last governed rate
+const SPL_RATE_UPDATE_INTERVAL_SECONDS: i64 = 3_600; // 1 hour
+const SPL_RATE_KEEPER_BAND_BPS: u16 = 2000; // 20%
+
+// Creator share vests linearly over 7 days from the latest accrual, so the
+// share of a refundable payment is still clawable inside the refund window
+const CREATOR_STREAM_SECONDS: i64 = 604_800; // 7 days
+// A proposed creator wallet can be accepted only after 72 hours
+const CREATOR_WALLET_ROTATION_DELAY: i64 = 259_200; // 72 hours
+
+// A0-24: a donation within 3 days of a service payment from the same wallet
+// is treated as a possible disguised payment
+const DONATION_CORRELATION_WINDOW_SECONDS: i64 = 259_200; // 3 days
+
+// Donation receipt archival: receipts are kept at least 30 days (well past the
+// A0-24 correlation window) before being folded into the archive Merkle tree
+const MIN_RECEIPT_RETENTION_SECONDS: i64 = 2_592_000; // 30 days
+const ARCHIVE_TREE_DEPTH: usize = 20; // up to 2^20 - 1 archived receipts
+
+// Subscriptions: billing periods of at least one day and a grace of at least
+// one hour, so a due period can always be charged; Subscription.status values
+const MIN_SUBSCRIPTION_PERIOD_SECONDS: i64 = 86_400;
+const MIN_SUBSCRIPTION_GRACE_SECONDS: i64 = 3_600;
+const SUBSCRIPTION_ACTIVE: u8 = 0;
+const SUBSCRIPTION_PAST_DUE: u8 = 1;
+const SUBSCRIPTION_LAPSED: u8 = 2;
+const SUBSCRIPTION_CANCELLED: u8 = 3;
+
+// Referrals: the governed share is a bps of the payment, carved from the
+// operations bucket, and is further bounded by that bucket at payment time
+const MAX_REFERRAL_SHARE_BPS: u16 = 1000; // 10%
+
+// Revenue attribution: at most half of a payment's operations share can be
+// credited to the owning agent's budget
+const MAX_AGENT_REVENUE_SHARE_BPS: u16 = 5000; // 50% of operations
+
+// Emergency evacuation: armed while noumen-core is Halted, executable 1 hour later
+#[cfg(not(feature = "short-policy-delays"))]
+const EVACUATION_DELAY_SECONDS: i64 = 3_600; // 1 hour
+#[cfg(feature = "short-policy-delays")]
+const EVACUATION_DELAY_SECONDS: i64 = 2; // localnet integration builds only
+
+// Treasury statements: a period can be closed once it is 30 days old
+const TREASURY_PERIOD_SECONDS: i64 = 2_592_000; // 30 days
+
+// Rolling revenue history: 30 completed days + the current (partial) day
+const SECONDS_PER_DAY: i64 = 86_400;
+const REVENUE_HISTORY_DAYS: usize = 31;
 
 // ──────────────────────────────────────────────
 // Program
@@ -38,8 +116,18 @@ pub mod noumen_treasury {
         config.keeper_authority = keeper_authority;
         config.creator_wallet = creator_wallet;
         config.is_initialized = true;
+        config.pending_creator_wallet = Pubkey::default();
+        config.creator_wallet_change_at = 0;
+        config.referral_share_bps = 0;
+        config.referral_lifetime_cap_lamports = 0;
+        config.agent_revenue_share_bps = 0;
+        config.cold_wallet = Pubkey::default();
+        config.evacuation_unlocks_at = 0;
+        config.evacuated_at = 0;
+        config.evacuation_epoch = 0;
+        config.last_evacuation_at = 0;
         config.bump = ctx.bumps.treasury_config;
-        config._reserved = [0u8; 32];
+        config._reserved = [0u8; 20];
 
         // --- TreasuryVault ---
         let vault = &mut ctx.accounts.treasury_vault;
@@ -53,6 +141,29 @@ pub mod noumen_treasury {
         vault.total_donations_swept = 0;
         vault.operations_lamports = 0;
         vault.updated_at = now;
+        vault.total_budget_outstanding = 0;
+        vault.budget_outstanding_by_type = [0u64; 4];
+        vault.reserve_release_window_start = now;
+        vault.reserve_release_window_base = 0;
+        vault.reserve_released_in_window = 0;
+        vault.total_reserve_released = 0;
+        vault.unattributed_inflow_lamports = 0;
+        vault.deficit_lamports = 0;
+        vault.last_reconciled_at = now;
+        vault.total_operational_expenses = 0;
+        vault.staked_lamports = 0;
+        vault.total_stake_rewards = 0;
+        vault.stake_account_count = 0;
+        vault.creator_escrow_lamports = 0;
+        vault.total_budget_disbursed = 0;
+        vault.total_revenue_refunded = 0;
+        vault.statement_count = 0;
+        vault.period_started_at = now;
+        vault.last_statement_hash = [0u8; 32];
+        vault.period_snapshot = TreasurySnapshot::default();
+        vault.referral_payable_lamports = 0;
+        vault.total_referral_paid = 0;
+        vault.total_evacuated_lamports = 0;
         vault.bump = ctx.bumps.treasury_vault;
         vault._reserved = [0u8; 48];
 
@@ -68,7 +179,8 @@ pub mod noumen_treasury {
         Ok(())
     }
 
-    /// Step 2: Initializes DonationVault and CCSConfig with genesis bands.
+    /// Step 2: Initializes DonationVault, CCSConfig with genesis bands, and the
+    /// RevenueHistory ring buffer that feeds the CCS rolling averages.
     /// Must be called after initialize_treasury.
     /// Signer: super_authority (same as initialize_treasury).
     pub fn initialize_donations(
@@ -80,8 +192,17 @@ pub mod noumen_treasury {
         donation.pending_sweep = 0;
         donation.sweep_count = 0;
         donation.last_sweep_at = 0;
+        donation.receipt_count = 0;
+        donation.flagged_count = 0;
+        donation.flagged_amount = 0;
+        donation.receipt_retention_seconds = 0;
+        donation.archived_receipt_count = 0;
+        donation.archived_receipts_root = DonationVault::empty_archive_root();
+        donation.archive_frontier = [[0u8; 32]; ARCHIVE_TREE_DEPTH];
+        donation.pending_flagged = 0;
+        donation.swept_receipt_count = 0;
         donation.bump = ctx.bumps.donation_vault;
-        donation._reserved = [0u8; 21];
+        donation._reserved = [0u8; 5];
 
         // --- CCSConfig with genesis bands ---
         let ccs = &mut ctx.accounts.ccs_config;
@@ -120,849 +241,7303 @@ pub mod noumen_treasury {
         ccs.avg_30d_revenue = 0;
         ccs.total_creator_paid = 0;
         ccs.creator_accumulated = 0;
+        ccs.creator_unvested = 0;
+        ccs.creator_stream_updated_at = Clock::get()?.unix_timestamp;
+        ccs.creator_stream_end = 0;
+        ccs.total_creator_accrued = 0;
         ccs.bump = ctx.bumps.ccs_config;
-        ccs._reserved = [0u8; 48];
+        ccs._reserved = [0u8; 16];
+
+        // --- RevenueHistory ---
+        let today = Clock::get()?.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+        let history = &mut ctx.accounts.revenue_history;
+        history.daily_revenue = [0u64; REVENUE_HISTORY_DAYS];
+        history.daily_donations = [0u64; REVENUE_HISTORY_DAYS];
+        history.current_day = today;
+        history.first_day = today;
+        history.bump = ctx.bumps.revenue_history;
+        history._reserved = [0u8; 32];
 
         msg!("noumen_treasury: donations + CCS initialized");
         Ok(())
     }
 
-    /// Processes an incoming service payment with a 4-way revenue split:
+    /// Processes an incoming service payment with a 3-way revenue split:
     ///   40% Operations (stays in vault, tracked separately)
-    ///   30% Treasury Reserve (marked as reserved in vault)
-    ///   15% Development Fund (stays in vault, tracked separately)
-    ///   15% Creator wallet (transferred via CPI)
-    /// The creator split uses the FIXED 15% (CREATOR_SPLIT_BPS), not the CCS band system.
-    /// CCS bands remain available for additional creator compensation mechanics.
+    ///   CCS creator share (escrowed in vault, streamed to the creator): base split
+    ///     of the band selected by avg_7d_revenue plus a performance stipend,
+    ///     capped at cap_total_bps (A0-28)
+    ///   Treasury Reserve: remainder (marked as reserved in vault)
+    /// The whole payment is transferred into treasury_vault.
+    /// The service must be Active in the noumen-service catalog. The price is the
+    /// ServiceEntry list price with the payer's volume discount applied, never
+    /// below min_price_lamports (A0-8), and `amount_lamports` must match it exactly.
+    /// The payer's VolumeDiscountTracker is updated in the same instruction, so
+    /// discounts always reflect paid usage. Every payment creates the
+    /// PaymentRecord keyed by (payer, payment_nonce) at the payer's expense (rent
+    /// returned by close_payment_record), so it is refundable within the refund
+    /// window and can be correlated with a donation (A0-24).
+    /// An optional registered referrer (never the payer) accrues the governed
+    /// referral share, carved out of the operations bucket and bounded by its
+    /// lifetime cap; it is paid out later by claim_referral_rewards.
+    /// When the owning agent has a BudgetAllocation, the payment is attributed
+    /// to that agent and agent_revenue_share_bps of the operations share is
+    /// added to its allocation. The budget PDA is always passed, so attribution
+    /// cannot be skipped. The credit stays in operations_lamports and is backed
+    /// by this payment, so it skips the allocation caps.
     /// Signer: payer (any external user paying for a service).
     pub fn process_service_payment(
         ctx: Context<ProcessServicePayment>,
         service_id: u16,
         amount_lamports: u64,
+        payment_nonce: u64,
     ) -> Result<()> {
-        require!(amount_lamports > 0, TreasuryError::ZeroAmount);
-
-        // ── 3-way revenue split on NET (basis points) ──
-        // Operations:        40% (4000 bps) — stays in vault for operational use
-        // Treasury Reserve:  45% (4500 bps) — marked as reserved in vault
-        // Creator:           15% (1500 bps) — transferred to creator_wallet via CPI
-
-        let operations_amount = amount_lamports
-            .checked_mul(OPERATIONS_SPLIT_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-
-        let treasury_reserve_amount = amount_lamports
-            .checked_mul(TREASURY_RESERVE_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-
-        // Creator gets the remainder to absorb any truncation dust,
-        // ensuring operations + treasury_reserve + creator == total_amount
-        let vault_total = operations_amount
-            .checked_add(treasury_reserve_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let now = Clock::get()?.unix_timestamp;
 
-        let creator_amount = amount_lamports
-            .checked_sub(vault_total)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let quote = ctx
+            .accounts
+            .volume_tracker
+            .quote_service(&ctx.accounts.service_entry, now)?;
 
-        // Invariant check: operations + treasury_reserve + creator == total_amount
+        require!(amount_lamports > 0, TreasuryError::ZeroAmount);
         require!(
-            operations_amount
-                .checked_add(treasury_reserve_amount)
-                .ok_or(TreasuryError::ArithmeticOverflow)?
-                .checked_add(creator_amount)
-                .ok_or(TreasuryError::ArithmeticOverflow)?
-                == amount_lamports,
-            TreasuryError::SplitMismatch
+            amount_lamports == quote.price_lamports,
+            TreasuryError::PaymentAmountMismatch
         );
 
-        // Transfer creator portion from payer -> creator_wallet via SystemProgram
-        if creator_amount > 0 {
-            system_program::transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.payer.to_account_info(),
-                        to: ctx.accounts.creator_wallet.to_account_info(),
-                    },
-                ),
-                creator_amount,
-            )?;
-        }
+        let mut split = ctx.accounts.ccs_config.split_revenue(amount_lamports)?;
+        let referrer_key = match ctx.accounts.referrer.as_mut() {
+            Some(referrer) => {
+                referrer.settle_evacuated(&ctx.accounts.treasury_config);
+                let link = ctx
+                    .accounts
+                    .referral_link
+                    .as_mut()
+                    .ok_or(TreasuryError::ReferrerMismatch)?;
+                let link_bump = ctx.bumps.referral_link.ok_or(TreasuryError::ReferrerMismatch)?;
+                if link.bind(ctx.accounts.payer.key(), referrer, link_bump, now)? {
+                    emit!(ReferralLinked {
+                        payer: link.payer,
+                        referrer: link.referrer,
+                        timestamp: now,
+                    });
+                }
+                let referral = referrer.referral_share(
+                    amount_lamports,
+                    &ctx.accounts.treasury_config,
+                    split.operations,
+                )?;
+                if referral > 0 {
+                    referrer.record_referral(referral, now)?;
+                    split.carve_referral(referral)?;
+                }
+                referrer.wallet
+            }
+            None => Pubkey::default(),
+        };
+        let operations_amount = split.operations;
+        let treasury_reserve_amount = split.reserve;
+        let creator_amount = split.creator;
 
-        // Transfer vault portion (operations + treasury_reserve) from payer -> treasury_vault PDA
-        if vault_total > 0 {
-            system_program::transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.payer.to_account_info(),
-                        to: ctx.accounts.treasury_vault.to_account_info(),
-                    },
-                ),
-                vault_total,
-            )?;
-        }
+        // Transfer the full payment (operations + reserve + creator escrow) from
+        // payer -> treasury_vault PDA
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.treasury_vault.to_account_info(),
+                },
+            ),
+            amount_lamports,
+        )?;
 
         // Now take mutable references for state updates (after CPI calls)
-        let now = Clock::get()?.unix_timestamp;
-
-        let vault = &mut ctx.accounts.treasury_vault;
-        vault.total_balance_lamports = vault
-            .total_balance_lamports
-            .checked_add(vault_total)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        vault.total_revenue_lifetime = vault
-            .total_revenue_lifetime
-            .checked_add(amount_lamports)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-
-        // Track reserved lamports (treasury reserve portion)
-        vault.reserved_lamports = vault
-            .reserved_lamports
-            .checked_add(treasury_reserve_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-
-        // Track operations separately
-        vault.operations_lamports = vault
-            .operations_lamports
-            .checked_add(operations_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        ctx.accounts
+            .treasury_vault
+            .credit_revenue(amount_lamports, &split, now)?;
+        ctx.accounts
+            .revenue_history
+            .record_revenue(amount_lamports, now)?;
+        ctx.accounts.ccs_config.accrue_creator(creator_amount, now)?;
+
+        let (agent_attributed, agent_credit) = attribute_to_owner(
+            &ctx.accounts.owner_budget.to_account_info(),
+            &ctx.accounts.treasury_config,
+            &mut ctx.accounts.treasury_vault,
+            amount_lamports,
+            operations_amount,
+            now,
+        )?;
 
-        // Recalculate free balance: total - reserved
-        vault.free_balance_lamports = vault
-            .total_balance_lamports
-            .checked_sub(vault.reserved_lamports)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        vault.updated_at = now;
+        let tracker = &mut ctx.accounts.volume_tracker;
+        tracker.record_usage(amount_lamports, now)?;
+
+        let record = &mut ctx.accounts.payment_record;
+        record.payer = ctx.accounts.payer.key();
+        record.nonce = payment_nonce;
+        record.service_id = service_id;
+        record.amount_lamports = amount_lamports;
+        record.operations_split = operations_amount;
+        record.treasury_reserve_split = treasury_reserve_amount;
+        record.creator_split = creator_amount;
+        record.paid_at = now;
+        record.refunded = false;
+        record.refunded_at = 0;
+        record.donation_correlated = false;
+        record.referrer = referrer_key;
+        record.referral_split = split.referral;
+        record.owning_agent_id = quote.owning_agent_id;
+        record.agent_attributed = agent_attributed;
+        record.agent_credit = agent_credit;
+        record.bump = ctx.bumps.payment_record;
+        record._reserved = [0u8; 32];
 
-        // Update CCS creator accumulated (tracks total creator payouts)
-        let ccs = &mut ctx.accounts.ccs_config;
-        ccs.total_creator_paid = ccs
-            .total_creator_paid
-            .checked_add(creator_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        ccs.creator_accumulated = ccs
-            .creator_accumulated
-            .checked_add(creator_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        emit!(VolumeDiscountUpdated {
+            wallet: tracker.wallet,
+            service_id,
+            monthly_scans: tracker.monthly_scan_count,
+            discount_tier: tracker.current_discount_tier,
+            timestamp: now,
+        });
 
         emit!(ServicePaymentProcessed {
             service_id,
+            payment_nonce,
             amount_lamports,
+            list_price_lamports: quote.list_price_lamports,
+            discount_tier: quote.discount_tier,
+            service_tier: quote.service_tier,
+            owning_agent_id: quote.owning_agent_id,
             creator_split: creator_amount,
             treasury_reserve_split: treasury_reserve_amount,
             operations_split: operations_amount,
+            referral_split: split.referral,
+            referrer: referrer_key,
+            agent_credit,
+            ccs_band: split.band,
+            creator_base_bps: split.base_bps,
+            creator_stipend_bps: split.stipend_bps,
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Allocates a budget to a specific agent. The allocated amount cannot exceed
-    /// 15% (AGENT_BUDGET_CAP_BPS) of the treasury's current free balance.
-    /// Signer: aeon_authority.
-    pub fn allocate_agent_budget(
-        ctx: Context<AllocateAgentBudget>,
-        agent_id: u16,
-        allocated: u64,
-        daily_cap: u64,
+    /// Refunds a SOL service payment that failed or was disputed, within
+    /// REFUND_WINDOW_SECONDS of payment. Reverses the operations and reserve
+    /// buckets from the vault. The creator share is clawed back from the creator
+    /// stream (unvested first, then vested but unwithdrawn) as far as it is still
+    /// in escrow; whatever the creator has already withdrawn is not refunded.
+    /// A referral share is likewise clawed back from the referrer's unclaimed
+    /// accrual; the referrer account is then required. A budget credit to the
+    /// owning agent is reversed as far as it is still undisbursed, unless the
+    /// agent's budget has been closed since. The refunded call is taken back out of
+    /// the payer's VolumeDiscountTracker, so it no longer earns a discount.
+    /// Signer: keeper_authority or the noumen-auditor authority.
+    pub fn refund_service_payment(
+        ctx: Context<RefundServicePayment>,
+        reason_hash: [u8; 32],
     ) -> Result<()> {
-        // Validate: allocated <= free_balance * AGENT_BUDGET_CAP_BPS / 10000
-        let free_balance = ctx.accounts.treasury_vault.free_balance_lamports;
-        let max_allocation = free_balance
-            .checked_mul(AGENT_BUDGET_CAP_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        ctx.accounts.treasury_vault.require_no_deficit()?;
+        let now = Clock::get()?.unix_timestamp;
+        let record = &ctx.accounts.payment_record;
 
+        require!(!record.refunded, TreasuryError::PaymentAlreadyRefunded);
         require!(
-            allocated <= max_allocation,
-            TreasuryError::AgentBudgetCapExceeded
+            record.paid_at > ctx.accounts.treasury_config.last_evacuation_at,
+            TreasuryError::SettledByEvacuation
         );
+        let window_end = record
+            .paid_at
+            .checked_add(REFUND_WINDOW_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(now <= window_end, TreasuryError::RefundWindowExpired);
 
-        let now = Clock::get()?.unix_timestamp;
+        let amount_lamports = record.amount_lamports;
+        let operations_reversed = record.operations_split;
+        let reserve_reversed = record.treasury_reserve_split;
+        let creator_split = record.creator_split;
 
-        let budget = &mut ctx.accounts.budget_allocation;
-        budget.agent_id = agent_id;
-        budget.allocated = allocated;
-        budget.spent = 0;
-        budget.daily_cap = daily_cap;
-        budget.daily_spent = 0;
-        budget.daily_reset_at = now;
-        budget.updated_at = now;
-        budget.bump = ctx.bumps.budget_allocation;
-        budget._reserved = [0u8; 32];
+        let creator_clawed_back = ctx
+            .accounts
+            .ccs_config
+            .claw_back_creator(creator_split, now)?;
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.reverse_revenue(
+            amount_lamports,
+            operations_reversed,
+            reserve_reversed,
+            creator_clawed_back,
+            now,
+        )?;
 
-        emit!(BudgetAllocated {
-            agent_id,
-            allocated,
-            daily_cap,
+        let referral_split = ctx.accounts.payment_record.referral_split;
+        let referral_clawed_back = if referral_split > 0 {
+            let referrer = ctx
+                .accounts
+                .referrer
+                .as_mut()
+                .ok_or(TreasuryError::ReferrerMismatch)?;
+            referrer.claw_back(referral_split, now)
+        } else {
+            0
+        };
+        vault.referral_payable_lamports = vault
+            .referral_payable_lamports
+            .checked_sub(referral_clawed_back)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        let agent_credit_reversed = reverse_owner_attribution(
+            &ctx.accounts.owner_budget.to_account_info(),
+            vault,
+            ctx.accounts.payment_record.agent_attributed,
+            amount_lamports,
+            ctx.accounts.payment_record.agent_credit,
+            now,
+        )?;
+
+        let refund_amount = operations_reversed
+            .checked_add(reserve_reversed)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(creator_clawed_back)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(referral_clawed_back)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        if refund_amount > 0 {
+            transfer_from_pda(
+                &ctx.accounts.treasury_vault.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                refund_amount,
+            )?;
+        }
+
+        let record = &mut ctx.accounts.payment_record;
+        ctx.accounts
+            .revenue_history
+            .reverse_revenue(record.amount_lamports, record.paid_at, now);
+        record.refunded = true;
+        record.refunded_at = now;
+
+        let tracker = &mut ctx.accounts.volume_tracker;
+        tracker.reverse_usage(record.amount_lamports, record.paid_at, now)?;
+
+        emit!(VolumeDiscountUpdated {
+            wallet: tracker.wallet,
+            service_id: record.service_id,
+            monthly_scans: tracker.monthly_scan_count,
+            discount_tier: tracker.current_discount_tier,
+            timestamp: now,
+        });
+
+        emit!(ServicePaymentRefunded {
+            payer: record.payer,
+            payment_nonce: record.nonce,
+            service_id: record.service_id,
+            amount_lamports: record.amount_lamports,
+            refund_lamports: refund_amount,
+            operations_reversed,
+            reserve_reversed,
+            creator_clawed_back,
+            referral_clawed_back,
+            agent_credit_reversed,
+            reason_hash,
+            refunded_by: ctx.accounts.authority.key(),
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Sweeps ALL pending SOL from DonationVault PDA to TreasuryVault PDA.
-    /// No CCS split applied (A0-24: donations confer no rights/priority/influence).
-    /// Signer: keeper_authority.
-    pub fn sweep_donations(ctx: Context<SweepDonations>) -> Result<()> {
-        let sweep_amount = ctx.accounts.donation_vault.pending_sweep;
-        require!(sweep_amount > 0, TreasuryError::NothingToSweep);
+    /// Closes a PaymentRecord once its refund window has passed, returning the
+    /// rent to the original payer. Permissionless crank.
+    pub fn close_payment_record(ctx: Context<ClosePaymentRecord>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let record = &ctx.accounts.payment_record;
+        let window_end = record
+            .paid_at
+            .checked_add(REFUND_WINDOW_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(now > window_end, TreasuryError::RefundWindowOpen);
+
+        emit!(PaymentRecordClosed {
+            payer: record.payer,
+            payment_nonce: record.nonce,
+            refunded: record.refunded,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Opens a prepaid balance for API-style usage. The PrepaidBalance PDA holds
+    /// the deposited lamports until they are drawn per query or withdrawn.
+    /// Signer: user (self-funded).
+    pub fn open_prepaid_balance(
+        ctx: Context<OpenPrepaidBalance>,
+        daily_ceiling_lamports: u64,
+    ) -> Result<()> {
+        require!(daily_ceiling_lamports > 0, TreasuryError::ZeroAmount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let prepaid = &mut ctx.accounts.prepaid_balance;
+        prepaid.user = ctx.accounts.user.key();
+        prepaid.balance_lamports = 0;
+        prepaid.daily_ceiling_lamports = daily_ceiling_lamports;
+        prepaid.daily_drawn_lamports = 0;
+        prepaid.daily_reset_at = now;
+        prepaid.total_deposited = 0;
+        prepaid.total_drawn = 0;
+        prepaid.total_withdrawn = 0;
+        prepaid.created_at = now;
+        prepaid.updated_at = now;
+        prepaid.bump = ctx.bumps.prepaid_balance;
+        prepaid._reserved = [0u8; 32];
+
+        emit!(PrepaidBalanceOpened {
+            user: prepaid.user,
+            daily_ceiling_lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
 
-        // Transfer SOL from donation_vault PDA -> treasury_vault PDA
-        // PDA signing with donation_vault seeds
-        let donation_bump = ctx.accounts.donation_vault.bump;
-        let seeds: &[&[u8]] = &[b"donation_vault", &[donation_bump]];
-        let signer_seeds: &[&[&[u8]]] = &[seeds];
+    /// Deposits SOL from the user into their PrepaidBalance PDA.
+    /// Signer: user.
+    pub fn deposit_prepaid(ctx: Context<DepositPrepaid>, amount: u64) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
 
         system_program::transfer(
-            CpiContext::new_with_signer(
+            CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
                 system_program::Transfer {
-                    from: ctx.accounts.donation_vault.to_account_info(),
-                    to: ctx.accounts.treasury_vault.to_account_info(),
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.prepaid_balance.to_account_info(),
                 },
-                signer_seeds,
             ),
-            sweep_amount,
+            amount,
         )?;
 
         let now = Clock::get()?.unix_timestamp;
-
-        // Update donation vault (mutable borrow after CPI)
-        let donation = &mut ctx.accounts.donation_vault;
-        donation.pending_sweep = 0;
-        donation.sweep_count = donation
-            .sweep_count
-            .checked_add(1)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        donation.last_sweep_at = now;
-
-        // Update treasury vault - donations go to total balance but do NOT
-        // count as revenue (they confer no CCS split)
-        let vault = &mut ctx.accounts.treasury_vault;
-        vault.total_balance_lamports = vault
-            .total_balance_lamports
-            .checked_add(sweep_amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        vault.total_donations_swept = vault
-            .total_donations_swept
-            .checked_add(sweep_amount)
+        let prepaid = &mut ctx.accounts.prepaid_balance;
+        prepaid.balance_lamports = prepaid
+            .balance_lamports
+            .checked_add(amount)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-
-        // Recalculate free balance
-        vault.free_balance_lamports = vault
-            .total_balance_lamports
-            .checked_sub(vault.reserved_lamports)
+        prepaid.total_deposited = prepaid
+            .total_deposited
+            .checked_add(amount)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-        vault.updated_at = now;
+        prepaid.updated_at = now;
 
-        emit!(DonationSwept {
-            amount: sweep_amount,
-            sweep_count: ctx.accounts.donation_vault.sweep_count,
+        emit!(PrepaidDeposited {
+            user: prepaid.user,
+            amount,
+            balance_lamports: prepaid.balance_lamports,
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Records a donation receipt with a hashed wallet address (A0-27 privacy).
-    /// Signer: keeper_authority.
-    pub fn record_donation_receipt(
-        ctx: Context<RecordDonationReceipt>,
-        nonce: u64,
-        amount: u64,
-        source_wallet_hash: [u8; 32],
-        disclosure_mode: u8,
-        receipt_hash: [u8; 32],
-    ) -> Result<()> {
+    /// Withdraws unused prepaid balance back to the user.
+    /// Signer: user.
+    pub fn withdraw_prepaid(ctx: Context<WithdrawPrepaid>, amount: u64) -> Result<()> {
         require!(amount > 0, TreasuryError::ZeroAmount);
+        require!(
+            amount <= ctx.accounts.prepaid_balance.balance_lamports,
+            TreasuryError::InsufficientPrepaidBalance
+        );
 
-        let now = Clock::get()?.unix_timestamp;
-
-        let receipt = &mut ctx.accounts.donation_receipt;
-        receipt.nonce = nonce;
-        receipt.amount = amount;
-        receipt.timestamp = now;
-        receipt.source_wallet_hash = source_wallet_hash;
-        receipt.disclosure_mode = disclosure_mode;
-        receipt.receipt_status = 0; // Active
-        receipt.counts_as_donation = true;
-        receipt.correlated_payments = 0;
-        receipt.correlated_flag = false;
-        receipt.receipt_hash = receipt_hash;
-        receipt.bump = ctx.bumps.donation_receipt;
-        receipt._reserved = [0u8; 42];
+        transfer_from_pda(
+            &ctx.accounts.prepaid_balance.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            amount,
+        )?;
 
-        // Update donation vault pending amount
-        let donation = &mut ctx.accounts.donation_vault;
-        donation.total_received = donation
-            .total_received
-            .checked_add(amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
-        donation.pending_sweep = donation
-            .pending_sweep
+        let now = Clock::get()?.unix_timestamp;
+        let prepaid = &mut ctx.accounts.prepaid_balance;
+        prepaid.balance_lamports -= amount;
+        prepaid.total_withdrawn = prepaid
+            .total_withdrawn
             .checked_add(amount)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
+        prepaid.updated_at = now;
 
-        emit!(DonationReceiptCreated {
-            nonce,
+        emit!(PrepaidWithdrawn {
+            user: prepaid.user,
             amount,
-            disclosure_mode,
+            balance_lamports: prepaid.balance_lamports,
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Withdraws accumulated creator CCS split from treasury vault.
-    /// Validates that withdrawal does not breach the 25% reserve ratio (A0-3).
-    /// Signer: creator_wallet.
-    pub fn withdraw_creator_split(
-        ctx: Context<WithdrawCreatorSplit>,
-        amount: u64,
+    /// Sets the user's daily drawdown ceiling. Takes effect immediately.
+    /// Signer: user.
+    pub fn set_prepaid_daily_ceiling(
+        ctx: Context<SetPrepaidDailyCeiling>,
+        daily_ceiling_lamports: u64,
     ) -> Result<()> {
-        require!(amount > 0, TreasuryError::ZeroAmount);
+        require!(daily_ceiling_lamports > 0, TreasuryError::ZeroAmount);
 
-        // Read state for validations before CPI
-        let creator_accumulated = ctx.accounts.ccs_config.creator_accumulated;
-        let total_balance = ctx.accounts.treasury_vault.total_balance_lamports;
-        let reserved = ctx.accounts.treasury_vault.reserved_lamports;
-        let free_balance = ctx.accounts.treasury_vault.free_balance_lamports;
-        let vault_bump = ctx.accounts.treasury_vault.bump;
+        let now = Clock::get()?.unix_timestamp;
+        let prepaid = &mut ctx.accounts.prepaid_balance;
+        prepaid.daily_ceiling_lamports = daily_ceiling_lamports;
+        prepaid.updated_at = now;
 
-        // Validate amount <= creator_accumulated
-        require!(
-            amount <= creator_accumulated,
-            TreasuryError::InsufficientCreatorBalance
-        );
+        emit!(PrepaidDailyCeilingUpdated {
+            user: prepaid.user,
+            daily_ceiling_lamports,
+            timestamp: now,
+        });
 
-        // Validate reserve ratio after withdrawal.
-        // After withdrawal, remaining balance = total_balance - amount.
-        // reserved_lamports must be >= (total_balance - amount) * RESERVE_RATIO_BPS / 10000
-        let balance_after = total_balance
-            .checked_sub(amount)
-            .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+        Ok(())
+    }
 
-        let required_reserve = balance_after
-            .checked_mul(RESERVE_RATIO_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+    /// Debits one query from a user's prepaid balance. Priced exactly like
+    /// process_service_payment (Active service, volume discount, A0-8 floor) and
+    /// split the same way: the whole amount moves to treasury_vault, with the
+    /// creator share escrowed and streamed. Bounded by the user's daily drawdown
+    /// ceiling. The drawer attests `query_id` (e.g. the hash of the off-chain
+    /// request); the PrepaidDraw PDA keyed by (user, query_id) bills each query
+    /// once and keeps the split for refund_prepaid_draw. The drawer pays its
+    /// rent, returned by close_prepaid_draw. An agent authority can only draw
+    /// for services its own agent owns. Like a service payment, the draw is
+    /// attributed to the owning agent's budget when it has one.
+    /// Signer: keeper_authority or the authority of the service's owning agent.
+    pub fn draw_prepaid_query(
+        ctx: Context<DrawPrepaidQuery>,
+        service_id: u16,
+        query_id: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
 
-        require!(
-            reserved >= required_reserve,
-            TreasuryError::ReserveRatioBreach
-        );
+        let quote = ctx
+            .accounts
+            .volume_tracker
+            .quote_service(&ctx.accounts.service_entry, now)?;
+        let amount_lamports = quote.price_lamports;
+        require!(amount_lamports > 0, TreasuryError::ZeroAmount);
 
-        // Also ensure we have enough free balance
+        let prepaid = &mut ctx.accounts.prepaid_balance;
         require!(
-            amount <= free_balance,
-            TreasuryError::InsufficientTreasuryBalance
+            amount_lamports <= prepaid.balance_lamports,
+            TreasuryError::InsufficientPrepaidBalance
         );
 
-        // H-TREAS-4: Daily spend limit enforcement
-        // daily_spend_lamports + amount <= free_balance * DAILY_SPEND_CAP_BPS / 10000
-        let now = Clock::get()?.unix_timestamp;
-        let daily_spend_reset_at = ctx.accounts.treasury_vault.daily_spend_reset_at;
-        let mut current_daily_spend = ctx.accounts.treasury_vault.daily_spend_lamports;
-
-        // Reset daily spend counter if a new day has started (86400 seconds)
-        if now - daily_spend_reset_at >= 86400 {
-            current_daily_spend = 0;
+        // Reset the user's daily window if a new day has started (86400 seconds)
+        if now - prepaid.daily_reset_at >= 86400 {
+            prepaid.daily_drawn_lamports = 0;
+            prepaid.daily_reset_at = now;
         }
-
-        let daily_limit = free_balance
-            .checked_mul(DAILY_SPEND_CAP_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
+        let daily_drawn_after = prepaid
+            .daily_drawn_lamports
+            .checked_add(amount_lamports)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-
         require!(
-            current_daily_spend.checked_add(amount).ok_or(TreasuryError::ArithmeticOverflow)? <= daily_limit,
-            TreasuryError::DailySpendCapExceeded
+            daily_drawn_after <= prepaid.daily_ceiling_lamports,
+            TreasuryError::PrepaidDailyCeilingExceeded
         );
 
-        // Transfer SOL from treasury_vault PDA -> creator_wallet
-        let seeds: &[&[u8]] = &[b"treasury_vault", &[vault_bump]];
-        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        prepaid.daily_drawn_lamports = daily_drawn_after;
+        prepaid.balance_lamports -= amount_lamports;
+        prepaid.total_drawn = prepaid
+            .total_drawn
+            .checked_add(amount_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        prepaid.updated_at = now;
 
-        system_program::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.treasury_vault.to_account_info(),
-                    to: ctx.accounts.creator_wallet.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            amount,
+        let split = ctx.accounts.ccs_config.split_revenue(amount_lamports)?;
+
+        transfer_from_pda(
+            &ctx.accounts.prepaid_balance.to_account_info(),
+            &ctx.accounts.treasury_vault.to_account_info(),
+            amount_lamports,
         )?;
 
-        let withdrawal_time = Clock::get()?.unix_timestamp;
+        ctx.accounts
+            .treasury_vault
+            .credit_revenue(amount_lamports, &split, now)?;
+        ctx.accounts
+            .revenue_history
+            .record_revenue(amount_lamports, now)?;
+        ctx.accounts.ccs_config.accrue_creator(split.creator, now)?;
+
+        let (agent_attributed, agent_credit) = attribute_to_owner(
+            &ctx.accounts.owner_budget.to_account_info(),
+            &ctx.accounts.treasury_config,
+            &mut ctx.accounts.treasury_vault,
+            amount_lamports,
+            split.operations,
+            now,
+        )?;
 
-        // Update vault (mutable borrow after CPI)
-        let vault = &mut ctx.accounts.treasury_vault;
-        vault.total_balance_lamports = balance_after;
-        vault.total_spent_lifetime = vault
-            .total_spent_lifetime
-            .checked_add(amount)
+        let tracker = &mut ctx.accounts.volume_tracker;
+        tracker.record_usage(amount_lamports, now)?;
+
+        let draw = &mut ctx.accounts.prepaid_draw;
+        draw.user = ctx.accounts.prepaid_balance.user;
+        draw.query_id = query_id;
+        draw.service_id = service_id;
+        draw.amount_lamports = amount_lamports;
+        draw.operations_split = split.operations;
+        draw.treasury_reserve_split = split.reserve;
+        draw.creator_split = split.creator;
+        draw.drawn_by = ctx.accounts.authority.key();
+        draw.drawn_at = now;
+        draw.refunded = false;
+        draw.bump = ctx.bumps.prepaid_draw;
+        draw.owning_agent_id = quote.owning_agent_id;
+        draw.agent_attributed = agent_attributed;
+        draw.agent_credit = agent_credit;
+        draw._reserved = [0u8; 5];
+
+        emit!(VolumeDiscountUpdated {
+            wallet: tracker.wallet,
+            service_id,
+            monthly_scans: tracker.monthly_scan_count,
+            discount_tier: tracker.current_discount_tier,
+            timestamp: now,
+        });
+
+        emit!(PrepaidQueryDrawn {
+            user: ctx.accounts.prepaid_balance.user,
+            query_id,
+            service_id,
+            amount_lamports,
+            list_price_lamports: quote.list_price_lamports,
+            discount_tier: quote.discount_tier,
+            service_tier: quote.service_tier,
+            owning_agent_id: quote.owning_agent_id,
+            balance_lamports: ctx.accounts.prepaid_balance.balance_lamports,
+            creator_split: split.creator,
+            treasury_reserve_split: split.reserve,
+            operations_split: split.operations,
+            agent_credit,
+            drawn_by: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Refunds a prepaid draw for a query that failed or was disputed, within
+    /// REFUND_WINDOW_SECONDS of the draw. Reverses the operations and reserve
+    /// buckets and claws the creator share back from the creator stream as in
+    /// refund_service_payment; the refund goes back into the user's prepaid
+    /// balance, not to their wallet. A budget credit to the owning agent is
+    /// reversed as in refund_service_payment. The query is taken back out of the
+    /// user's VolumeDiscountTracker and, if drawn in the current day, out of the
+    /// daily drawdown.
+    /// Signer: keeper_authority or the noumen-auditor authority.
+    pub fn refund_prepaid_draw(
+        ctx: Context<RefundPrepaidDraw>,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.treasury_vault.require_no_deficit()?;
+        let now = Clock::get()?.unix_timestamp;
+        let draw = &ctx.accounts.prepaid_draw;
+
+        require!(!draw.refunded, TreasuryError::PaymentAlreadyRefunded);
+        require!(
+            draw.drawn_at > ctx.accounts.treasury_config.last_evacuation_at,
+            TreasuryError::SettledByEvacuation
+        );
+        let window_end = draw
+            .drawn_at
+            .checked_add(REFUND_WINDOW_SECONDS)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-        vault.free_balance_lamports = vault
-            .total_balance_lamports
-            .checked_sub(vault.reserved_lamports)
+        require!(now <= window_end, TreasuryError::RefundWindowExpired);
+
+        let amount_lamports = draw.amount_lamports;
+        let operations_reversed = draw.operations_split;
+        let reserve_reversed = draw.treasury_reserve_split;
+        let drawn_at = draw.drawn_at;
+        let agent_attributed = draw.agent_attributed;
+        let agent_credit = draw.agent_credit;
+
+        let creator_clawed_back = ctx
+            .accounts
+            .ccs_config
+            .claw_back_creator(draw.creator_split, now)?;
+        ctx.accounts.treasury_vault.reverse_revenue(
+            amount_lamports,
+            operations_reversed,
+            reserve_reversed,
+            creator_clawed_back,
+            now,
+        )?;
+        let agent_credit_reversed = reverse_owner_attribution(
+            &ctx.accounts.owner_budget.to_account_info(),
+            &mut ctx.accounts.treasury_vault,
+            agent_attributed,
+            amount_lamports,
+            agent_credit,
+            now,
+        )?;
+
+        let refund_amount = operations_reversed
+            .checked_add(reserve_reversed)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(creator_clawed_back)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
+        if refund_amount > 0 {
+            transfer_from_pda(
+                &ctx.accounts.treasury_vault.to_account_info(),
+                &ctx.accounts.prepaid_balance.to_account_info(),
+                refund_amount,
+            )?;
+        }
 
-        // H-TREAS-4: Update daily spend tracking
-        if withdrawal_time - vault.daily_spend_reset_at >= 86400 {
-            vault.daily_spend_lamports = amount;
-            vault.daily_spend_reset_at = withdrawal_time;
-        } else {
-            vault.daily_spend_lamports = current_daily_spend
-                .checked_add(amount)
-                .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let prepaid = &mut ctx.accounts.prepaid_balance;
+        prepaid.balance_lamports = prepaid
+            .balance_lamports
+            .checked_add(refund_amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        prepaid.total_drawn = prepaid.total_drawn.saturating_sub(refund_amount);
+        if drawn_at >= prepaid.daily_reset_at {
+            prepaid.daily_drawn_lamports = prepaid.daily_drawn_lamports.saturating_sub(refund_amount);
         }
+        prepaid.updated_at = now;
 
-        vault.updated_at = withdrawal_time;
+        ctx.accounts
+            .revenue_history
+            .reverse_revenue(amount_lamports, drawn_at, now);
 
-        // Update CCS
-        let ccs = &mut ctx.accounts.ccs_config;
-        ccs.creator_accumulated = ccs
-            .creator_accumulated
-            .checked_sub(amount)
-            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let draw = &mut ctx.accounts.prepaid_draw;
+        draw.refunded = true;
 
-        emit!(CreatorWithdrawal {
-            amount,
-            remaining_accumulated: ccs.creator_accumulated,
+        let tracker = &mut ctx.accounts.volume_tracker;
+        tracker.reverse_usage(amount_lamports, drawn_at, now)?;
+
+        emit!(VolumeDiscountUpdated {
+            wallet: tracker.wallet,
+            service_id: draw.service_id,
+            monthly_scans: tracker.monthly_scan_count,
+            discount_tier: tracker.current_discount_tier,
+            timestamp: now,
+        });
+
+        emit!(PrepaidDrawRefunded {
+            user: draw.user,
+            query_id: draw.query_id,
+            service_id: draw.service_id,
+            amount_lamports,
+            refund_lamports: refund_amount,
+            operations_reversed,
+            reserve_reversed,
+            creator_clawed_back,
+            agent_credit_reversed,
+            balance_lamports: ctx.accounts.prepaid_balance.balance_lamports,
+            reason_hash,
+            refunded_by: ctx.accounts.authority.key(),
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// H-TREAS-2: Updates an existing agent budget allocation without resetting spent fields.
-    /// Signer: aeon_authority. Only modifies allocated and daily_cap.
-    pub fn update_agent_budget(
-        ctx: Context<UpdateAgentBudget>,
-        _agent_id: u16,
-        new_allocated: u64,
-        new_daily_cap: u64,
-    ) -> Result<()> {
-        // Validate: new_allocated <= free_balance * AGENT_BUDGET_CAP_BPS / 10000
-        let free_balance = ctx.accounts.treasury_vault.free_balance_lamports;
-        let max_allocation = free_balance
-            .checked_mul(AGENT_BUDGET_CAP_BPS as u64)
-            .ok_or(TreasuryError::ArithmeticOverflow)?
-            .checked_div(10_000)
+    /// Closes a PrepaidDraw once its refund window has passed, returning the
+    /// rent to the drawer that paid it. Permissionless crank.
+    pub fn close_prepaid_draw(ctx: Context<ClosePrepaidDraw>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let draw = &ctx.accounts.prepaid_draw;
+        let window_end = draw
+            .drawn_at
+            .checked_add(REFUND_WINDOW_SECONDS)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(now > window_end, TreasuryError::RefundWindowOpen);
 
-        require!(
-            new_allocated <= max_allocation,
-            TreasuryError::AgentBudgetCapExceeded
-        );
+        emit!(PrepaidDrawClosed {
+            user: draw.user,
+            query_id: draw.query_id,
+            refunded: draw.refunded,
+            timestamp: now,
+        });
 
-        let now = Clock::get()?.unix_timestamp;
-        let budget = &mut ctx.accounts.budget_allocation;
+        Ok(())
+    }
 
-        // Only update allocation and cap; do NOT reset spent/daily_spent
-        budget.allocated = new_allocated;
-        budget.daily_cap = new_daily_cap;
-        budget.updated_at = now;
+    /// Creates a subscription plan (e.g. Pro, Institutional) with a fixed price
+    /// per billing period and a grace period for failed charges. A plan owned
+    /// by an agent has its charges attributed to that agent's budget, like a
+    /// service payment; a plan without an owner is treasury revenue only.
+    /// Signer: aeon_authority.
+    pub fn create_subscription_plan(
+        ctx: Context<CreateSubscriptionPlan>,
+        plan_id: u16,
+        tier: u8,
+        price_lamports: u64,
+        period_seconds: i64,
+        grace_seconds: i64,
+        owning_agent_id: Option<u16>,
+    ) -> Result<()> {
+        require!(price_lamports > 0, TreasuryError::ZeroAmount);
+        validate_subscription_period(period_seconds, grace_seconds)?;
 
-        emit!(BudgetUpdated {
-            agent_id: budget.agent_id,
-            new_allocated,
-            new_daily_cap,
+        let now = Clock::get()?.unix_timestamp;
+        let plan = &mut ctx.accounts.subscription_plan;
+        plan.plan_id = plan_id;
+        plan.tier = tier;
+        plan.price_lamports = price_lamports;
+        plan.period_seconds = period_seconds;
+        plan.grace_seconds = grace_seconds;
+        plan.is_active = true;
+        plan.open_subscriptions = 0;
+        plan.total_revenue = 0;
+        plan.created_at = now;
+        plan.updated_at = now;
+        plan.bump = ctx.bumps.subscription_plan;
+        plan.agent_owned = owning_agent_id.is_some();
+        plan.owning_agent_id = owning_agent_id.unwrap_or(0);
+        plan._reserved = [0u8; 29];
+
+        emit!(SubscriptionPlanUpdated {
+            plan_id,
+            tier,
+            price_lamports,
+            period_seconds,
+            grace_seconds,
+            is_active: true,
+            owning_agent_id,
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Updates the 7-day and 30-day rolling revenue averages used for CCS band selection.
-    /// Signer: keeper_authority.
-    /// L-TREAS-2: Now emits RevenueAveragesUpdated event for auditability.
-    pub fn update_revenue_averages(
-        ctx: Context<UpdateRevenueAverages>,
-        avg_7d_revenue: u64,
-        avg_30d_revenue: u64,
+    /// Updates a plan's price, grace period or availability. A price above a
+    /// subscriber's approved max_price_lamports makes their next charge fail, so
+    /// existing subscribers are never charged more than they approved.
+    /// Signer: aeon_authority.
+    pub fn update_subscription_plan(
+        ctx: Context<UpdateSubscriptionPlan>,
+        price_lamports: u64,
+        grace_seconds: i64,
+        is_active: bool,
     ) -> Result<()> {
-        let ccs = &mut ctx.accounts.ccs_config;
-        ccs.avg_7d_revenue = avg_7d_revenue;
-        ccs.avg_30d_revenue = avg_30d_revenue;
+        require!(price_lamports > 0, TreasuryError::ZeroAmount);
+        let plan = &mut ctx.accounts.subscription_plan;
+        validate_subscription_period(plan.period_seconds, grace_seconds)?;
 
-        emit!(RevenueAveragesUpdated {
-            avg_7d_revenue,
-            avg_30d_revenue,
-            timestamp: Clock::get()?.unix_timestamp,
+        let now = Clock::get()?.unix_timestamp;
+        plan.price_lamports = price_lamports;
+        plan.grace_seconds = grace_seconds;
+        plan.is_active = is_active;
+        plan.updated_at = now;
+
+        emit!(SubscriptionPlanUpdated {
+            plan_id: plan.plan_id,
+            tier: plan.tier,
+            price_lamports,
+            period_seconds: plan.period_seconds,
+            grace_seconds,
+            is_active,
+            owning_agent_id: plan.owner(),
+            timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Initialize volume discount tracker for a wallet.
-    /// Creates VolumeDiscountTracker PDA with zero usage.
-    /// Signer: user wallet.
-    pub fn initialize_volume_tracker(
-        ctx: Context<InitializeVolumeTracker>,
+    /// Subscribes the user to an active plan. The user approves a per-period
+    /// price ceiling and a total allowance, and funds the Subscription PDA, which
+    /// is the escrow charges are pulled from. The first period is due at once;
+    /// clients normally bundle charge_subscription in the same transaction.
+    /// Signer: user.
+    pub fn subscribe(
+        ctx: Context<Subscribe>,
+        plan_id: u16,
+        max_price_lamports: u64,
+        allowance_lamports: u64,
+        deposit_lamports: u64,
     ) -> Result<()> {
+        let plan = &mut ctx.accounts.subscription_plan;
+        require!(plan.is_active, TreasuryError::SubscriptionPlanInactive);
+        require!(
+            plan.price_lamports <= max_price_lamports,
+            TreasuryError::SubscriptionPriceAboveMax
+        );
+        plan.open_subscriptions = plan
+            .open_subscriptions
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        if deposit_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.subscription.to_account_info(),
+                    },
+                ),
+                deposit_lamports,
+            )?;
+        }
+
         let now = Clock::get()?.unix_timestamp;
-        let tracker = &mut ctx.accounts.volume_tracker;
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user = ctx.accounts.user.key();
+        subscription.plan_id = plan_id;
+        subscription.status = SUBSCRIPTION_ACTIVE;
+        subscription.max_price_lamports = max_price_lamports;
+        subscription.allowance_remaining = allowance_lamports;
+        subscription.escrow_lamports = deposit_lamports;
+        subscription.paid_through = now;
+        subscription.periods_charged = 0;
+        subscription.total_charged = 0;
+        subscription.started_at = now;
+        subscription.updated_at = now;
+        subscription.bump = ctx.bumps.subscription;
+        subscription._reserved = [0u8; 32];
+
+        emit!(SubscriptionStarted {
+            user: subscription.user,
+            plan_id,
+            max_price_lamports,
+            allowance_lamports,
+            escrow_lamports: deposit_lamports,
+            timestamp: now,
+        });
 
-        tracker.wallet = ctx.accounts.wallet.key();
-        tracker.monthly_scan_count = 0;
-        tracker.monthly_reset_at = now
-            .checked_add(2592000)
-            .ok_or(TreasuryError::ArithmeticOverflow)?; // +30 days
-        tracker.lifetime_scans = 0;
-        tracker.current_discount_tier = 0; // No discount
-        tracker.total_spent_lamports = 0;
-        tracker.bump = ctx.bumps.volume_tracker;
-        tracker._reserved = [0u8; 32];
+        Ok(())
+    }
 
-        emit!(VolumeTrackerInitialized {
-            wallet: tracker.wallet,
+    /// Adds SOL to a subscription's escrow.
+    /// Signer: user.
+    pub fn fund_subscription(ctx: Context<FundSubscription>, amount: u64) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        ctx.accounts.subscription.require_open()?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.subscription.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.escrow_lamports = subscription
+            .escrow_lamports
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        subscription.updated_at = now;
+
+        emit!(SubscriptionFunded {
+            user: subscription.user,
+            plan_id: subscription.plan_id,
+            amount,
+            escrow_lamports: subscription.escrow_lamports,
             timestamp: now,
         });
 
         Ok(())
     }
 
-    /// Track volume usage after a service payment.
-    /// Auto-resets monthly counter every 30 days, updates discount tier.
-    /// Signer: keeper_authority (called after successful payment).
-    pub fn track_volume_usage(
-        ctx: Context<TrackVolumeUsage>,
-        service_id: u16,
-        amount_lamports: u64,
+    /// Replaces the user's approved per-period price ceiling and remaining
+    /// allowance.
+    /// Signer: user.
+    pub fn set_subscription_allowance(
+        ctx: Context<SetSubscriptionAllowance>,
+        max_price_lamports: u64,
+        allowance_lamports: u64,
     ) -> Result<()> {
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.require_open()?;
+
         let now = Clock::get()?.unix_timestamp;
-        let tracker = &mut ctx.accounts.volume_tracker;
+        subscription.max_price_lamports = max_price_lamports;
+        subscription.allowance_remaining = allowance_lamports;
+        subscription.updated_at = now;
+
+        emit!(SubscriptionAllowanceUpdated {
+            user: subscription.user,
+            plan_id: subscription.plan_id,
+            max_price_lamports,
+            allowance_remaining: allowance_lamports,
+            timestamp: now,
+        });
 
-        // Auto-reset monthly counter every 30 days
-        if now >= tracker.monthly_reset_at {
-            tracker.monthly_scan_count = 0;
-            tracker.monthly_reset_at = now
-                .checked_add(2592000)
-                .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Charges one billing period once it is due (now >= paid_through). The plan
+    /// price moves from the subscription escrow to treasury_vault and is split
+    /// like a service payment (operations, CCS creator share, reserve).
+    /// Once paid_through + grace_seconds has passed the subscription lapses
+    /// without being charged, however well funded it is, so overdue periods can
+    /// only be caught up while still inside grace. Otherwise, if the charge
+    /// cannot be made (plan inactive, price above the approved ceiling,
+    /// allowance or escrow exhausted) the instruction still succeeds and the
+    /// subscription becomes PastDue. A successful charge during grace restores it.
+    /// A charge on an agent-owned plan is attributed to that agent's budget
+    /// when it has one.
+    /// Signer: none (permissionless crank).
+    pub fn charge_subscription(ctx: Context<ChargeSubscription>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let plan = &ctx.accounts.subscription_plan;
+        let subscription = &ctx.accounts.subscription;
+        subscription.require_open()?;
+        require!(
+            now >= subscription.paid_through,
+            TreasuryError::SubscriptionNotDue
+        );
+
+        let grace_until = subscription
+            .paid_through
+            .checked_add(plan.grace_seconds)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        if now > grace_until {
+            let subscription = &mut ctx.accounts.subscription;
+            subscription.status = SUBSCRIPTION_LAPSED;
+            subscription.updated_at = now;
+            emit!(SubscriptionLapsed {
+                user: subscription.user,
+                plan_id: subscription.plan_id,
+                paid_through: subscription.paid_through,
+                timestamp: now,
+            });
+            return Ok(());
         }
 
-        // Increment counters
-        tracker.monthly_scan_count = tracker
-            .monthly_scan_count
-            .checked_add(1)
+        let price = plan.price_lamports;
+        let chargeable = plan.is_active
+            && price <= subscription.max_price_lamports
+            && price <= subscription.allowance_remaining
+            && price <= subscription.escrow_lamports;
+
+        if !chargeable {
+            let subscription = &mut ctx.accounts.subscription;
+            if subscription.status == SUBSCRIPTION_ACTIVE {
+                subscription.status = SUBSCRIPTION_PAST_DUE;
+                emit!(SubscriptionPastDue {
+                    user: subscription.user,
+                    plan_id: subscription.plan_id,
+                    grace_until,
+                    timestamp: now,
+                });
+            }
+            subscription.updated_at = now;
+            return Ok(());
+        }
+
+        let split = ctx.accounts.ccs_config.split_revenue(price)?;
+        transfer_from_pda(
+            &ctx.accounts.subscription.to_account_info(),
+            &ctx.accounts.treasury_vault.to_account_info(),
+            price,
+        )?;
+
+        ctx.accounts.treasury_vault.credit_revenue(price, &split, now)?;
+        ctx.accounts.revenue_history.record_revenue(price, now)?;
+        ctx.accounts.ccs_config.accrue_creator(split.creator, now)?;
+
+        let agent_credit = match ctx.accounts.subscription_plan.owner() {
+            Some(_) => {
+                let budget_info = ctx
+                    .accounts
+                    .owner_budget
+                    .as_ref()
+                    .ok_or(TreasuryError::MissingAgentBudget)?
+                    .to_account_info();
+                attribute_to_owner(
+                    &budget_info,
+                    &ctx.accounts.treasury_config,
+                    &mut ctx.accounts.treasury_vault,
+                    price,
+                    split.operations,
+                    now,
+                )?
+                .1
+            }
+            None => 0,
+        };
+
+        let plan = &mut ctx.accounts.subscription_plan;
+        plan.total_revenue = plan
+            .total_revenue
+            .checked_add(price)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-        tracker.lifetime_scans = tracker
-            .lifetime_scans
+        let period_seconds = plan.period_seconds;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.escrow_lamports -= price;
+        subscription.allowance_remaining -= price;
+        subscription.periods_charged = subscription
+            .periods_charged
             .checked_add(1)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
-        tracker.total_spent_lamports = tracker
-            .total_spent_lamports
-            .checked_add(amount_lamports)
+        subscription.total_charged = subscription
+            .total_charged
+            .checked_add(price)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        // Periods are contiguous: time spent past due is billed, not skipped
+        subscription.paid_through = subscription
+            .paid_through
+            .checked_add(period_seconds)
             .ok_or(TreasuryError::ArithmeticOverflow)?;
+        subscription.status = SUBSCRIPTION_ACTIVE;
+        subscription.updated_at = now;
+
+        emit!(SubscriptionCharged {
+            user: subscription.user,
+            plan_id: subscription.plan_id,
+            amount_lamports: price,
+            period_index: subscription.periods_charged,
+            paid_through: subscription.paid_through,
+            escrow_lamports: subscription.escrow_lamports,
+            creator_split: split.creator,
+            treasury_reserve_split: split.reserve,
+            operations_split: split.operations,
+            agent_credit,
+            timestamp: now,
+        });
 
-        // Update discount tier based on monthly scan count
-        // 0-9: 0% discount (tier 0)
-        // 10-49: 10% discount (tier 1)
-        // 50-99: 20% discount (tier 2)
-        // 100+: 30% discount (tier 3)
-        tracker.current_discount_tier = match tracker.monthly_scan_count {
-            0..=9 => 0,
-            10..=49 => 1,
-            50..=99 => 2,
-            _ => 3,
-        };
+        Ok(())
+    }
 
-        emit!(VolumeDiscountUpdated {
-            wallet: tracker.wallet,
-            service_id,
-            monthly_scans: tracker.monthly_scan_count,
-            discount_tier: tracker.current_discount_tier,
+    /// Cancels a subscription: no further charges, the already-paid period runs
+    /// to paid_through, and the unused escrow is returned to the user.
+    /// Signer: user.
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        ctx.accounts.subscription.require_open()?;
+
+        let refunded_lamports = ctx.accounts.subscription.escrow_lamports;
+        if refunded_lamports > 0 {
+            transfer_from_pda(
+                &ctx.accounts.subscription.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                refunded_lamports,
+            )?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.escrow_lamports = 0;
+        subscription.status = SUBSCRIPTION_CANCELLED;
+        subscription.updated_at = now;
+
+        emit!(SubscriptionCancelled {
+            user: subscription.user,
+            plan_id: subscription.plan_id,
+            paid_through: subscription.paid_through,
+            refunded_lamports,
             timestamp: now,
         });
 
         Ok(())
     }
-}
-
-// ──────────────────────────────────────────────
-// Accounts (State)
-// ──────────────────────────────────────────────
 
-#[account]
-pub struct TreasuryConfig {
-    pub super_authority: Pubkey,
-    pub aeon_authority: Pubkey,
-    pub keeper_authority: Pubkey,
-    pub creator_wallet: Pubkey,
-    pub is_initialized: bool,
-    pub bump: u8,
-    pub _reserved: [u8; 32],
-}
+    /// Closes a cancelled or lapsed subscription after its paid-through time,
+    /// returning rent and any remaining escrow to the user. The user can then
+    /// subscribe to the plan again.
+    /// Signer: user.
+    pub fn close_subscription(ctx: Context<CloseSubscription>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        require!(
+            (subscription.status == SUBSCRIPTION_CANCELLED
+                || subscription.status == SUBSCRIPTION_LAPSED)
+                && now >= subscription.paid_through,
+            TreasuryError::SubscriptionStillOpen
+        );
 
-impl TreasuryConfig {
-    pub const LEN: usize = 8  // discriminator
-        + 32  // super_authority
-        + 32  // aeon_authority
-        + 32  // keeper_authority
-        + 32  // creator_wallet
-        + 1   // is_initialized
-        + 1   // bump
-        + 32; // _reserved
-}
+        let plan = &mut ctx.accounts.subscription_plan;
+        plan.open_subscriptions = plan.open_subscriptions.saturating_sub(1);
 
-#[account]
-pub struct TreasuryVault {
-    pub total_balance_lamports: u64,
-    pub reserved_lamports: u64,
-    pub free_balance_lamports: u64,
-    pub total_revenue_lifetime: u64,
-    pub total_spent_lifetime: u64,
-    pub daily_spend_lamports: u64,
-    pub daily_spend_reset_at: i64,
-    pub total_donations_swept: u64,
-    pub operations_lamports: u64,
-    pub updated_at: i64,
-    pub bump: u8,
-    pub _reserved: [u8; 48],
-}
+        emit!(SubscriptionClosed {
+            user: subscription.user,
+            plan_id: subscription.plan_id,
+            timestamp: now,
+        });
 
-impl TreasuryVault {
-    pub const LEN: usize = 8  // discriminator
-        + 8   // total_balance_lamports
-        + 8   // reserved_lamports
-        + 8   // free_balance_lamports
-        + 8   // total_revenue_lifetime
-        + 8   // total_spent_lifetime
-        + 8   // daily_spend_lamports
-        + 8   // daily_spend_reset_at
-        + 8   // total_donations_swept
-        + 8   // operations_lamports
-        + 8   // updated_at
-        + 1   // bump
-        + 48; // _reserved
-}
+        Ok(())
+    }
 
+    /// Sets the referral share (bps of each referred payment, carved from the
+    /// operations bucket, <= MAX_REFERRAL_SHARE_BPS) and the lifetime cap on
+    /// what a single referrer can earn. Both start at 0 (referrals disabled).
+    /// Governance: executed Layer 2 PolicyProposal (delay >= 24h) whose
+    /// change_hash = policy_change_hash("referral_policy",
+    /// borsh((share_bps, lifetime_cap_lamports))).
+    /// Signer: aeon_authority.
+    pub fn set_referral_policy(
+        ctx: Context<SetReferralPolicy>,
+        share_bps: u16,
+        lifetime_cap_lamports: u64,
+    ) -> Result<()> {
+        require!(
+            share_bps <= MAX_REFERRAL_SHARE_BPS,
+            TreasuryError::InvalidReferralPolicy
+        );
+
+        let change_hash = policy_change_hash(
+            b"referral_policy",
+            &(share_bps, lifetime_cap_lamports).try_to_vec()?,
+        );
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            2,
+            noumen_core::LAYER2_MIN_DELAY,
+            change_hash,
+        )?;
+
+        let config = &mut ctx.accounts.treasury_config;
+        config.referral_share_bps = share_bps;
+        config.referral_lifetime_cap_lamports = lifetime_cap_lamports;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(ReferralPolicyUpdated {
+            proposal_id,
+            share_bps,
+            lifetime_cap_lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the fraction of each attributed payment's operations share that is
+    /// credited to the owning agent's BudgetAllocation
+    /// (<= MAX_AGENT_REVENUE_SHARE_BPS). Starts at 0 (attribution only).
+    /// Governance: executed Layer 2 PolicyProposal (delay >= 24h) whose
+    /// change_hash = policy_change_hash("agent_revenue_share", borsh(share_bps)).
+    /// Signer: aeon_authority.
+    pub fn set_agent_revenue_share(
+        ctx: Context<SetAgentRevenueShare>,
+        share_bps: u16,
+    ) -> Result<()> {
+        require!(
+            share_bps <= MAX_AGENT_REVENUE_SHARE_BPS,
+            TreasuryError::InvalidAgentRevenueShare
+        );
+
+        let change_hash = policy_change_hash(b"agent_revenue_share", &share_bps.try_to_vec()?);
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            2,
+            noumen_core::LAYER2_MIN_DELAY,
+            change_hash,
+        )?;
+
+        ctx.accounts.treasury_config.agent_revenue_share_bps = share_bps;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(AgentRevenueShareUpdated {
+            proposal_id,
+            share_bps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Registers the signer as a referrer. Referrers start inactive and earn
+    /// nothing until aeon_authority approves them with set_referrer_active, so a
+    /// payer cannot register a second wallet of its own and refer itself.
+    /// Signer: referrer wallet (pays the Referrer rent).
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.wallet = ctx.accounts.wallet.key();
+        referrer.is_active = false;
+        referrer.accrued_lamports = 0;
+        referrer.total_earned = 0;
+        referrer.total_claimed = 0;
+        referrer.referred_payments = 0;
+        referrer.registered_at = now;
+        referrer.updated_at = now;
+        referrer.bump = ctx.bumps.referrer;
+        referrer.evacuation_epoch = ctx.accounts.treasury_config.evacuation_epoch;
+        referrer._reserved = [0u8; 28];
+
+        emit!(ReferrerUpdated {
+            wallet: referrer.wallet,
+            is_active: false,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Approves (activates) or deactivates a referrer. Inactive referrers accrue
+    /// nothing on new payments but can still claim what they already earned.
+    /// Signer: aeon_authority.
+    pub fn set_referrer_active(ctx: Context<SetReferrerActive>, is_active: bool) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.is_active = is_active;
+        referrer.updated_at = now;
+
+        emit!(ReferrerUpdated {
+            wallet: referrer.wallet,
+            is_active,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Pays the referrer's whole accrued referral balance out of the vault.
+    /// Accruals are a liability held outside total_balance_lamports, so only the
+    /// reconciliation deficit gate applies.
+    /// Signer: referrer wallet.
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        ctx.accounts.treasury_vault.require_no_deficit()?;
+        ctx.accounts
+            .referrer
+            .settle_evacuated(&ctx.accounts.treasury_config);
+        let amount = ctx.accounts.referrer.accrued_lamports;
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        require!(
+            amount <= ctx.accounts.treasury_vault.referral_payable_lamports,
+            TreasuryError::InsufficientTreasuryBalance
+        );
+
+        transfer_from_pda(
+            &ctx.accounts.treasury_vault.to_account_info(),
+            &ctx.accounts.wallet.to_account_info(),
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.referral_payable_lamports -= amount;
+        vault.total_referral_paid = vault
+            .total_referral_paid
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.updated_at = now;
+
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.accrued_lamports = 0;
+        referrer.total_claimed = referrer
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        referrer.updated_at = now;
+
+        emit!(ReferralClaimed {
+            wallet: referrer.wallet,
+            amount,
+            total_claimed: referrer.total_claimed,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Token-denominated counterpart of process_service_payment (e.g. USDC for B2B).
+    /// Pricing and validation are identical: the service must be Active and the
+    /// discounted lamport price (floored at min_price_lamports, A0-8) is converted
+    /// at the mint's governed `token_units_per_sol` rate, rounded up; `amount` must
+    /// match it exactly. The split mirrors the SOL rail: the whole payment goes to
+    /// the treasury-owned token account; operations + reserve are booked in the
+    /// mint's SplTreasuryVault and the creator share is escrowed there on the same
+    /// CREATOR_STREAM_SECONDS vesting stream as SOL (withdraw_spl_creator_split).
+    /// Token revenue is not recorded in RevenueHistory (lamport-denominated CCS
+    /// averages).
+    /// Signer: payer (any external user paying for a service).
+    pub fn process_service_payment_spl(
+        ctx: Context<ProcessServicePaymentSpl>,
+        service_id: u16,
+        amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            ctx.accounts.spl_vault.is_allowed,
+            TreasuryError::MintNotAllowed
+        );
+
+        let quote = ctx
+            .accounts
+            .volume_tracker
+            .quote_service(&ctx.accounts.service_entry, now)?;
+        let price_tokens = ctx.accounts.spl_vault.lamports_to_tokens(quote.price_lamports)?;
+
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        require!(amount == price_tokens, TreasuryError::PaymentAmountMismatch);
+
+        let split = ctx.accounts.ccs_config.split_revenue(amount)?;
+
+        // Transfer the full payment (operations + reserve + creator escrow) from
+        // payer -> treasury token account
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        ctx.accounts.spl_vault.credit_revenue(amount, &split, now)?;
+
+        let tracker = &mut ctx.accounts.volume_tracker;
+        tracker.record_usage(quote.price_lamports, now)?;
+
+        emit!(VolumeDiscountUpdated {
+            wallet: tracker.wallet,
+            service_id,
+            monthly_scans: tracker.monthly_scan_count,
+            discount_tier: tracker.current_discount_tier,
+            timestamp: now,
+        });
+
+        emit!(SplServicePaymentProcessed {
+            service_id,
+            mint: ctx.accounts.mint.key(),
+            amount,
+            price_lamports: quote.price_lamports,
+            list_price_lamports: quote.list_price_lamports,
+            discount_tier: quote.discount_tier,
+            service_tier: quote.service_tier,
+            owning_agent_id: quote.owning_agent_id,
+            creator_split: split.creator,
+            treasury_reserve_split: split.reserve,
+            operations_split: split.operations,
+            ccs_band: split.band,
+            creator_base_bps: split.base_bps,
+            creator_stipend_bps: split.stipend_bps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Adds, updates or removes a mint on the SPL payment allowlist.
+    /// First use for a mint creates its SplTreasuryVault and the treasury-owned
+    /// token account (authority: the SplTreasuryVault PDA). The rate set here is
+    /// also the centre of the band update_spl_token_rate may move within.
+    /// Requires an executed noumen-core PolicyProposal (layer 1, >= 72h delay)
+    /// whose change_hash commits to (mint, allowed, token_units_per_sol).
+    /// Signer: aeon_authority.
+    pub fn set_spl_mint_allowlist(
+        ctx: Context<SetSplMintAllowlist>,
+        allowed: bool,
+        token_units_per_sol: u64,
+    ) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        let change_hash = policy_change_hash(
+            b"spl_mint_allowlist",
+            &(mint, allowed, token_units_per_sol).try_to_vec()?,
+        );
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            1,
+            noumen_core::LAYER1_MIN_DELAY,
+            change_hash,
+        )?;
+        require!(
+            !allowed || token_units_per_sol > 0,
+            TreasuryError::InvalidTokenRate
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+
+        let first_use = ctx.accounts.spl_vault.mint == Pubkey::default();
+        if first_use {
+            create_vault_token_account(ctx.accounts, ctx.bumps.vault_token_account)?;
+        }
+
+        let spl_vault = &mut ctx.accounts.spl_vault;
+        if first_use {
+            spl_vault.mint = mint;
+            spl_vault.token_account = ctx.accounts.vault_token_account.key();
+            spl_vault.total_balance = 0;
+            spl_vault.reserved = 0;
+            spl_vault.free_balance = 0;
+            spl_vault.operations = 0;
+            spl_vault.total_revenue_lifetime = 0;
+            spl_vault.total_creator_paid = 0;
+            spl_vault.token_account_bump = ctx.bumps.vault_token_account;
+            spl_vault.bump = ctx.bumps.spl_vault;
+            spl_vault.total_withdrawn = 0;
+            spl_vault.creator_accumulated = 0;
+            spl_vault.creator_unvested = 0;
+            spl_vault.creator_stream_updated_at = now;
+            spl_vault.creator_stream_end = 0;
+            spl_vault.total_creator_accrued = 0;
+            spl_vault._reserved = [0u8; 32];
+        }
+        spl_vault.is_allowed = allowed;
+        spl_vault.token_units_per_sol = token_units_per_sol;
+        spl_vault.governed_token_units_per_sol = token_units_per_sol;
+        spl_vault.rate_updated_at = now;
+        spl_vault.allowlist_proposal_id = proposal_id;
+        spl_vault.updated_at = now;
+
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(SplMintAllowlistUpdated {
+            mint,
+            allowed,
+            token_units_per_sol,
+            proposal_id,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Tracks the market rate of an allowlisted mint between governed updates.
+    /// The new rate must stay within SPL_RATE_KEEPER_BAND_BPS of the rate last
+    /// set by set_spl_mint_allowlist, and updates are at least
+    /// SPL_RATE_UPDATE_INTERVAL_SECONDS apart. Moving the band itself still
+    /// takes a Layer 1 proposal.
+    /// Signer: keeper_authority.
+    pub fn update_spl_token_rate(
+        ctx: Context<UpdateSplTokenRate>,
+        token_units_per_sol: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let spl_vault = &mut ctx.accounts.spl_vault;
+        require!(spl_vault.is_allowed, TreasuryError::MintNotAllowed);
+
+        let governed = spl_vault.governed_token_units_per_sol;
+        let band = apply_bps(governed, SPL_RATE_KEEPER_BAND_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let floor = governed
+            .checked_sub(band)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let ceiling = governed
+            .checked_add(band)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            token_units_per_sol > 0 && (floor..=ceiling).contains(&token_units_per_sol),
+            TreasuryError::TokenRateOutOfBand
+        );
+
+        let next_update_at = spl_vault
+            .rate_updated_at
+            .checked_add(SPL_RATE_UPDATE_INTERVAL_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(now >= next_update_at, TreasuryError::SplRateUpdateTooSoon);
+
+        let previous_rate = spl_vault.token_units_per_sol;
+        spl_vault.token_units_per_sol = token_units_per_sol;
+        spl_vault.rate_updated_at = now;
+        spl_vault.updated_at = now;
+
+        emit!(SplTokenRateUpdated {
+            mint: spl_vault.mint,
+            previous_rate,
+            token_units_per_sol,
+            governed_token_units_per_sol: governed,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Pays `amount` of a mint out of the treasury token account to `destination`.
+    /// Operations funds need an executed Layer 2 PolicyProposal (delay >= 24h);
+    /// reserve funds a Layer 1 proposal (delay >= 72h), and the reserve left must
+    /// still cover RESERVE_RATIO_BPS of the remaining balance (A0-3).
+    /// Governance: change_hash = policy_change_hash("spl_withdraw",
+    /// borsh(mint, destination, amount, from_reserve)).
+    /// Signer: aeon_authority.
+    pub fn withdraw_spl_treasury(
+        ctx: Context<WithdrawSplTreasury>,
+        amount: u64,
+        from_reserve: bool,
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+
+        let mint = ctx.accounts.mint.key();
+        let destination = ctx.accounts.destination.key();
+        let change_hash = policy_change_hash(
+            b"spl_withdraw",
+            &(mint, destination, amount, from_reserve).try_to_vec()?,
+        );
+        let (layer, min_delay) = if from_reserve {
+            (1, noumen_core::LAYER1_MIN_DELAY)
+        } else {
+            (2, noumen_core::LAYER2_MIN_DELAY)
+        };
+        require_executed_proposal(&ctx.accounts.policy_proposal, layer, min_delay, change_hash)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.spl_vault.debit_withdrawal(amount, from_reserve, now)?;
+
+        let spl_vault = &ctx.accounts.spl_vault;
+        let vault_bump = [spl_vault.bump];
+        let vault_seeds: &[&[u8]] = &[b"spl_vault", mint.as_ref(), &vault_bump];
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.spl_vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        let spl_vault = &ctx.accounts.spl_vault;
+        emit!(SplTreasuryWithdrawn {
+            mint,
+            destination,
+            amount,
+            from_reserve,
+            proposal_id,
+            total_balance: spl_vault.total_balance,
+            reserved: spl_vault.reserved,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraws vested creator share of one mint from its SplTreasuryVault
+    /// escrow to a token account owned by the creator wallet. Same rules as
+    /// withdraw_creator_split: the stream is vested up to now and only
+    /// creator_accumulated can be taken.
+    /// Signer: creator_wallet.
+    pub fn withdraw_spl_creator_split(
+        ctx: Context<WithdrawSplCreatorSplit>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+
+        let spl_vault = &mut ctx.accounts.spl_vault;
+        spl_vault.vest_creator(now)?;
+        require!(
+            amount <= spl_vault.creator_accumulated,
+            TreasuryError::InsufficientCreatorBalance
+        );
+
+        let mint = ctx.accounts.mint.key();
+        let vault_bump = [spl_vault.bump];
+        let vault_seeds: &[&[u8]] = &[b"spl_vault", mint.as_ref(), &vault_bump];
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.creator_token_account.to_account_info(),
+                    authority: ctx.accounts.spl_vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let spl_vault = &mut ctx.accounts.spl_vault;
+        spl_vault.creator_accumulated -= amount;
+        spl_vault.total_creator_paid = spl_vault
+            .total_creator_paid
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        spl_vault.updated_at = now;
+
+        emit!(SplCreatorWithdrawal {
+            mint,
+            amount,
+            remaining_accumulated: spl_vault.creator_accumulated,
+            remaining_unvested: spl_vault.creator_unvested,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Allocates a budget to a specific agent. The allocated amount cannot exceed
+    /// 15% (AGENT_BUDGET_CAP_BPS) of the treasury's current free balance, and the
+    /// sum of all outstanding allocations must fit the free balance. Evaluator
+    /// allocations are capped together at EVALUATOR_BUDGET_CAP_BPS (25%).
+    /// The agent type is read from the agent's noumen-core AgentManifest, and
+    /// only Active agents can be given a budget.
+    /// Signer: aeon_authority.
+    pub fn allocate_agent_budget(
+        ctx: Context<AllocateAgentBudget>,
+        agent_id: u16,
+        allocated: u64,
+        daily_cap: u64,
+    ) -> Result<()> {
+        // Validate: allocated <= free_balance * AGENT_BUDGET_CAP_BPS / 10000
+        let free_balance = ctx.accounts.treasury_vault.free_balance_lamports;
+        let max_allocation = apply_bps(free_balance, AGENT_BUDGET_CAP_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        require!(
+            allocated <= max_allocation,
+            TreasuryError::AgentBudgetCapExceeded
+        );
+
+        let agent_type = ctx.accounts.agent_manifest.agent_type;
+        ctx.accounts
+            .treasury_vault
+            .rebalance_outstanding_budget(agent_type, 0, allocated)?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        let budget = &mut ctx.accounts.budget_allocation;
+        budget.agent_id = agent_id;
+        budget.allocated = allocated;
+        budget.spent = 0;
+        budget.daily_cap = daily_cap;
+        budget.daily_spent = 0;
+        budget.daily_reset_at = now;
+        budget.updated_at = now;
+        budget.agent_type = agent_type;
+        budget.revenue_generated = 0;
+        budget.revenue_credited = 0;
+        budget.bump = ctx.bumps.budget_allocation;
+        budget._reserved = [0u8; 15];
+
+        emit!(BudgetAllocated {
+            agent_id,
+            allocated,
+            daily_cap,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Sweeps ALL pending SOL from DonationVault PDA to TreasuryVault PDA.
+    /// No CCS split applied (A0-24: donations confer no rights/priority/influence).
+    /// Lamports of receipts flagged as disguised payments before the sweep are
+    /// booked as unattributed inflow, not as donations, and stay out of
+    /// total_donations_swept and the donation history. A receipt flagged after
+    /// its sweep is only counted in DonationVault.flagged_amount: the lifetime
+    /// counters behind period statements never decrease.
+    /// Signer: keeper_authority.
+    pub fn sweep_donations(ctx: Context<SweepDonations>) -> Result<()> {
+        require!(
+            ctx.accounts.donation_vault.pending_sweep > 0,
+            TreasuryError::NothingToSweep
+        );
+        let now = Clock::get()?.unix_timestamp;
+        sweep_pending_donations(
+            &mut ctx.accounts.donation_vault,
+            &mut ctx.accounts.treasury_vault,
+            &mut ctx.accounts.revenue_history,
+            now,
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless donation: transfers SOL from the donor into the
+    /// donation_vault PDA and creates a DonationReceipt for the amount actually
+    /// transferred. The receipt nonce is DonationVault.receipt_count. The receipt
+    /// stores donor_wallet_hash(salt, donor) instead of the address (A0-27); this
+    /// is a pseudonym, not a secret, since the donor signs this transaction and
+    /// the salt is in its data. receipt_hash is the domain-tagged
+    /// donation_receipt_hash over the receipt fields.
+    /// Donations confer no rights, priority or influence (A0-24).
+    /// Signer: donor (any wallet).
+    pub fn donate(
+        ctx: Context<Donate>,
+        amount: u64,
+        salt: [u8; 32],
+        disclosure_mode: u8,
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        require!(disclosure_mode <= 1, TreasuryError::InvalidDisclosureMode);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.donor.to_account_info(),
+                    to: ctx.accounts.donation_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let nonce = ctx.accounts.donation_vault.receipt_count;
+        let source_wallet_hash = donor_wallet_hash(&salt, &ctx.accounts.donor.key());
+
+        // A0-24: a donor who paid for a service within the correlation window is
+        // presumed to be paying in disguise. The tracker address is derived from the
+        // donor key, so it cannot be swapped for another wallet's.
+        let correlated = recent_payment_from(&ctx.accounts.donor_volume_tracker, now)?;
+        let receipt_status: u8 = if correlated { 1 } else { 0 }; // 1 = Flagged, 0 = Active
+        let receipt_hash = donation_receipt_hash(
+            nonce,
+            amount,
+            now,
+            &source_wallet_hash,
+            disclosure_mode,
+            receipt_status,
+        );
+
+        let receipt = &mut ctx.accounts.donation_receipt;
+        receipt.nonce = nonce;
+        receipt.amount = amount;
+        receipt.timestamp = now;
+        receipt.source_wallet_hash = source_wallet_hash;
+        receipt.disclosure_mode = disclosure_mode;
+        receipt.receipt_status = receipt_status;
+        receipt.counts_as_donation = !correlated;
+        receipt.correlated_payments = correlated as u32;
+        receipt.correlated_flag = correlated;
+        receipt.receipt_hash = receipt_hash;
+        receipt.bump = ctx.bumps.donation_receipt;
+        receipt._reserved = [0u8; 42];
+
+        let donation = &mut ctx.accounts.donation_vault;
+        donation.total_received = donation
+            .total_received
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        donation.pending_sweep = donation
+            .pending_sweep
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        donation.receipt_count = donation
+            .receipt_count
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        if correlated {
+            donation.record_flagged(nonce, amount)?;
+        }
+
+        emit!(DonationReceiptCreated {
+            nonce,
+            amount,
+            source_wallet_hash,
+            disclosure_mode,
+            receipt_hash,
+            timestamp: now,
+        });
+
+        if correlated {
+            emit!(DonationCorrelated {
+                receipt_nonce: nonce,
+                payment_nonce: None,
+                correlated_payments: 1,
+                counts_as_donation: false,
+                already_swept: false,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Links a donation receipt to a service payment from the same wallet made
+    /// within DONATION_CORRELATION_WINDOW_SECONDS of it (either direction). The
+    /// caller proves the link by revealing the receipt's salt:
+    /// donor_wallet_hash(salt, payment_record.payer) must equal
+    /// source_wallet_hash. The receipt is flagged and stops counting as a
+    /// donation (A0-24). Each payment record can be correlated once. Every
+    /// service payment creates a PaymentRecord, so this also catches a payment
+    /// made after the donation, which `donate` cannot see.
+    /// Signer: none (permissionless crank).
+    pub fn correlate_donation(ctx: Context<CorrelateDonation>, salt: [u8; 32]) -> Result<()> {
+        let record = &ctx.accounts.payment_record;
+        require!(
+            !record.donation_correlated,
+            TreasuryError::PaymentAlreadyCorrelated
+        );
+
+        let receipt = &ctx.accounts.donation_receipt;
+        require!(
+            donor_wallet_hash(&salt, &record.payer) == receipt.source_wallet_hash,
+            TreasuryError::DonationNotCorrelated
+        );
+        let gap = receipt
+            .timestamp
+            .checked_sub(record.paid_at)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_abs()
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            gap <= DONATION_CORRELATION_WINDOW_SECONDS,
+            TreasuryError::CorrelationWindowExceeded
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let payment_nonce = record.nonce;
+        ctx.accounts.payment_record.donation_correlated = true;
+
+        let receipt = &mut ctx.accounts.donation_receipt;
+        receipt.correlated_payments = receipt
+            .correlated_payments
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let newly_flagged = receipt.counts_as_donation;
+        receipt.correlated_flag = true;
+        receipt.counts_as_donation = false;
+        receipt.receipt_status = 1; // Flagged
+
+        let already_swept = if newly_flagged {
+            let (nonce, amount) = (receipt.nonce, receipt.amount);
+            ctx.accounts.donation_vault.record_flagged(nonce, amount)?
+        } else {
+            false
+        };
+
+        emit!(DonationCorrelated {
+            receipt_nonce: ctx.accounts.donation_receipt.nonce,
+            payment_nonce: Some(payment_nonce),
+            correlated_payments: ctx.accounts.donation_receipt.correlated_payments,
+            counts_as_donation: false,
+            already_swept,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Sets how long donation receipts are kept before archive_donation_receipt
+    /// can fold them into the archive root (>= MIN_RECEIPT_RETENTION_SECONDS).
+    /// Starts at 0: archival disabled.
+    /// Governance: executed Layer 2 PolicyProposal (delay >= 24h) whose
+    /// change_hash = policy_change_hash("receipt_retention", borsh(retention_seconds)).
+    /// Signer: aeon_authority.
+    pub fn set_receipt_retention(
+        ctx: Context<SetReceiptRetention>,
+        retention_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            retention_seconds >= MIN_RECEIPT_RETENTION_SECONDS,
+            TreasuryError::InvalidReceiptRetention
+        );
+
+        let change_hash =
+            policy_change_hash(b"receipt_retention", &retention_seconds.try_to_vec()?);
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            2,
+            noumen_core::LAYER2_MIN_DELAY,
+            change_hash,
+        )?;
+
+        ctx.accounts.donation_vault.receipt_retention_seconds = retention_seconds;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(ReceiptRetentionUpdated {
+            proposal_id,
+            retention_seconds,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Folds the oldest unarchived donation receipt into the archive Merkle tree
+    /// kept in DonationVault and closes it. Receipts are archived strictly in
+    /// nonce order, so leaf i of the tree is receipt i; the leaf commits to the
+    /// receipt's final state. The rent goes to the treasury vault (the donor is
+    /// only known by salted hash) and is booked by reconcile_treasury.
+    /// Signer: none (permissionless crank).
+    pub fn archive_donation_receipt(ctx: Context<ArchiveDonationReceipt>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let retention = ctx.accounts.donation_vault.receipt_retention_seconds;
+        require!(retention > 0, TreasuryError::ReceiptArchivalDisabled);
+
+        let receipt = &ctx.accounts.donation_receipt;
+        let archivable_at = receipt
+            .timestamp
+            .checked_add(retention)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            now >= archivable_at,
+            TreasuryError::ReceiptRetentionNotElapsed
+        );
+
+        let nonce = receipt.nonce;
+        let leaf = receipt.archive_leaf()?;
+        let donation = &mut ctx.accounts.donation_vault;
+        let archive_root = donation.append_archive_leaf(leaf)?;
+
+        emit!(DonationReceiptArchived {
+            nonce,
+            leaf,
+            archive_root,
+            archived_receipt_count: donation.archived_receipt_count,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Closes the BudgetAllocation of an agent killed in noumen-core. The
+    /// undisbursed allocation is released from the outstanding budget totals,
+    /// returning it to free balance for new allocations; the rent goes back to
+    /// aeon_authority, which paid it.
+    /// Signer: none (permissionless crank).
+    pub fn close_agent_budget(ctx: Context<CloseAgentBudget>, agent_id: u16) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let budget = &ctx.accounts.budget_allocation;
+        let released = budget.outstanding()?;
+        let spent = budget.spent;
+        ctx.accounts
+            .treasury_vault
+            .release_outstanding_budget(budget.agent_type, released)?;
+        ctx.accounts.treasury_vault.updated_at = now;
+
+        emit!(AgentBudgetClosed {
+            agent_id,
+            released,
+            spent,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraws vested creator CCS split from the creator escrow in treasury_vault.
+    /// The stream is vested up to now first; only creator_accumulated (vested,
+    /// not yet withdrawn) can be taken. Escrowed lamports are a liability outside
+    /// total_balance_lamports, so the reserve ratio and daily spend cap (A0-3)
+    /// do not apply.
+    /// Signer: creator_wallet.
+    pub fn withdraw_creator_split(
+        ctx: Context<WithdrawCreatorSplit>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        ctx.accounts.treasury_vault.require_no_deficit()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let ccs = &mut ctx.accounts.ccs_config;
+        ccs.vest_creator(now)?;
+        require!(
+            amount <= ccs.creator_accumulated,
+            TreasuryError::InsufficientCreatorBalance
+        );
+        require!(
+            amount <= ctx.accounts.treasury_vault.creator_escrow_lamports,
+            TreasuryError::InsufficientCreatorBalance
+        );
+
+        transfer_from_pda(
+            &ctx.accounts.treasury_vault.to_account_info(),
+            &ctx.accounts.creator_wallet.to_account_info(),
+            amount,
+        )?;
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.creator_escrow_lamports -= amount;
+        vault.updated_at = now;
+
+        let ccs = &mut ctx.accounts.ccs_config;
+        ccs.creator_accumulated -= amount;
+        ccs.total_creator_paid = ccs
+            .total_creator_paid
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        emit!(CreatorWithdrawal {
+            amount,
+            remaining_accumulated: ccs.creator_accumulated,
+            remaining_unvested: ccs.creator_unvested,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Starts a creator wallet rotation. The new wallet can accept it after
+    /// CREATOR_WALLET_ROTATION_DELAY; until then the current wallet keeps
+    /// receiving withdrawals and either party below can cancel. Proposing again
+    /// replaces the pending wallet and restarts the delay.
+    /// Signer: creator_wallet or super_authority.
+    pub fn propose_creator_wallet(
+        ctx: Context<ProposeCreatorWallet>,
+        new_wallet: Pubkey,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            new_wallet != Pubkey::default() && new_wallet != config.creator_wallet,
+            TreasuryError::InvalidCreatorWallet
+        );
+
+        let effective_at = now
+            .checked_add(CREATOR_WALLET_ROTATION_DELAY)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        config.pending_creator_wallet = new_wallet;
+        config.creator_wallet_change_at = effective_at;
+
+        emit!(CreatorWalletChangeProposed {
+            current_wallet: config.creator_wallet,
+            pending_wallet: new_wallet,
+            effective_at,
+            proposed_by: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Completes a creator wallet rotation once the delay has passed. Vested and
+    /// unvested creator balances carry over to the new wallet.
+    /// Signer: pending_creator_wallet.
+    pub fn accept_creator_wallet(ctx: Context<AcceptCreatorWallet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            config.creator_wallet_change_at != 0,
+            TreasuryError::NoPendingCreatorWallet
+        );
+        require!(
+            now >= config.creator_wallet_change_at,
+            TreasuryError::CreatorWalletDelayNotElapsed
+        );
+
+        let previous_wallet = config.creator_wallet;
+        config.creator_wallet = config.pending_creator_wallet;
+        config.pending_creator_wallet = Pubkey::default();
+        config.creator_wallet_change_at = 0;
+
+        emit!(CreatorWalletRotated {
+            previous_wallet,
+            new_wallet: config.creator_wallet,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Cancels a pending creator wallet rotation.
+    /// Signer: creator_wallet or super_authority.
+    pub fn cancel_creator_wallet_change(ctx: Context<ProposeCreatorWallet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            config.creator_wallet_change_at != 0,
+            TreasuryError::NoPendingCreatorWallet
+        );
+
+        let cancelled_wallet = config.pending_creator_wallet;
+        config.pending_creator_wallet = Pubkey::default();
+        config.creator_wallet_change_at = 0;
+
+        emit!(CreatorWalletChangeCancelled {
+            cancelled_wallet,
+            cancelled_by: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// H-TREAS-2: Updates an existing agent budget allocation without resetting spent fields.
+    /// Signer: aeon_authority. Only modifies allocated and daily_cap.
+    /// Re-applies the aggregate and per-type caps to the change in outstanding budget.
+    pub fn update_agent_budget(
+        ctx: Context<UpdateAgentBudget>,
+        _agent_id: u16,
+        new_allocated: u64,
+        new_daily_cap: u64,
+    ) -> Result<()> {
+        // Validate: new_allocated <= free_balance * AGENT_BUDGET_CAP_BPS / 10000
+        let free_balance = ctx.accounts.treasury_vault.free_balance_lamports;
+        let max_allocation = apply_bps(free_balance, AGENT_BUDGET_CAP_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        require!(
+            new_allocated <= max_allocation,
+            TreasuryError::AgentBudgetCapExceeded
+        );
+
+        // Allocation can shrink, but not below what has already been disbursed
+        let budget = &mut ctx.accounts.budget_allocation;
+        require!(
+            new_allocated >= budget.spent,
+            TreasuryError::AllocationBelowSpent
+        );
+        let old_outstanding = budget.outstanding()?;
+        let new_outstanding = new_allocated
+            .checked_sub(budget.spent)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        ctx.accounts.treasury_vault.rebalance_outstanding_budget(
+            budget.agent_type,
+            old_outstanding,
+            new_outstanding,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // Only update allocation and cap; do NOT reset spent/daily_spent
+        budget.allocated = new_allocated;
+        budget.daily_cap = new_daily_cap;
+        budget.updated_at = now;
+
+        emit!(BudgetUpdated {
+            agent_id: budget.agent_id,
+            new_allocated,
+            new_daily_cap,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Disburses lamports from the treasury vault to an agent's authority, drawing
+    /// down its BudgetAllocation. Enforces the allocation, the per-agent daily cap,
+    /// the global daily spend cap (DAILY_SPEND_CAP_BPS) and the reserve ratio (A0-3).
+    /// Funds are debited from operations_lamports. The agent must be Active in noumen-core.
+    /// Signer: aeon_authority.
+    pub fn disburse_agent_budget(
+        ctx: Context<DisburseAgentBudget>,
+        agent_id: u16,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        ctx.accounts.treasury_vault.require_no_deficit()?;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // ── Per-agent limits ──
+        let budget = &mut ctx.accounts.budget_allocation;
+
+        // Reset the agent's daily window if a new day has started (86400 seconds)
+        if now - budget.daily_reset_at >= 86400 {
+            budget.daily_spent = 0;
+            budget.daily_reset_at = now;
+        }
+
+        let spent_after = budget
+            .spent
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            spent_after <= budget.allocated,
+            TreasuryError::AgentBudgetExhausted
+        );
+
+        let daily_spent_after = budget
+            .daily_spent
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            daily_spent_after <= budget.daily_cap,
+            TreasuryError::AgentDailyCapExceeded
+        );
+
+        budget.spent = spent_after;
+        budget.daily_spent = daily_spent_after;
+        budget.updated_at = now;
+        let agent_type = budget.agent_type;
+
+        // ── Treasury-wide limits ──
+        let vault = &mut ctx.accounts.treasury_vault;
+        require!(
+            amount <= vault.operations_lamports,
+            TreasuryError::InsufficientOperationsBalance
+        );
+        require!(
+            amount <= vault.free_balance_lamports,
+            TreasuryError::InsufficientTreasuryBalance
+        );
+        let balance_after = vault.require_reserve_ratio_after(amount)?;
+        vault.charge_daily_spend(amount, now)?;
+        vault.release_outstanding_budget(agent_type, amount)?;
+
+        transfer_from_pda(
+            &vault.to_account_info(),
+            &ctx.accounts.agent_authority.to_account_info(),
+            amount,
+        )?;
+
+        vault.total_balance_lamports = balance_after;
+        vault.operations_lamports = vault
+            .operations_lamports
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.total_spent_lifetime = vault
+            .total_spent_lifetime
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.total_budget_disbursed = vault
+            .total_budget_disbursed
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.refresh_free_balance()?;
+        vault.updated_at = now;
+
+        emit!(BudgetDisbursed {
+            agent_id,
+            recipient: ctx.accounts.agent_authority.key(),
+            amount,
+            spent: spent_after,
+            daily_spent: daily_spent_after,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Registers an operational expense payee (RPC provider, AI API vendor, ...).
+    /// Only registered, active payees can receive pay_operational_expense.
+    /// Signer: aeon_authority.
+    pub fn register_expense_payee(
+        ctx: Context<RegisterExpensePayee>,
+        label_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payee = &mut ctx.accounts.expense_payee;
+        payee.wallet = ctx.accounts.payee_wallet.key();
+        payee.label_hash = label_hash;
+        payee.is_active = true;
+        payee.total_paid = 0;
+        payee.payment_count = 0;
+        payee.registered_at = now;
+        payee.updated_at = now;
+        payee.bump = ctx.bumps.expense_payee;
+        payee._reserved = [0u8; 32];
+
+        emit!(ExpensePayeeUpdated {
+            wallet: payee.wallet,
+            label_hash,
+            is_active: true,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Activates or deactivates a registered expense payee.
+    /// Signer: aeon_authority.
+    pub fn set_expense_payee_active(
+        ctx: Context<SetExpensePayeeActive>,
+        is_active: bool,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payee = &mut ctx.accounts.expense_payee;
+        payee.is_active = is_active;
+        payee.updated_at = now;
+
+        emit!(ExpensePayeeUpdated {
+            wallet: payee.wallet,
+            label_hash: payee.label_hash,
+            is_active,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Pays an operational bill from operations_lamports to a registered payee.
+    /// Bounded by operations_lamports, free balance, the A0-3 reserve ratio and
+    /// DAILY_SPEND_CAP_BPS. Each invoice gets an ExpenseRecord ledger PDA keyed by
+    /// invoice_hash, so an invoice can only be paid once.
+    /// Signer: aeon_authority.
+    pub fn pay_operational_expense(
+        ctx: Context<PayOperationalExpense>,
+        payee: Pubkey,
+        amount: u64,
+        invoice_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.require_no_deficit()?;
+        require!(
+            amount <= vault.operations_lamports,
+            TreasuryError::InsufficientOperationsBalance
+        );
+        require!(
+            amount <= vault.free_balance_lamports,
+            TreasuryError::InsufficientTreasuryBalance
+        );
+        let balance_after = vault.require_reserve_ratio_after(amount)?;
+        vault.charge_daily_spend(amount, now)?;
+
+        transfer_from_pda(
+            &vault.to_account_info(),
+            &ctx.accounts.payee_wallet.to_account_info(),
+            amount,
+        )?;
+
+        vault.total_balance_lamports = balance_after;
+        vault.operations_lamports = vault
+            .operations_lamports
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.total_spent_lifetime = vault
+            .total_spent_lifetime
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.total_operational_expenses = vault
+            .total_operational_expenses
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.refresh_free_balance()?;
+        vault.updated_at = now;
+        let operations_after = vault.operations_lamports;
+
+        let registry = &mut ctx.accounts.expense_payee;
+        registry.total_paid = registry
+            .total_paid
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        registry.payment_count = registry
+            .payment_count
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        registry.updated_at = now;
+
+        let record = &mut ctx.accounts.expense_record;
+        record.invoice_hash = invoice_hash;
+        record.payee = payee;
+        record.amount = amount;
+        record.paid_at = now;
+        record.operations_after = operations_after;
+        record.bump = ctx.bumps.expense_record;
+        record._reserved = [0u8; 16];
+
+        emit!(OperationalExpensePaid {
+            payee,
+            amount,
+            invoice_hash,
+            operations_lamports: operations_after,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the CCS bands (thresholds, base splits, stipend ceilings).
+    /// A0-28: bands are Layer 1 parameters; requires an executed noumen-core
+    /// PolicyProposal (layer 1, >= 30-day delay) whose change_hash commits to the
+    /// new bands. The floor/caps are Layer 0 and cannot be changed here.
+    /// Signer: aeon_authority.
+    pub fn update_ccs_bands(
+        ctx: Context<UpdateCcsBands>,
+        new_bands: [CCSBand; 4],
+    ) -> Result<()> {
+        let change_hash = policy_change_hash(b"ccs_bands", &new_bands.try_to_vec()?);
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            1,
+            CCS_BAND_MIN_DELAY,
+            change_hash,
+        )?;
+
+        let ccs = &mut ctx.accounts.ccs_config;
+        ccs.validate_bands(&new_bands)?;
+        ccs.bands = new_bands;
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(CcsBandsUpdated {
+            proposal_id,
+            bands: new_bands,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Releases `amount` from reserved_lamports into free balance. Only the excess
+    /// above the A0-3 requirement (RESERVE_RATIO_BPS of total balance) can be
+    /// released, and at most RESERVE_RELEASE_ANNUAL_CAP_BPS of the reserve per
+    /// 365-day window. No lamports move; the reserve is reclassified.
+    /// Governance: executed Layer 1 PolicyProposal (delay >= 72h) whose
+    /// change_hash = policy_change_hash("reserve_release", borsh(amount)).
+    /// Signer: aeon_authority.
+    pub fn release_reserve(ctx: Context<ReleaseReserve>, amount: u64) -> Result<()> {
+        require!(amount > 0, TreasuryError::ZeroAmount);
+
+        let change_hash = policy_change_hash(b"reserve_release", &amount.try_to_vec()?);
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            1,
+            noumen_core::LAYER1_MIN_DELAY,
+            change_hash,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &mut ctx.accounts.treasury_vault;
+        let excess = vault.excess_reserve()?;
+        require!(amount <= excess, TreasuryError::ReserveRatioBreach);
+        vault.charge_reserve_release(amount, now)?;
+
+        vault.reserved_lamports = vault
+            .reserved_lamports
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.require_reserve_covers_stake()?;
+        vault.refresh_free_balance()?;
+        vault.updated_at = now;
+
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        let vault = &ctx.accounts.treasury_vault;
+        emit!(ReserveReleased {
+            proposal_id,
+            amount,
+            reserved_lamports: vault.reserved_lamports,
+            free_balance_lamports: vault.free_balance_lamports,
+            released_in_window: vault.reserve_released_in_window,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Delegates `amount` of the reserve to `vote_account` through a new native
+    /// stake account PDA `[b"stake_account", index]` whose staker and withdrawer
+    /// is the treasury `[b"stake_authority"]` PDA. The lamports stay on the books
+    /// (total_balance and reserved_lamports) and are tracked in staked_lamports,
+    /// so A0-3 reserve checks keep counting them; at most MAX_STAKED_RESERVE_BPS
+    /// of the reserve can be staked.
+    /// Governance: executed Layer 1 PolicyProposal (delay >= 72h) whose
+    /// change_hash = policy_change_hash("stake_delegate", borsh(vote_account, amount)).
+    /// Signer: aeon_authority.
+    pub fn delegate_reserve_stake(ctx: Context<DelegateReserveStake>, amount: u64) -> Result<()> {
+        let vote_key = ctx.accounts.vote_account.key();
+        let change_hash =
+            policy_change_hash(b"stake_delegate", &(vote_key, amount).try_to_vec()?);
+        require_executed_proposal(
+            &ctx.accounts.policy_proposal,
+            1,
+            noumen_core::LAYER1_MIN_DELAY,
+            change_hash,
+        )?;
+
+        let stake_space = StakeStateV2::size_of();
+        let rent_minimum = Rent::get()?.minimum_balance(stake_space);
+        require!(amount > rent_minimum, TreasuryError::StakeAmountTooSmall);
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.require_no_deficit()?;
+        let staked_after = vault
+            .staked_lamports
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let stake_cap = apply_bps(vault.reserved_lamports, MAX_STAKED_RESERVE_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(staked_after <= stake_cap, TreasuryError::StakeCapExceeded);
+        let index = vault.stake_account_count;
+
+        // Fund the (system-owned, empty) stake account PDA straight from the vault,
+        // then size it and hand it to the stake program under the PDA's signature.
+        let stake_info = ctx.accounts.stake_account.to_account_info();
+        transfer_from_pda(&vault.to_account_info(), &stake_info, amount)?;
+
+        let index_bytes = index.to_le_bytes();
+        let stake_bump = [ctx.bumps.stake_account];
+        let stake_seeds: &[&[u8]] = &[b"stake_account", &index_bytes, &stake_bump];
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Allocate {
+                    account_to_allocate: stake_info.clone(),
+                },
+                &[stake_seeds],
+            ),
+            stake_space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Assign {
+                    account_to_assign: stake_info.clone(),
+                },
+                &[stake_seeds],
+            ),
+            &native_stake::program::ID,
+        )?;
+
+        let authority_key = ctx.accounts.stake_authority.key();
+        invoke(
+            &native_stake::instruction::initialize(
+                &stake_info.key(),
+                &Authorized {
+                    staker: authority_key,
+                    withdrawer: authority_key,
+                },
+                &Lockup::default(),
+            ),
+            &[stake_info.clone(), ctx.accounts.rent.to_account_info()],
+        )?;
+
+        let authority_bump = [ctx.bumps.stake_authority];
+        let authority_seeds: &[&[u8]] = &[b"stake_authority", &authority_bump];
+        invoke_signed(
+            &native_stake::instruction::delegate_stake(&stake_info.key(), &authority_key, &vote_key),
+            &[
+                stake_info.clone(),
+                ctx.accounts.vote_account.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_history.to_account_info(),
+                ctx.accounts.stake_config.to_account_info(),
+                ctx.accounts.stake_authority.to_account_info(),
+            ],
+            &[authority_seeds],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.staked_lamports = staked_after;
+        vault.stake_account_count = index
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.updated_at = now;
+
+        let proposal_id = ctx.accounts.policy_proposal.proposal_id;
+        let record = &mut ctx.accounts.treasury_stake;
+        record.index = index;
+        record.stake_account = stake_info.key();
+        record.vote_account = vote_key;
+        record.principal_lamports = amount;
+        record.status = 0; // Active
+        record.delegated_at = now;
+        record.deactivated_at = 0;
+        record.withdrawn_at = 0;
+        record.withdrawn_lamports = 0;
+        record.proposal_id = proposal_id;
+        record.bump = ctx.bumps.treasury_stake;
+        record._reserved = [0u8; 32];
+
+        ctx.accounts.applied_proposal.record(
+            proposal_id,
+            change_hash,
+            now,
+            ctx.bumps.applied_proposal,
+        );
+
+        emit!(ReserveStakeDelegated {
+            index,
+            stake_account: stake_info.key(),
+            vote_account: vote_key,
+            amount,
+            staked_lamports: staked_after,
+            proposal_id,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Deactivates a treasury stake account so it can be withdrawn after the
+    /// cooldown. Reducing exposure needs no proposal.
+    /// Signer: aeon_authority.
+    pub fn deactivate_reserve_stake(ctx: Context<DeactivateReserveStake>) -> Result<()> {
+        let authority_bump = [ctx.bumps.stake_authority];
+        let authority_seeds: &[&[u8]] = &[b"stake_authority", &authority_bump];
+        invoke_signed(
+            &native_stake::instruction::deactivate_stake(
+                &ctx.accounts.stake_account.key(),
+                &ctx.accounts.stake_authority.key(),
+            ),
+            &[
+                ctx.accounts.stake_account.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_authority.to_account_info(),
+            ],
+            &[authority_seeds],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let record = &mut ctx.accounts.treasury_stake;
+        record.status = 1; // Deactivating
+        record.deactivated_at = now;
+
+        emit!(ReserveStakeDeactivated {
+            index: record.index,
+            stake_account: record.stake_account,
+            principal_lamports: record.principal_lamports,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraws a deactivated stake account's full balance back into the vault
+    /// (fails in the stake program while cooldown is still running). The
+    /// principal returns to the liquid reserve; anything above it is staking
+    /// reward, booked as non-revenue treasury income in free balance.
+    /// Signer: none (permissionless crank; lamports can only go to the vault).
+    pub fn withdraw_reserve_stake(ctx: Context<WithdrawReserveStake>) -> Result<()> {
+        let stake_info = ctx.accounts.stake_account.to_account_info();
+        let withdrawn = stake_info.lamports();
+
+        let authority_bump = [ctx.bumps.stake_authority];
+        let authority_seeds: &[&[u8]] = &[b"stake_authority", &authority_bump];
+        invoke_signed(
+            &native_stake::instruction::withdraw(
+                &stake_info.key(),
+                &ctx.accounts.stake_authority.key(),
+                &ctx.accounts.treasury_vault.key(),
+                withdrawn,
+                None,
+            ),
+            &[
+                stake_info.clone(),
+                ctx.accounts.treasury_vault.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.stake_history.to_account_info(),
+                ctx.accounts.stake_authority.to_account_info(),
+            ],
+            &[authority_seeds],
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let principal = ctx.accounts.treasury_stake.principal_lamports;
+        let rewards = withdrawn.saturating_sub(principal);
+        let shortfall = principal.saturating_sub(withdrawn);
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.staked_lamports = vault
+            .staked_lamports
+            .checked_sub(principal)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.total_balance_lamports = vault
+            .total_balance_lamports
+            .checked_add(rewards)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_sub(shortfall)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        // A shortfall can only come out of the reserve it was staked from
+        vault.reserved_lamports = vault.reserved_lamports.saturating_sub(shortfall);
+        vault.total_stake_rewards = vault
+            .total_stake_rewards
+            .checked_add(rewards)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.refresh_free_balance()?;
+        vault.updated_at = now;
+
+        let record = &mut ctx.accounts.treasury_stake;
+        record.status = 2; // Withdrawn
+        record.withdrawn_at = now;
+        record.withdrawn_lamports = withdrawn;
+
+        emit!(ReserveStakeWithdrawn {
+            index: record.index,
+            stake_account: record.stake_account,
+            principal_lamports: principal,
+            withdrawn_lamports: withdrawn,
+            rewards,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Compares the vault PDA's real lamports (minus its rent-exempt minimum) with
+    /// total_balance_lamports. A surplus (unsolicited transfers) is booked into
+    /// free balance as unattributed inflow, never as revenue. A deficit is
+    /// recorded and blocks every vault outflow until a later reconciliation
+    /// finds the books covered again.
+    /// Signer: none (permissionless crank).
+    pub fn reconcile_treasury(ctx: Context<ReconcileTreasury>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault_info = ctx.accounts.treasury_vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let actual_lamports = vault_info.lamports().saturating_sub(rent_minimum);
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        // Staked reserve lives in stake accounts, not in the vault PDA; creator
+        // escrow and referral accruals are held in the PDA but outside
+        // total_balance_lamports
+        let booked_lamports = vault
+            .total_balance_lamports
+            .checked_sub(vault.staked_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(vault.creator_escrow_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(vault.referral_payable_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let surplus = actual_lamports.saturating_sub(booked_lamports);
+        let deficit = booked_lamports.saturating_sub(actual_lamports);
+
+        if surplus > 0 {
+            vault.total_balance_lamports = vault
+                .total_balance_lamports
+                .checked_add(surplus)
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+            vault.unattributed_inflow_lamports = vault
+                .unattributed_inflow_lamports
+                .checked_add(surplus)
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+            vault.refresh_free_balance()?;
+        }
+        vault.deficit_lamports = deficit;
+        vault.last_reconciled_at = now;
+        vault.updated_at = now;
+
+        emit!(TreasuryReconciled {
+            actual_lamports,
+            booked_lamports,
+            surplus,
+            deficit,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Closes the current accounting period (at least TREASURY_PERIOD_SECONDS
+    /// long) into an immutable TreasuryStatement PDA: period flows are the change
+    /// in the lifetime counters of TreasuryVault and CCSConfig since the last
+    /// close, plus opening/closing balances. Each statement commits to the hash
+    /// of the previous one, so the series cannot be rewritten or have gaps.
+    /// Signer: none (permissionless crank; the caller pays the statement rent).
+    pub fn close_treasury_period(ctx: Context<CloseTreasuryPeriod>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault = &ctx.accounts.treasury_vault;
+        let period_end = vault
+            .period_started_at
+            .checked_add(TREASURY_PERIOD_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(now >= period_end, TreasuryError::PeriodNotElapsed);
+
+        let opening = vault.period_snapshot;
+        let closing = vault.snapshot(&ctx.accounts.ccs_config)?;
+        let period_index = vault.statement_count;
+        let period_start = vault.period_started_at;
+        let prev_statement_hash = vault.last_statement_hash;
+
+        let flow = |closing: u64, opening: u64| {
+            closing
+                .checked_sub(opening)
+                .ok_or(TreasuryError::ArithmeticOverflow)
+        };
+
+        let statement = &mut ctx.accounts.treasury_statement;
+        statement.period_index = period_index;
+        statement.period_start = period_start;
+        statement.period_end = now;
+        statement.revenue = flow(closing.revenue_gross, opening.revenue_gross)?;
+        statement.refunds = flow(closing.revenue_refunded, opening.revenue_refunded)?;
+        statement.donations_swept = flow(closing.donations_swept, opening.donations_swept)?;
+        statement.creator_paid = flow(closing.creator_paid, opening.creator_paid)?;
+        statement.operations_spent = flow(closing.operational_expenses, opening.operational_expenses)?;
+        statement.budgets_disbursed = flow(closing.budgets_disbursed, opening.budgets_disbursed)?;
+        statement.reserve_released = flow(closing.reserve_released, opening.reserve_released)?;
+        statement.stake_rewards = flow(closing.stake_rewards, opening.stake_rewards)?;
+        statement.unattributed_inflow = flow(closing.unattributed_inflow, opening.unattributed_inflow)?;
+        statement.referral_paid = flow(closing.referral_paid, opening.referral_paid)?;
+        statement.evacuated = flow(closing.evacuated, opening.evacuated)?;
+        statement.opening_balance = opening.total_balance;
+        statement.closing_balance = closing.total_balance;
+        statement.opening_reserved = opening.reserved;
+        statement.closing_reserved = closing.reserved;
+        statement.reserve_delta = i64::try_from(closing.reserved as i128 - opening.reserved as i128)
+            .map_err(|_| TreasuryError::ArithmeticOverflow)?;
+        statement.prev_statement_hash = prev_statement_hash;
+        statement.statement_hash = [0u8; 32];
+        statement.bump = ctx.bumps.treasury_statement;
+        statement._reserved = [0u8; 32];
+        let statement_hash = statement.compute_hash()?;
+        statement.statement_hash = statement_hash;
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        vault.statement_count = period_index
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.period_started_at = now;
+        vault.last_statement_hash = statement_hash;
+        vault.period_snapshot = closing;
+
+        emit!(TreasuryPeriodClosed {
+            period_index,
+            period_start,
+            period_end: now,
+            revenue: ctx.accounts.treasury_statement.revenue,
+            closing_balance: closing.total_balance,
+            statement_hash,
+            prev_statement_hash,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Registers (or replaces) the cold wallet that receives an emergency
+    /// evacuation. The new wallet co-signs to prove control; replacing an
+    /// existing cold wallet also needs the current one, so a compromised
+    /// super_authority alone cannot redirect an evacuation. Not allowed while
+    /// an evacuation is armed.
+    /// Signer: super_authority + current cold wallet (if any) + new cold wallet.
+    pub fn register_cold_wallet(ctx: Context<RegisterColdWallet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            config.evacuation_unlocks_at == 0,
+            TreasuryError::EvacuationAlreadyArmed
+        );
+
+        let previous_wallet = config.cold_wallet;
+        config.cold_wallet = ctx.accounts.new_cold_wallet.key();
+
+        emit!(ColdWalletRegistered {
+            previous_wallet,
+            cold_wallet: config.cold_wallet,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Arms an emergency evacuation of the treasury vault. Only possible while
+    /// noumen-core is in CircuitBreakerMode::Halted and a cold wallet is
+    /// registered; executable after EVACUATION_DELAY_SECONDS.
+    /// Signer: super_authority.
+    pub fn arm_treasury_evacuation(ctx: Context<ArmTreasuryEvacuation>) -> Result<()> {
+        require!(
+            ctx.accounts.aeon_config.circuit_breaker_mode == CircuitBreakerMode::Halted as u8,
+            TreasuryError::CircuitBreakerNotHalted
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            config.cold_wallet != Pubkey::default(),
+            TreasuryError::ColdWalletNotRegistered
+        );
+        require!(
+            config.evacuation_unlocks_at == 0,
+            TreasuryError::EvacuationAlreadyArmed
+        );
+
+        let unlocks_at = now
+            .checked_add(EVACUATION_DELAY_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        config.evacuation_unlocks_at = unlocks_at;
+
+        emit!(TreasuryEvacuationArmed {
+            cold_wallet: config.cold_wallet,
+            unlocks_at,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Disarms a pending evacuation.
+    /// Signer: super_authority or cold wallet.
+    pub fn cancel_treasury_evacuation(ctx: Context<CancelTreasuryEvacuation>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(
+            config.evacuation_unlocks_at != 0,
+            TreasuryError::EvacuationNotArmed
+        );
+        config.evacuation_unlocks_at = 0;
+
+        emit!(TreasuryEvacuationCancelled {
+            cancelled_by: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Moves every lamport above rent from the treasury vault to the cold wallet
+    /// and freezes the treasury (is_initialized = false) until
+    /// reinitialize_treasury. Requires an armed evacuation whose delay has
+    /// passed and noumen-core still Halted. The books are reduced to the staked
+    /// reserve, which stays in its stake accounts. Pending donations are swept
+    /// into the vault first, so they leave with it. SOL creator escrow and
+    /// referral accruals leave with the rest and are settled from the cold
+    /// wallet: the creator stream is zeroed here, and referrers' accruals are
+    /// dropped lazily by bumping evacuation_epoch. Payments made up to now can
+    /// no longer be refunded. SPL vaults are not evacuated, so the SPL creator
+    /// escrow stays backed in its token accounts.
+    /// Signer: super_authority + cold wallet.
+    pub fn evacuate_treasury(ctx: Context<EvacuateTreasury>) -> Result<()> {
+        require!(
+            ctx.accounts.aeon_config.circuit_breaker_mode == CircuitBreakerMode::Halted as u8,
+            TreasuryError::CircuitBreakerNotHalted
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let unlocks_at = ctx.accounts.treasury_config.evacuation_unlocks_at;
+        require!(unlocks_at != 0, TreasuryError::EvacuationNotArmed);
+        require!(
+            now >= unlocks_at,
+            TreasuryError::EvacuationDelayNotElapsed
+        );
+
+        // Unswept donations leave with the rest instead of being stranded in
+        // the donation vault while the treasury is frozen
+        let donations_swept = sweep_pending_donations(
+            &mut ctx.accounts.donation_vault,
+            &mut ctx.accounts.treasury_vault,
+            &mut ctx.accounts.revenue_history,
+            now,
+        )?;
+
+        let vault_info = ctx.accounts.treasury_vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let amount = vault_info.lamports().saturating_sub(rent_minimum);
+        if amount > 0 {
+            transfer_from_pda(
+                &vault_info,
+                &ctx.accounts.cold_wallet.to_account_info(),
+                amount,
+            )?;
+        }
+
+        let ccs = &mut ctx.accounts.ccs_config;
+        ccs.vest_creator(now)?;
+        let creator_accumulated = ccs.creator_accumulated;
+        let creator_unvested = ccs.creator_unvested;
+        ccs.creator_accumulated = 0;
+        ccs.creator_unvested = 0;
+        ccs.creator_stream_end = now;
+
+        let vault = &mut ctx.accounts.treasury_vault;
+        let creator_escrow_lamports = vault.creator_escrow_lamports;
+        let referral_payable_lamports = vault.referral_payable_lamports;
+        vault.total_balance_lamports = vault.staked_lamports;
+        vault.reserved_lamports = vault.staked_lamports;
+        vault.operations_lamports = 0;
+        vault.creator_escrow_lamports = 0;
+        vault.referral_payable_lamports = 0;
+        vault.deficit_lamports = 0;
+        vault.refresh_free_balance()?;
+        vault.total_evacuated_lamports = vault
+            .total_evacuated_lamports
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        vault.updated_at = now;
+
+        let config = &mut ctx.accounts.treasury_config;
+        config.is_initialized = false;
+        config.evacuation_unlocks_at = 0;
+        config.evacuated_at = now;
+        config.last_evacuation_at = now;
+        config.evacuation_epoch = config
+            .evacuation_epoch
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        emit!(TreasuryEvacuated {
+            cold_wallet: config.cold_wallet,
+            amount,
+            staked_lamports: vault.staked_lamports,
+            creator_escrow_lamports,
+            creator_accumulated,
+            creator_unvested,
+            referral_payable_lamports,
+            donations_swept,
+            evacuation_epoch: config.evacuation_epoch,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Unfreezes an evacuated treasury with fresh aeon and keeper authorities,
+    /// once noumen-core has left Halted mode. The vault restarts from the
+    /// post-evacuation books; funds are returned by plain transfers and picked
+    /// up by reconcile_treasury.
+    /// Signer: super_authority + cold wallet.
+    pub fn reinitialize_treasury(
+        ctx: Context<ReinitializeTreasury>,
+        aeon_authority: Pubkey,
+        keeper_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.aeon_config.circuit_breaker_mode != CircuitBreakerMode::Halted as u8,
+            TreasuryError::CircuitBreakerStillHalted
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.treasury_config;
+        require!(config.evacuated_at != 0, TreasuryError::TreasuryNotEvacuated);
+
+        let evacuated_at = config.evacuated_at;
+        config.aeon_authority = aeon_authority;
+        config.keeper_authority = keeper_authority;
+        config.evacuated_at = 0;
+        config.is_initialized = true;
+
+        emit!(TreasuryReinitialized {
+            aeon_authority,
+            keeper_authority,
+            evacuated_at,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Grows TreasuryConfig, TreasuryVault and DonationVault created by an
+    /// earlier program version to their current size. Fields added since
+    /// genesis sit after `bump`, where `_reserved` used to start, so the old
+    /// bytes keep their meaning and the new fields come up zeroed; the vault
+    /// timestamps and the empty archive root are then set as the initializers
+    /// would. Accounts already at full size are skipped, so this is idempotent.
+    /// Signer: super_authority (pays the extra rent).
+    pub fn migrate_treasury_accounts(ctx: Context<MigrateTreasuryAccounts>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payer = ctx.accounts.super_authority.to_account_info();
+        let system = ctx.accounts.system_program.to_account_info();
+
+        // The config may predate the current layout, so its authority is read
+        // from the genesis offsets rather than through Account<TreasuryConfig>.
+        let config_info = ctx.accounts.treasury_config.to_account_info();
+        {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() >= 40 && data[..8] == TreasuryConfig::DISCRIMINATOR,
+                TreasuryError::NotInitialized
+            );
+            require!(
+                data[8..40] == payer.key().to_bytes(),
+                TreasuryError::UnauthorizedAeon
+            );
+        }
+        let config_grown = grow_account(&config_info, TreasuryConfig::LEN, &payer, &system)?;
+
+        let vault_info = ctx.accounts.treasury_vault.to_account_info();
+        require!(
+            vault_info.try_borrow_data()?[..8] == TreasuryVault::DISCRIMINATOR,
+            TreasuryError::NotInitialized
+        );
+        let vault_grown = grow_account(&vault_info, TreasuryVault::LEN, &payer, &system)?;
+        if vault_grown {
+            let mut vault = TreasuryVault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+            vault.reserve_release_window_start = now;
+            vault.last_reconciled_at = now;
+            vault.period_started_at = now;
+            vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+        }
+
+        let donation_info = ctx.accounts.donation_vault.to_account_info();
+        require!(
+            donation_info.try_borrow_data()?[..8] == DonationVault::DISCRIMINATOR,
+            TreasuryError::NotInitialized
+        );
+        let donation_grown = grow_account(&donation_info, DonationVault::LEN, &payer, &system)?;
+        if donation_grown {
+            let mut donation =
+                DonationVault::try_deserialize(&mut &donation_info.try_borrow_data()?[..])?;
+            donation.archived_receipts_root = DonationVault::empty_archive_root();
+            donation.try_serialize(&mut &mut donation_info.try_borrow_mut_data()?[..])?;
+        }
+
+        emit!(TreasuryAccountsMigrated {
+            config_grown,
+            vault_grown,
+            donation_grown,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Recomputes the 7-day and 30-day rolling revenue averages used for CCS band
+    /// selection from the on-chain RevenueHistory buckets (completed days only,
+    /// service revenue only — donations confer no CCS split).
+    /// Permissionless crank: no signer beyond the fee payer.
+    /// L-TREAS-2: Emits RevenueAveragesUpdated event for auditability.
+    pub fn update_revenue_averages(ctx: Context<UpdateRevenueAverages>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        let history = &mut ctx.accounts.revenue_history;
+        history.advance_to(now);
+        let avg_7d_revenue = history.average_daily_revenue(7)?;
+        let avg_30d_revenue = history.average_daily_revenue(30)?;
+
+        let ccs = &mut ctx.accounts.ccs_config;
+        ccs.avg_7d_revenue = avg_7d_revenue;
+        ccs.avg_30d_revenue = avg_30d_revenue;
+
+        emit!(RevenueAveragesUpdated {
+            avg_7d_revenue,
+            avg_30d_revenue,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Initialize volume discount tracker for a wallet.
+    /// Creates VolumeDiscountTracker PDA with zero usage.
+    /// Signer: user wallet.
+    pub fn initialize_volume_tracker(
+        ctx: Context<InitializeVolumeTracker>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let tracker = &mut ctx.accounts.volume_tracker;
+
+        tracker.wallet = ctx.accounts.wallet.key();
+        tracker.monthly_scan_count = 0;
+        tracker.monthly_reset_at = now
+            .checked_add(2592000)
+            .ok_or(TreasuryError::ArithmeticOverflow)?; // +30 days
+        tracker.lifetime_scans = 0;
+        tracker.current_discount_tier = 0; // No discount
+        tracker.total_spent_lamports = 0;
+        tracker.last_payment_at = 0;
+        tracker.bump = ctx.bumps.volume_tracker;
+        tracker._reserved = [0u8; 24];
+
+        emit!(VolumeTrackerInitialized {
+            wallet: tracker.wallet,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+}
+
+// ──────────────────────────────────────────────
+// Vault Helpers
+// ──────────────────────────────────────────────
+
+/// Moves lamports out of a PDA owned by this program. SystemProgram::transfer
+/// cannot debit an account that carries data, so lamports are moved directly.
+/// The source must stay rent-exempt after the debit.
+pub fn transfer_from_pda<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let rent_floor = Rent::get()?.minimum_balance(from.data_len());
+    let from_after = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+    require!(
+        from_after >= rent_floor,
+        TreasuryError::InsufficientTreasuryBalance
+    );
+    let to_after = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+    **from.try_borrow_mut_lamports()? = from_after;
+    **to.try_borrow_mut_lamports()? = to_after;
+    Ok(())
+}
+
+/// Reallocs a program-owned account up to `len`, zero-filling the new bytes.
+/// The payer covers only the extra rent, so any balance the account already
+/// tracks is untouched. Returns false if the account is already that large.
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<bool> {
+    let old_len = account.data_len();
+    if old_len >= len {
+        return Ok(false);
+    }
+    let rent = Rent::get()?;
+    let top_up = rent
+        .minimum_balance(len)
+        .checked_sub(rent.minimum_balance(old_len))
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.realloc(len, true)?;
+    Ok(true)
+}
+
+impl TreasuryVault {
+    /// Books a service payment already transferred in: operations and reserve
+    /// buckets grow, the creator share goes to escrow and the referral share to
+    /// referral_payable_lamports, lifetime revenue counts the gross amount.
+    pub fn credit_revenue(&mut self, amount: u64, split: &RevenueSplit, now: i64) -> Result<()> {
+        let vault_total = split
+            .operations
+            .checked_add(split.reserve)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_balance_lamports = self
+            .total_balance_lamports
+            .checked_add(vault_total)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.creator_escrow_lamports = self
+            .creator_escrow_lamports
+            .checked_add(split.creator)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.referral_payable_lamports = self
+            .referral_payable_lamports
+            .checked_add(split.referral)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_revenue_lifetime = self
+            .total_revenue_lifetime
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        // Track reserved lamports (treasury reserve portion)
+        self.reserved_lamports = self
+            .reserved_lamports
+            .checked_add(split.reserve)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        // Track operations separately
+        self.operations_lamports = self
+            .operations_lamports
+            .checked_add(split.operations)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        self.refresh_free_balance()?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Reserve above the A0-3 requirement of RESERVE_RATIO_BPS of total balance
+    /// (requirement rounded up).
+    pub fn excess_reserve(&self) -> Result<u64> {
+        let required_reserve =
+            apply_bps(self.total_balance_lamports, RESERVE_RATIO_BPS, Rounding::Up)
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(self.reserved_lamports.saturating_sub(required_reserve))
+    }
+
+    /// Applies the annual reserve release cap. Rolls the 365-day window if it has
+    /// elapsed (or no release has happened yet), snapshotting the current reserve
+    /// as the cap base, then records the release. Must be called before
+    /// reserved_lamports is reduced.
+    pub fn charge_reserve_release(&mut self, amount: u64, now: i64) -> Result<()> {
+        if now - self.reserve_release_window_start >= RESERVE_RELEASE_WINDOW_SECONDS
+            || self.reserve_release_window_base == 0
+        {
+            self.reserve_release_window_start = now;
+            self.reserve_release_window_base = self.reserved_lamports;
+            self.reserve_released_in_window = 0;
+        }
+        let annual_cap = apply_bps(
+            self.reserve_release_window_base,
+            RESERVE_RELEASE_ANNUAL_CAP_BPS,
+            Rounding::Down,
+        )
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let released_after = self
+            .reserve_released_in_window
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        require!(
+            released_after <= annual_cap,
+            TreasuryError::ReserveReleaseCapExceeded
+        );
+        self.reserve_released_in_window = released_after;
+        self.total_reserve_released = self
+            .total_reserve_released
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Staked lamports come out of the reserve, so the reserve must always cover
+    /// them.
+    pub fn require_reserve_covers_stake(&self) -> Result<()> {
+        require!(
+            self.reserved_lamports >= self.staked_lamports,
+            TreasuryError::ReserveBelowStaked
+        );
+        Ok(())
+    }
+
+    /// Lifetime counters and balances as of now, for period statements. Every
+    /// counter only ever grows, so period flows are plain differences.
+    pub fn snapshot(&self, ccs: &CCSConfig) -> Result<TreasurySnapshot> {
+        Ok(TreasurySnapshot {
+            revenue_gross: self
+                .total_revenue_lifetime
+                .checked_add(self.total_revenue_refunded)
+                .ok_or(TreasuryError::ArithmeticOverflow)?,
+            revenue_refunded: self.total_revenue_refunded,
+            donations_swept: self.total_donations_swept,
+            creator_paid: ccs.total_creator_paid,
+            operational_expenses: self.total_operational_expenses,
+            budgets_disbursed: self.total_budget_disbursed,
+            reserve_released: self.total_reserve_released,
+            stake_rewards: self.total_stake_rewards,
+            unattributed_inflow: self.unattributed_inflow_lamports,
+            referral_paid: self.total_referral_paid,
+            evacuated: self.total_evacuated_lamports,
+            total_balance: self.total_balance_lamports,
+            reserved: self.reserved_lamports,
+        })
+    }
+
+    /// Outflows are refused while reconcile_treasury has found the PDA holding
+    /// fewer lamports than the books.
+    pub fn require_no_deficit(&self) -> Result<()> {
+        require!(self.deficit_lamports == 0, TreasuryError::TreasuryDeficit);
+        Ok(())
+    }
+
+    /// Takes a refunded payment back out of the books: the operations and
+    /// reserve buckets shrink, the clawed-back creator share leaves escrow, and
+    /// the gross amount moves from lifetime revenue to total_revenue_refunded.
+    pub fn reverse_revenue(
+        &mut self,
+        amount: u64,
+        operations: u64,
+        reserve: u64,
+        creator_clawed_back: u64,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            self.operations_lamports >= operations,
+            TreasuryError::InsufficientOperationsBalance
+        );
+        require!(
+            self.reserved_lamports >= reserve,
+            TreasuryError::InsufficientTreasuryBalance
+        );
+        self.operations_lamports -= operations;
+        self.reserved_lamports -= reserve;
+        self.require_reserve_covers_stake()?;
+        self.total_balance_lamports = self
+            .total_balance_lamports
+            .checked_sub(operations)
+            .ok_or(TreasuryError::InsufficientTreasuryBalance)?
+            .checked_sub(reserve)
+            .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+        self.refresh_free_balance()?;
+        self.creator_escrow_lamports = self
+            .creator_escrow_lamports
+            .checked_sub(creator_clawed_back)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_revenue_lifetime = self.total_revenue_lifetime.saturating_sub(amount);
+        self.total_revenue_refunded = self
+            .total_revenue_refunded
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Recalculates free balance: total - reserved.
+    pub fn refresh_free_balance(&mut self) -> Result<()> {
+        self.free_balance_lamports = self
+            .total_balance_lamports
+            .checked_sub(self.reserved_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// A0-3: after an outflow of `amount` from free balance, reserved_lamports
+    /// must still cover RESERVE_RATIO_BPS of the remaining balance.
+    /// Returns the total balance after the outflow.
+    pub fn require_reserve_ratio_after(&self, amount: u64) -> Result<u64> {
+        let balance_after = self
+            .total_balance_lamports
+            .checked_sub(amount)
+            .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+
+        // Round the requirement up so the reserve is never undershot by dust
+        let required_reserve = apply_bps(balance_after, RESERVE_RATIO_BPS, Rounding::Up)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        require!(
+            self.reserved_lamports >= required_reserve,
+            TreasuryError::ReserveRatioBreach
+        );
+        Ok(balance_after)
+    }
+
+    /// Moves one agent's outstanding budget from `old_outstanding` to
+    /// `new_outstanding` in the aggregate totals. Increases must satisfy:
+    ///   - sum of all outstanding allocations <= free balance
+    ///   - sum of Evaluator allocations <= EVALUATOR_BUDGET_CAP_BPS of free balance
+    ///
+    /// Decreases are always allowed so budgets can shrink after free balance drops.
+    pub fn rebalance_outstanding_budget(
+        &mut self,
+        agent_type: u8,
+        old_outstanding: u64,
+        new_outstanding: u64,
+    ) -> Result<()> {
+        let idx = agent_type as usize;
+        require!(
+            idx < self.budget_outstanding_by_type.len(),
+            TreasuryError::InvalidAgentType
+        );
+
+        let total_after = self
+            .total_budget_outstanding
+            .checked_sub(old_outstanding)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(new_outstanding)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let type_after = self.budget_outstanding_by_type[idx]
+            .checked_sub(old_outstanding)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_add(new_outstanding)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        if new_outstanding > old_outstanding {
+            require!(
+                total_after <= self.free_balance_lamports,
+                TreasuryError::AggregateBudgetCapExceeded
+            );
+            if agent_type == AgentType::Evaluator as u8 {
+                let evaluator_cap = apply_bps(
+                    self.free_balance_lamports,
+                    EVALUATOR_BUDGET_CAP_BPS,
+                    Rounding::Down,
+                )
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+                require!(
+                    type_after <= evaluator_cap,
+                    TreasuryError::EvaluatorBudgetCapExceeded
+                );
+            }
+        }
+
+        self.total_budget_outstanding = total_after;
+        self.budget_outstanding_by_type[idx] = type_after;
+        Ok(())
+    }
+
+    /// Releases `amount` of outstanding budget once it has been disbursed.
+    pub fn release_outstanding_budget(&mut self, agent_type: u8, amount: u64) -> Result<()> {
+        let idx = agent_type as usize;
+        require!(
+            idx < self.budget_outstanding_by_type.len(),
+            TreasuryError::InvalidAgentType
+        );
+        self.total_budget_outstanding = self
+            .total_budget_outstanding
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.budget_outstanding_by_type[idx] = self.budget_outstanding_by_type[idx]
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Adds revenue credited to an agent's budget to the outstanding totals.
+    /// Not capped: the credit comes out of operations revenue booked by the
+    /// same payment, so it never exceeds the free balance it added.
+    pub fn credit_attributed_budget(&mut self, agent_type: u8, amount: u64) -> Result<()> {
+        let idx = agent_type as usize;
+        require!(
+            idx < self.budget_outstanding_by_type.len(),
+            TreasuryError::InvalidAgentType
+        );
+        self.total_budget_outstanding = self
+            .total_budget_outstanding
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.budget_outstanding_by_type[idx] = self.budget_outstanding_by_type[idx]
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// H-TREAS-4: daily_spend_lamports + amount <= free_balance * DAILY_SPEND_CAP_BPS / 10000.
+    /// Rolls the 24h window if it has elapsed and records the spend.
+    /// Must be called before free balance is reduced by the outflow.
+    pub fn charge_daily_spend(&mut self, amount: u64, now: i64) -> Result<()> {
+        if now - self.daily_spend_reset_at >= 86400 {
+            self.daily_spend_lamports = 0;
+            self.daily_spend_reset_at = now;
+        }
+
+        let daily_limit = apply_bps(self.free_balance_lamports, DAILY_SPEND_CAP_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let daily_after = self
+            .daily_spend_lamports
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        require!(
+            daily_after <= daily_limit,
+            TreasuryError::DailySpendCapExceeded
+        );
+
+        self.daily_spend_lamports = daily_after;
+        Ok(())
+    }
+}
+
+// ──────────────────────────────────────────────
+// Donation Helpers
+// ──────────────────────────────────────────────
+
+/// True when `tracker_info` (the donor's VolumeDiscountTracker PDA, which may not
+/// exist) records a service payment within DONATION_CORRELATION_WINDOW_SECONDS.
+pub fn recent_payment_from(tracker_info: &AccountInfo, now: i64) -> Result<bool> {
+    if tracker_info.owner != &crate::ID || tracker_info.data_is_empty() {
+        return Ok(false);
+    }
+    let data = tracker_info.try_borrow_data()?;
+    let tracker = VolumeDiscountTracker::try_deserialize(&mut &data[..])?;
+    Ok(tracker.last_payment_at > 0
+        && now - tracker.last_payment_at <= DONATION_CORRELATION_WINDOW_SECONDS)
+}
+
+/// Moves donation_vault's pending_sweep into treasury_vault. Donations go to
+/// total balance but do NOT count as revenue (they confer no CCS split);
+/// flagged lamports are booked as unattributed inflow instead. Used by
+/// sweep_donations and by evacuate_treasury, so evacuation leaves no unswept
+/// donations behind. Returns the swept amount (0 when nothing is pending).
+pub fn sweep_pending_donations<'info>(
+    donation_vault: &mut Account<'info, DonationVault>,
+    treasury_vault: &mut Account<'info, TreasuryVault>,
+    revenue_history: &mut Account<'info, RevenueHistory>,
+    now: i64,
+) -> Result<u64> {
+    let sweep_amount = donation_vault.pending_sweep;
+    if sweep_amount == 0 {
+        return Ok(0);
+    }
+    let flagged_amount = donation_vault.pending_flagged.min(sweep_amount);
+    let donated_amount = sweep_amount - flagged_amount;
+
+    // pending_sweep only grows from real `donate` transfers, so the lamports exist
+    transfer_from_pda(
+        &donation_vault.to_account_info(),
+        &treasury_vault.to_account_info(),
+        sweep_amount,
+    )?;
+
+    donation_vault.pending_sweep = 0;
+    donation_vault.pending_flagged = 0;
+    donation_vault.swept_receipt_count = donation_vault.receipt_count;
+    donation_vault.sweep_count = donation_vault
+        .sweep_count
+        .checked_add(1)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    donation_vault.last_sweep_at = now;
+
+    treasury_vault.total_balance_lamports = treasury_vault
+        .total_balance_lamports
+        .checked_add(sweep_amount)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    treasury_vault.total_donations_swept = treasury_vault
+        .total_donations_swept
+        .checked_add(donated_amount)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    treasury_vault.unattributed_inflow_lamports = treasury_vault
+        .unattributed_inflow_lamports
+        .checked_add(flagged_amount)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    treasury_vault.refresh_free_balance()?;
+    treasury_vault.updated_at = now;
+
+    revenue_history.record_donations(donated_amount, now)?;
+
+    emit!(DonationSwept {
+        amount: sweep_amount,
+        flagged_amount,
+        sweep_count: donation_vault.sweep_count,
+        timestamp: now,
+    });
+
+    Ok(sweep_amount)
+}
+
+impl DonationVault {
+    /// Books a receipt that no longer counts as a donation (A0-24). If its
+    /// lamports have not been swept yet, they are held out of the next sweep's
+    /// donation totals. Returns whether the receipt had already been swept.
+    pub fn record_flagged(&mut self, receipt_nonce: u64, amount: u64) -> Result<bool> {
+        self.flagged_count = self
+            .flagged_count
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.flagged_amount = self
+            .flagged_amount
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let already_swept = receipt_nonce < self.swept_receipt_count;
+        if !already_swept {
+            self.pending_flagged = self
+                .pending_flagged
+                .checked_add(amount)
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+        }
+        Ok(already_swept)
+    }
+}
+
+// ──────────────────────────────────────────────
+// Revenue History Helpers
+// ──────────────────────────────────────────────
+
+impl RevenueHistory {
+    fn bucket(day: i64) -> usize {
+        day.rem_euclid(REVENUE_HISTORY_DAYS as i64) as usize
+    }
+
+    /// Rolls the ring buffer forward to the day containing `now`, zeroing every
+    /// bucket for days that passed without activity. A clock reading earlier
+    /// than current_day leaves the buffer untouched.
+    pub fn advance_to(&mut self, now: i64) {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        if today <= self.current_day {
+            return;
+        }
+        let elapsed = (today - self.current_day).min(REVENUE_HISTORY_DAYS as i64);
+        for offset in 0..elapsed {
+            let bucket = Self::bucket(today - offset);
+            self.daily_revenue[bucket] = 0;
+            self.daily_donations[bucket] = 0;
+        }
+        self.current_day = today;
+    }
+
+    pub fn record_revenue(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.advance_to(now);
+        let bucket = Self::bucket(self.current_day);
+        self.daily_revenue[bucket] = self.daily_revenue[bucket]
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_donations(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.advance_to(now);
+        let bucket = Self::bucket(self.current_day);
+        self.daily_donations[bucket] = self.daily_donations[bucket]
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Removes refunded revenue from the bucket of the day it was paid, if that
+    /// day is still inside the ring buffer.
+    pub fn reverse_revenue(&mut self, amount: u64, paid_at: i64, now: i64) {
+        self.advance_to(now);
+        let paid_day = paid_at.div_euclid(SECONDS_PER_DAY);
+        if paid_day > self.current_day
+            || self.current_day - paid_day >= REVENUE_HISTORY_DAYS as i64
+        {
+            return;
+        }
+        let bucket = Self::bucket(paid_day);
+        self.daily_revenue[bucket] = self.daily_revenue[bucket].saturating_sub(amount);
+    }
+
+    /// Average daily service revenue over the last `days` completed days
+    /// (today's partial bucket is excluded). While the history is younger than
+    /// the window, divides by the number of completed days actually observed.
+    pub fn average_daily_revenue(&self, days: usize) -> Result<u64> {
+        require!(
+            days > 0 && days < REVENUE_HISTORY_DAYS,
+            TreasuryError::InvalidRevenueWindow
+        );
+        let observed = self
+            .current_day
+            .checked_sub(self.first_day)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let window = (days as i64).min(observed);
+        if window <= 0 {
+            return Ok(0);
+        }
+
+        let mut sum: u64 = 0;
+        for offset in 1..=window {
+            sum = sum
+                .checked_add(self.daily_revenue[Self::bucket(self.current_day - offset)])
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+        }
+        Ok(sum / window as u64)
+    }
+}
+
+// ──────────────────────────────────────────────
+// Volume Discount Helpers
+// ──────────────────────────────────────────────
+
+/// Resolved price of one service call for a specific payer.
+pub struct ServiceQuote {
+    pub list_price_lamports: u64,
+    pub price_lamports: u64,
+    pub discount_tier: u8,
+    pub service_tier: u8,
+    pub owning_agent_id: u16,
+}
+
+impl VolumeDiscountTracker {
+    /// Prices one call to `service` for this tracker's wallet: the service must be
+    /// Active, the volume discount earned so far this month is applied to the list
+    /// price, and the result never goes below min_price_lamports (A0-8).
+    pub fn quote_service(
+        &mut self,
+        service: &noumen_service::ServiceEntry,
+        now: i64,
+    ) -> Result<ServiceQuote> {
+        require!(
+            service.level == ServiceLevel::Active as u8,
+            TreasuryError::ServiceNotActive
+        );
+        self.roll_month(now)?;
+
+        let price_lamports = economic_engine::apply_volume_discount(
+            service.price_lamports,
+            self.monthly_scan_count as u32,
+        )
+        .ok_or(TreasuryError::ArithmeticOverflow)?
+        .max(service.min_price_lamports);
+
+        Ok(ServiceQuote {
+            list_price_lamports: service.price_lamports,
+            price_lamports,
+            discount_tier: self.current_discount_tier,
+            service_tier: service.service_tier,
+            owning_agent_id: service.owning_agent_id,
+        })
+    }
+
+    /// Auto-resets the monthly counter every 30 days. Must run before the
+    /// discount for a payment is priced.
+    pub fn roll_month(&mut self, now: i64) -> Result<()> {
+        if now >= self.monthly_reset_at {
+            self.monthly_scan_count = 0;
+            self.current_discount_tier = 0;
+            self.monthly_reset_at = now
+                .checked_add(2592000)
+                .ok_or(TreasuryError::ArithmeticOverflow)?; // +30 days
+        }
+        Ok(())
+    }
+
+    /// Counts one paid service call and recomputes the discount tier that
+    /// will apply to the next payment.
+    pub fn record_usage(&mut self, amount_lamports: u64, now: i64) -> Result<()> {
+        self.last_payment_at = now;
+        self.monthly_scan_count = self
+            .monthly_scan_count
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.lifetime_scans = self
+            .lifetime_scans
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_spent_lamports = self
+            .total_spent_lamports
+            .checked_add(amount_lamports)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+
+        self.current_discount_tier =
+            economic_engine::volume_discount_tier(self.monthly_scan_count as u32);
+        Ok(())
+    }
+
+    /// Undoes record_usage for a refunded call. The monthly count is only
+    /// reduced while the payment still falls in the current 30-day window.
+    pub fn reverse_usage(&mut self, amount_lamports: u64, paid_at: i64, now: i64) -> Result<()> {
+        self.roll_month(now)?;
+        let month_start = self
+            .monthly_reset_at
+            .checked_sub(2592000)
+            .ok_or(TreasuryError::ArithmeticOverflow)?; // -30 days
+        if paid_at >= month_start {
+            self.monthly_scan_count = self.monthly_scan_count.saturating_sub(1);
+        }
+        self.lifetime_scans = self.lifetime_scans.saturating_sub(1);
+        self.total_spent_lamports = self.total_spent_lamports.saturating_sub(amount_lamports);
+
+        self.current_discount_tier =
+            economic_engine::volume_discount_tier(self.monthly_scan_count as u32);
+        Ok(())
+    }
+}
+
+// ──────────────────────────────────────────────
+// SPL Vault Helpers
+// ──────────────────────────────────────────────
+
+/// Share of `unvested` that vests between `updated_at` and `now` on a linear
+/// stream ending at `end` (everything once `end` has passed).
+fn stream_vested(unvested: u64, updated_at: i64, end: i64, now: i64) -> Result<u64> {
+    let elapsed = now.saturating_sub(updated_at);
+    if elapsed <= 0 {
+        return Ok(0);
+    }
+    let remaining = end.saturating_sub(updated_at);
+    if elapsed >= remaining {
+        return Ok(unvested);
+    }
+    mul_div(unvested, elapsed as u64, remaining as u64, Rounding::Down)
+        .ok_or(TreasuryError::ArithmeticOverflow.into())
+}
+
+impl SplTreasuryVault {
+    /// Converts a lamport price into base units of this mint at the governed
+    /// rate. Rounded up so the treasury never receives less than the SOL price.
+    pub fn lamports_to_tokens(&self, lamports: u64) -> Result<u64> {
+        mul_div(lamports, self.token_units_per_sol, LAMPORTS_PER_SOL, Rounding::Up)
+            .ok_or(TreasuryError::ArithmeticOverflow.into())
+    }
+
+    /// Books a payment already transferred in, mirroring TreasuryVault accounting.
+    /// The creator share stays in the token account as escrow on this mint's
+    /// creator stream, outside total_balance.
+    pub fn credit_revenue(&mut self, amount: u64, split: &RevenueSplit, now: i64) -> Result<()> {
+        let vault_total = split
+            .operations
+            .checked_add(split.reserve)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_balance = self
+            .total_balance
+            .checked_add(vault_total)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.reserved = self
+            .reserved
+            .checked_add(split.reserve)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.operations = self
+            .operations
+            .checked_add(split.operations)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_revenue_lifetime = self
+            .total_revenue_lifetime
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.accrue_creator(split.creator, now)?;
+        self.free_balance = self
+            .total_balance
+            .checked_sub(self.reserved)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Token counterpart of CCSConfig::vest_creator for this mint's stream.
+    pub fn vest_creator(&mut self, now: i64) -> Result<()> {
+        if now <= self.creator_stream_updated_at {
+            return Ok(());
+        }
+        let vested = stream_vested(
+            self.creator_unvested,
+            self.creator_stream_updated_at,
+            self.creator_stream_end,
+            now,
+        )?;
+        self.creator_unvested -= vested;
+        self.creator_accumulated = self
+            .creator_accumulated
+            .checked_add(vested)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.creator_stream_updated_at = now;
+        Ok(())
+    }
+
+    /// Token counterpart of CCSConfig::accrue_creator: the unvested balance
+    /// restarts its CREATOR_STREAM_SECONDS stream on every accrual.
+    pub fn accrue_creator(&mut self, creator_amount: u64, now: i64) -> Result<()> {
+        self.vest_creator(now)?;
+        self.creator_unvested = self
+            .creator_unvested
+            .checked_add(creator_amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_creator_accrued = self
+            .total_creator_accrued
+            .checked_add(creator_amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.creator_stream_end = now
+            .checked_add(CREATOR_STREAM_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Books a governed outflow from the operations bucket or, with
+    /// `from_reserve`, from the reserve, which must still cover
+    /// RESERVE_RATIO_BPS of the remaining balance (A0-3).
+    pub fn debit_withdrawal(&mut self, amount: u64, from_reserve: bool, now: i64) -> Result<()> {
+        let balance_after = self
+            .total_balance
+            .checked_sub(amount)
+            .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+        if from_reserve {
+            let reserved_after = self
+                .reserved
+                .checked_sub(amount)
+                .ok_or(TreasuryError::InsufficientTreasuryBalance)?;
+            let required_reserve = apply_bps(balance_after, RESERVE_RATIO_BPS, Rounding::Up)
+                .ok_or(TreasuryError::ArithmeticOverflow)?;
+            require!(
+                reserved_after >= required_reserve,
+                TreasuryError::ReserveRatioBreach
+            );
+            self.reserved = reserved_after;
+        } else {
+            self.operations = self
+                .operations
+                .checked_sub(amount)
+                .ok_or(TreasuryError::InsufficientOperationsBalance)?;
+        }
+        self.total_balance = balance_after;
+        self.free_balance = self
+            .total_balance
+            .checked_sub(self.reserved)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+/// Creates the treasury token account PDA for a newly allowlisted mint, owned
+/// by the SplTreasuryVault PDA. Done by hand rather than with `init_if_needed`,
+/// whose token-account codegen needs anchor-spl's token_2022 module. Tolerates
+/// lamports sent to the address beforehand.
+fn create_vault_token_account<'info>(
+    accounts: &SetSplMintAllowlist<'info>,
+    bump: u8,
+) -> Result<()> {
+    let token_account = accounts.vault_token_account.to_account_info();
+    let system = accounts.system_program.to_account_info();
+    let mint = accounts.mint.key();
+    let bump = [bump];
+    let seeds: &[&[u8]] = &[b"spl_vault_tokens", mint.as_ref(), &bump];
+
+    let rent_minimum = Rent::get()?.minimum_balance(TokenAccount::LEN);
+    let top_up = rent_minimum.saturating_sub(token_account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system.clone(),
+                system_program::Transfer {
+                    from: accounts.aeon_authority.to_account_info(),
+                    to: token_account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system.clone(),
+            system_program::Allocate {
+                account_to_allocate: token_account.clone(),
+            },
+            &[seeds],
+        ),
+        TokenAccount::LEN as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system,
+            system_program::Assign {
+                account_to_assign: token_account.clone(),
+            },
+            &[seeds],
+        ),
+        &token::ID,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        accounts.token_program.to_account_info(),
+        token::InitializeAccount3 {
+            account: token_account,
+            mint: accounts.mint.to_account_info(),
+            authority: accounts.spl_vault.to_account_info(),
+        },
+    ))
+}
+
+// ──────────────────────────────────────────────
+// Governance Helpers
+// ──────────────────────────────────────────────
+
+/// Checks that a noumen-core PolicyProposal was executed on the expected layer,
+/// waited at least `min_delay` seconds, and commits to `change_hash`.
+/// Single use is enforced separately by the AppliedProposal marker PDA.
+pub fn require_executed_proposal(
+    proposal: &noumen_core::PolicyProposal,
+    policy_layer: u8,
+    min_delay: i64,
+    change_hash: [u8; 32],
+) -> Result<()> {
+    // noumen-core status: 0 = Pending, 1 = Executed
+    require!(proposal.status == 1, TreasuryError::ProposalNotExecuted);
+    require!(
+        proposal.policy_layer == policy_layer,
+        TreasuryError::InvalidPolicyLayer
+    );
+    let delay = proposal
+        .delay_until
+        .checked_sub(proposal.proposed_at)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    require!(delay >= min_delay, TreasuryError::InsufficientPolicyDelay);
+    require!(
+        proposal.change_hash == change_hash,
+        TreasuryError::PolicyChangeHashMismatch
+    );
+    Ok(())
+}
+
+/// Billing periods are at least a day; grace is at least an hour (a zero grace
+/// would lapse every due period before it could be charged) and at most one period.
+pub fn validate_subscription_period(period_seconds: i64, grace_seconds: i64) -> Result<()> {
+    require!(
+        period_seconds >= MIN_SUBSCRIPTION_PERIOD_SECONDS
+            && (MIN_SUBSCRIPTION_GRACE_SECONDS..=period_seconds).contains(&grace_seconds),
+        TreasuryError::InvalidSubscriptionPeriod
+    );
+    Ok(())
+}
+
+// ──────────────────────────────────────────────
+// CCS Helpers
+// ──────────────────────────────────────────────
+
+/// Result of splitting one payment between operations, reserve and creator.
+pub struct RevenueSplit {
+    pub operations: u64,
+    pub reserve: u64,
+    pub creator: u64,
+    /// Referral share carved out of operations (SOL service payments only)
+    pub referral: u64,
+    pub band: u8,
+    pub base_bps: u16,
+    pub stipend_bps: u16,
+}
+
+impl RevenueSplit {
+    /// Moves `amount` from the operations bucket to the referral share.
+    pub fn carve_referral(&mut self, amount: u64) -> Result<()> {
+        self.operations = self
+            .operations
+            .checked_sub(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.referral = self
+            .referral
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+impl CCSConfig {
+    /// Index of the highest band whose threshold <= avg_7d_revenue (daily average).
+    pub fn select_band(&self) -> usize {
+        self.bands
+            .iter()
+            .rposition(|b| self.avg_7d_revenue >= b.threshold_lamports)
+            .unwrap_or(0)
+    }
+
+    /// Creator compensation in bps: (base split, performance stipend).
+    /// Base split is the selected band's, never below floor_base_split_bps.
+    /// The stipend ceiling is min(band.max_stipend_bps, cap_stipend_bps), scaled by
+    /// avg_7d / avg_30d (capped at 1.0) so it is paid in full only while revenue
+    /// holds up, and is zero without a 30-day history. Total <= cap_total_bps.
+    pub fn creator_split_bps(&self) -> Result<(u16, u16)> {
+        let band = &self.bands[self.select_band()];
+
+        let base_bps = band
+            .base_split_bps
+            .max(self.floor_base_split_bps)
+            .min(self.cap_total_bps);
+
+        let stipend_ceiling = band.max_stipend_bps.min(self.cap_stipend_bps) as u64;
+        let performance_bps = if self.avg_30d_revenue == 0 {
+            0
+        } else {
+            bps_of(self.avg_7d_revenue, self.avg_30d_revenue, Rounding::Down)
+                .ok_or(TreasuryError::ArithmeticOverflow)?
+                .min(BPS_DENOMINATOR)
+        };
+        let stipend = mul_div(stipend_ceiling, performance_bps, BPS_DENOMINATOR, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let stipend_bps = (stipend as u16).min(self.cap_total_bps - base_bps);
+
+        Ok((base_bps, stipend_bps))
+    }
+
+    /// Splits `amount` into operations (40%), creator (CCS bps, rounded down) and
+    /// reserve (remainder, absorbs truncation dust). Parts always sum to `amount`.
+    pub fn split_revenue(&self, amount: u64) -> Result<RevenueSplit> {
+        let (base_bps, stipend_bps) = self.creator_split_bps()?;
+
+        let operations = apply_bps(amount, OPERATIONS_SPLIT_BPS, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let creator = apply_bps(amount, base_bps + stipend_bps, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let reserve = amount
+            .checked_sub(operations)
+            .ok_or(TreasuryError::ArithmeticOverflow)?
+            .checked_sub(creator)
+            .ok_or(TreasuryError::SplitMismatch)?;
+
+        Ok(RevenueSplit {
+            operations,
+            reserve,
+            creator,
+            referral: 0,
+            band: self.select_band() as u8,
+            base_bps,
+            stipend_bps,
+        })
+    }
+
+    /// Moves the share of creator_unvested that has vested since the last update
+    /// into creator_accumulated. Vesting is linear up to creator_stream_end.
+    pub fn vest_creator(&mut self, now: i64) -> Result<()> {
+        if now <= self.creator_stream_updated_at {
+            return Ok(());
+        }
+        let vested = stream_vested(
+            self.creator_unvested,
+            self.creator_stream_updated_at,
+            self.creator_stream_end,
+            now,
+        )?;
+        self.creator_unvested -= vested;
+        self.creator_accumulated = self
+            .creator_accumulated
+            .checked_add(vested)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.creator_stream_updated_at = now;
+        Ok(())
+    }
+
+    /// Adds a payment's creator share to the stream. Each accrual restarts the
+    /// stream: everything still unvested then vests over CREATOR_STREAM_SECONDS.
+    pub fn accrue_creator(&mut self, creator_amount: u64, now: i64) -> Result<()> {
+        self.vest_creator(now)?;
+        self.creator_unvested = self
+            .creator_unvested
+            .checked_add(creator_amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_creator_accrued = self
+            .total_creator_accrued
+            .checked_add(creator_amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.creator_stream_end = now
+            .checked_add(CREATOR_STREAM_SECONDS)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Reverses up to `creator_amount` of accrued creator share for a refund,
+    /// taking unvested lamports first and then vested but unwithdrawn ones.
+    /// Returns the amount actually clawed back.
+    pub fn claw_back_creator(&mut self, creator_amount: u64, now: i64) -> Result<u64> {
+        self.vest_creator(now)?;
+        let from_unvested = creator_amount.min(self.creator_unvested);
+        self.creator_unvested -= from_unvested;
+        let from_accumulated = (creator_amount - from_unvested).min(self.creator_accumulated);
+        self.creator_accumulated -= from_accumulated;
+        let clawed_back = from_unvested + from_accumulated;
+        self.total_creator_accrued = self.total_creator_accrued.saturating_sub(clawed_back);
+        Ok(clawed_back)
+    }
+
+    /// A0-28 envelope for governed band updates: band 0 starts at 0 and
+    /// thresholds strictly increase; base split >= floor; stipend ceiling
+    /// <= stipend cap; base + stipend <= total cap.
+    pub fn validate_bands(&self, bands: &[CCSBand; 4]) -> Result<()> {
+        require!(bands[0].threshold_lamports == 0, TreasuryError::InvalidCcsBands);
+        for pair in bands.windows(2) {
+            require!(
+                pair[1].threshold_lamports > pair[0].threshold_lamports,
+                TreasuryError::InvalidCcsBands
+            );
+        }
+        for band in bands.iter() {
+            require!(
+                band.base_split_bps >= self.floor_base_split_bps,
+                TreasuryError::InvalidCcsBands
+            );
+            require!(
+                band.max_stipend_bps <= self.cap_stipend_bps,
+                TreasuryError::InvalidCcsBands
+            );
+            require!(
+                band.base_split_bps as u32 + band.max_stipend_bps as u32
+                    <= self.cap_total_bps as u32,
+                TreasuryError::InvalidCcsBands
+            );
+        }
+        Ok(())
+    }
+}
+
+impl AppliedProposal {
+    pub fn record(&mut self, proposal_id: u32, change_hash: [u8; 32], now: i64, bump: u8) {
+        self.proposal_id = proposal_id;
+        self.change_hash = change_hash;
+        self.applied_at = now;
+        self.bump = bump;
+    }
+}
+
+// ──────────────────────────────────────────────
+// Accounts (State)
+// ──────────────────────────────────────────────
+
+#[account]
+pub struct TreasuryConfig {
+    pub super_authority: Pubkey,
+    pub aeon_authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub creator_wallet: Pubkey,
+    pub is_initialized: bool,
+    pub bump: u8,
+    // Fields below were added after genesis. They start where `_reserved`
+    // used to, so older accounts only need migrate_treasury_accounts to grow.
+    /// Wallet proposed by propose_creator_wallet; default when none is pending
+    pub pending_creator_wallet: Pubkey,
+    /// Earliest time the pending wallet can accept; 0 when none is pending
+    pub creator_wallet_change_at: i64,
+    /// Governed referral share, bps of each referred payment (0 = disabled)
+    pub referral_share_bps: u16,
+    /// Governed cap on what one referrer can earn over its lifetime
+    pub referral_lifetime_cap_lamports: u64,
+    /// Governed share of attributed operations revenue credited to the owning
+    /// agent's budget (0 = attribution only)
+    pub agent_revenue_share_bps: u16,
+    /// Pre-registered destination of an emergency evacuation (default = none)
+    pub cold_wallet: Pubkey,
+    /// Earliest evacuate_treasury time of an armed evacuation; 0 when disarmed
+    pub evacuation_unlocks_at: i64,
+    /// Time of the last evacuation; non-zero while the treasury is frozen
+    pub evacuated_at: i64,
+    // Carved from `_reserved` (was 32 bytes); the account size is unchanged.
+    /// Number of evacuations so far; referral accruals from an earlier epoch
+    /// left with the evacuated lamports
+    pub evacuation_epoch: u32,
+    /// Time of the most recent evacuation (kept after reinitialize_treasury);
+    /// payments made up to then cannot be refunded
+    pub last_evacuation_at: i64,
+    pub _reserved: [u8; 20],
+}
+
+impl TreasuryConfig {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // super_authority
+        + 32  // aeon_authority
+        + 32  // keeper_authority
+        + 32  // creator_wallet
+        + 1   // is_initialized
+        + 1   // bump
+        + 32  // pending_creator_wallet
+        + 8   // creator_wallet_change_at
+        + 2   // referral_share_bps
+        + 8   // referral_lifetime_cap_lamports
+        + 2   // agent_revenue_share_bps
+        + 32  // cold_wallet
+        + 8   // evacuation_unlocks_at
+        + 8   // evacuated_at
+        + 4   // evacuation_epoch
+        + 8   // last_evacuation_at
+        + 20; // _reserved
+}
+
+#[account]
+pub struct TreasuryVault {
+    pub total_balance_lamports: u64,
+    pub reserved_lamports: u64,
+    pub free_balance_lamports: u64,
+    pub total_revenue_lifetime: u64,
+    pub total_spent_lifetime: u64,
+    pub daily_spend_lamports: u64,
+    pub daily_spend_reset_at: i64,
+    pub total_donations_swept: u64,
+    pub operations_lamports: u64,
+    pub updated_at: i64,
+    pub bump: u8,
+    // Fields below were added after genesis (see TreasuryConfig).
+    /// Sum of (allocated - spent) across all BudgetAllocations
+    pub total_budget_outstanding: u64,
+    /// Outstanding budget per AgentType (Collector, Evaluator, Executor, Auditor)
+    pub budget_outstanding_by_type: [u64; 4],
+    /// Start of the current 365-day reserve release window
+    pub reserve_release_window_start: i64,
+    /// reserved_lamports when the window opened; the annual cap is a share of it
+    pub reserve_release_window_base: u64,
+    pub reserve_released_in_window: u64,
+    pub total_reserve_released: u64,
+    /// Lifetime lamports found by reconcile_treasury without a booked source
+    pub unattributed_inflow_lamports: u64,
+    /// Books minus real lamports at the last reconciliation; blocks outflows
+    pub deficit_lamports: u64,
+    pub last_reconciled_at: i64,
+    /// Lifetime operations_lamports paid out by pay_operational_expense
+    pub total_operational_expenses: u64,
+    /// Reserve lamports currently delegated to native stake accounts. Still
+    /// counted in total_balance_lamports and reserved_lamports.
+    pub staked_lamports: u64,
+    /// Lifetime staking rewards (non-revenue income)
+    pub total_stake_rewards: u64,
+    /// Stake accounts ever created; index of the next one
+    pub stake_account_count: u32,
+    /// Creator share held in the PDA for the creator stream; a liability, not
+    /// part of total_balance_lamports
+    pub creator_escrow_lamports: u64,
+    /// Lifetime lamports paid out by disburse_agent_budget
+    pub total_budget_disbursed: u64,
+    /// Lifetime gross payment amounts reversed by refund_service_payment
+    pub total_revenue_refunded: u64,
+    /// Statements closed; index of the next TreasuryStatement
+    pub statement_count: u32,
+    pub period_started_at: i64,
+    pub last_statement_hash: [u8; 32],
+    /// Counters and balances at the last period close (zero before the first)
+    pub period_snapshot: TreasurySnapshot,
+    /// Referral shares accrued but not yet claimed; a liability, not part of
+    /// total_balance_lamports
+    pub referral_payable_lamports: u64,
+    /// Lifetime referral rewards claimed
+    pub total_referral_paid: u64,
+    /// Lifetime lamports moved to the cold wallet by evacuate_treasury
+    pub total_evacuated_lamports: u64,
+    pub _reserved: [u8; 48],
+}
+
+impl TreasuryVault {
+    pub const LEN: usize = 8  // discriminator
+        + 8   // total_balance_lamports
+        + 8   // reserved_lamports
+        + 8   // free_balance_lamports
+        + 8   // total_revenue_lifetime
+        + 8   // total_spent_lifetime
+        + 8   // daily_spend_lamports
+        + 8   // daily_spend_reset_at
+        + 8   // total_donations_swept
+        + 8   // operations_lamports
+        + 8   // updated_at
+        + 1   // bump
+        + 8   // total_budget_outstanding
+        + 8 * 4 // budget_outstanding_by_type
+        + 8   // reserve_release_window_start
+        + 8   // reserve_release_window_base
+        + 8   // reserve_released_in_window
+        + 8   // total_reserve_released
+        + 8   // unattributed_inflow_lamports
+        + 8   // deficit_lamports
+        + 8   // last_reconciled_at
+        + 8   // total_operational_expenses
+        + 8   // staked_lamports
+        + 8   // total_stake_rewards
+        + 4   // stake_account_count
+        + 8   // creator_escrow_lamports
+        + 8   // total_budget_disbursed
+        + 8   // total_revenue_refunded
+        + 4   // statement_count
+        + 8   // period_started_at
+        + 32  // last_statement_hash
+        + TreasurySnapshot::LEN // period_snapshot
+        + 8   // referral_payable_lamports
+        + 8   // total_referral_paid
+        + 8   // total_evacuated_lamports
+        + 48; // _reserved
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct TreasurySnapshot {
+    /// total_revenue_lifetime + total_revenue_refunded (never decreases)
+    pub revenue_gross: u64,
+    pub revenue_refunded: u64,
+    pub donations_swept: u64,
+    pub creator_paid: u64,
+    pub operational_expenses: u64,
+    pub budgets_disbursed: u64,
+    pub reserve_released: u64,
+    pub stake_rewards: u64,
+    pub unattributed_inflow: u64,
+    pub referral_paid: u64,
+    pub evacuated: u64,
+    pub total_balance: u64,
+    pub reserved: u64,
+}
+
+impl TreasurySnapshot {
+    pub const LEN: usize = 8 * 13;
+}
+
+/// TreasuryStatement: immutable financial statement for one closed period.
+/// statement_hash = SHA-256("treasury_statement" || borsh(statement with
+/// statement_hash zeroed)); it covers prev_statement_hash, chaining the series.
+#[account]
+pub struct TreasuryStatement {
+    pub period_index: u32,
+    pub period_start: i64,
+    pub period_end: i64,
+    /// Gross service and subscription revenue, before refunds
+    pub revenue: u64,
+    pub refunds: u64,
+    pub donations_swept: u64,
+    pub creator_paid: u64,
+    pub operations_spent: u64,
+    pub budgets_disbursed: u64,
+    pub reserve_released: u64,
+    pub stake_rewards: u64,
+    pub unattributed_inflow: u64,
+    pub referral_paid: u64,
+    pub evacuated: u64,
+    pub opening_balance: u64,
+    pub closing_balance: u64,
+    pub opening_reserved: u64,
+    pub closing_reserved: u64,
+    pub reserve_delta: i64,
+    pub prev_statement_hash: [u8; 32],
+    pub statement_hash: [u8; 32],
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl TreasuryStatement {
+    pub const LEN: usize = 8  // discriminator
+        + 4   // period_index
+        + 8   // period_start
+        + 8   // period_end
+        + 8   // revenue
+        + 8   // refunds
+        + 8   // donations_swept
+        + 8   // creator_paid
+        + 8   // operations_spent
+        + 8   // budgets_disbursed
+        + 8   // reserve_released
+        + 8   // stake_rewards
+        + 8   // unattributed_inflow
+        + 8   // referral_paid
+        + 8   // evacuated
+        + 8   // opening_balance
+        + 8   // closing_balance
+        + 8   // opening_reserved
+        + 8   // closing_reserved
+        + 8   // reserve_delta
+        + 32  // prev_statement_hash
+        + 32  // statement_hash
+        + 1   // bump
+        + 32; // _reserved
+
+    pub fn compute_hash(&self) -> Result<[u8; 32]> {
+        let mut body = self.clone();
+        body.statement_hash = [0u8; 32];
+        Ok(anchor_lang::solana_program::hash::hashv(&[
+            b"treasury_statement",
+            &body.try_to_vec()?,
+        ])
+        .to_bytes())
+    }
+}
+
+#[account]
+pub struct CCSConfig {
+    pub bands: [CCSBand; 4],
+    pub cap_total_bps: u16,
+    pub floor_base_split_bps: u16,
+    pub cap_stipend_bps: u16,
+    pub avg_7d_revenue: u64,
+    pub avg_30d_revenue: u64,
+    /// Lifetime creator share actually withdrawn to creator_wallet
+    pub total_creator_paid: u64,
+    /// Vested creator share available to withdraw
+    pub creator_accumulated: u64,
+    pub bump: u8,
+    // Carved from `_reserved` (was 48 bytes); the account size is unchanged.
+    /// Accrued creator share not yet vested
+    pub creator_unvested: u64,
+    pub creator_stream_updated_at: i64,
+    /// Time at which creator_unvested is fully vested
+    pub creator_stream_end: i64,
+    /// Lifetime creator share accrued, net of refund clawbacks
+    pub total_creator_accrued: u64,
+    pub _reserved: [u8; 16],
+}
+
+impl CCSConfig {
+    pub const LEN: usize = 8  // discriminator
+        + CCSBand::LEN * 4    // 4 bands
+        + 2   // cap_total_bps
+        + 2   // floor_base_split_bps
+        + 2   // cap_stipend_bps
+        + 8   // avg_7d_revenue
+        + 8   // avg_30d_revenue
+        + 8   // total_creator_paid
+        + 8   // creator_accumulated
+        + 1   // bump
+        + 8   // creator_unvested
+        + 8   // creator_stream_updated_at
+        + 8   // creator_stream_end
+        + 8   // total_creator_accrued
+        + 16; // _reserved
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct CCSBand {
+    pub threshold_lamports: u64,
+    pub base_split_bps: u16,
+    pub max_stipend_bps: u16,
+}
+
+impl CCSBand {
+    pub const LEN: usize = 8  // threshold_lamports
+        + 2  // base_split_bps
+        + 2; // max_stipend_bps
+}
+
+#[account]
+pub struct DonationVault {
+    pub total_received: u64,
+    pub pending_sweep: u64,
+    pub sweep_count: u32,
+    pub last_sweep_at: i64,
+    pub bump: u8,
+    // Fields below were added after genesis (see TreasuryConfig).
+    pub receipt_count: u64,
+    pub flagged_count: u32,
+    pub flagged_amount: u64,
+    /// Governed minimum age before a receipt can be archived (0 = disabled)
+    pub receipt_retention_seconds: i64,
+    /// Receipts folded into the archive tree; nonce of the next one to archive
+    pub archived_receipt_count: u64,
+    /// Root of the archive Merkle tree (depth ARCHIVE_TREE_DEPTH, zero-padded)
+    pub archived_receipts_root: [u8; 32],
+    /// Rightmost filled node per level, for appending without the full tree
+    pub archive_frontier: [[u8; 32]; ARCHIVE_TREE_DEPTH],
+    // Carved from `_reserved` (was 21 bytes); the account size is unchanged.
+    /// Flagged lamports still in pending_sweep; swept as unattributed inflow
+    pub pending_flagged: u64,
+    /// Receipts with a nonce below this have been swept
+    pub swept_receipt_count: u64,
+    pub _reserved: [u8; 5],
+}
+
+impl DonationVault {
+    pub const LEN: usize = 8  // discriminator
+        + 8   // total_received
+        + 8   // pending_sweep
+        + 4   // sweep_count
+        + 8   // last_sweep_at
+        + 1   // bump
+        + 8   // receipt_count
+        + 4   // flagged_count
+        + 8   // flagged_amount
+        + 8   // receipt_retention_seconds
+        + 8   // archived_receipt_count
+        + 32  // archived_receipts_root
+        + 32 * ARCHIVE_TREE_DEPTH // archive_frontier
+        + 8   // pending_flagged
+        + 8   // swept_receipt_count
+        + 5;  // _reserved
+
+    /// Appends a leaf to the archive tree and returns the new root
+    /// (shared_types::merkle fixed-depth tree: only the frontier is stored).
+    pub fn append_archive_leaf(&mut self, leaf: [u8; 32]) -> Result<[u8; 32]> {
+        let index = self.archived_receipt_count;
+        require!(
+            frontier_append(&mut self.archive_frontier, index, leaf),
+            TreasuryError::ArchiveFull
+        );
+
+        self.archived_receipt_count = index + 1;
+        self.archived_receipts_root =
+            frontier_root(&self.archive_frontier, self.archived_receipt_count);
+        Ok(self.archived_receipts_root)
+    }
+
+    /// Root of the archive tree with no leaves.
+    pub fn empty_archive_root() -> [u8; 32] {
+        empty_subtree_root(ARCHIVE_TREE_DEPTH)
+    }
+}
+
+#[account]
+pub struct BudgetAllocation {
+    pub agent_id: u16,
+    pub allocated: u64,
+    pub spent: u64,
+    pub daily_cap: u64,
+    pub daily_spent: u64,
+    pub daily_reset_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    // Carved from `_reserved` (was 32 bytes); the account size is unchanged.
+    pub agent_type: u8,
+    /// Lifetime gross payments for services this agent owns (net of refunds)
+    pub revenue_generated: u64,
+    /// Lifetime operations revenue credited to `allocated` (net of reversals)
+    pub revenue_credited: u64,
+    pub _reserved: [u8; 15],
+}
+
+impl BudgetAllocation {
+    pub const LEN: usize = 8  // discriminator
+        + 2   // agent_id
+        + 8   // allocated
+        + 8   // spent
+        + 8   // daily_cap
+        + 8   // daily_spent
+        + 8   // daily_reset_at
+        + 8   // updated_at
+        + 1   // bump
+        + 1   // agent_type
+        + 8   // revenue_generated
+        + 8   // revenue_credited
+        + 15; // _reserved
+
+    /// Allocated but not yet disbursed.
+    pub fn outstanding(&self) -> Result<u64> {
+        Ok(self
+            .allocated
+            .checked_sub(self.spent)
+            .ok_or(TreasuryError::ArithmeticOverflow)?)
+    }
+
+    /// Attributes a payment of `gross` to this agent and adds `credit` of its
+    /// operations share to the allocation.
+    pub fn attribute_revenue(&mut self, gross: u64, credit: u64, now: i64) -> Result<()> {
+        self.revenue_generated = self
+            .revenue_generated
+            .checked_add(gross)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.revenue_credited = self
+            .revenue_credited
+            .checked_add(credit)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.allocated = self
+            .allocated
+            .checked_add(credit)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Reverses a refunded payment's attribution. Only the undisbursed part of
+    /// the credit can be taken back. Returns the credit actually reversed.
+    pub fn reverse_attribution(&mut self, gross: u64, credit: u64, now: i64) -> Result<u64> {
+        let reversed = credit.min(self.outstanding()?);
+        self.allocated -= reversed;
+        self.revenue_credited = self.revenue_credited.saturating_sub(reversed);
+        self.revenue_generated = self.revenue_generated.saturating_sub(gross);
+        self.updated_at = now;
+        Ok(reversed)
+    }
+}
+
+/// Reads the BudgetAllocation at `budget_info`, which may not exist (the agent
+/// was never budgeted, or its budget was closed). Callers pin the address to
+/// the owning agent, so an existing budget cannot be left out.
+pub fn load_owner_budget(budget_info: &AccountInfo) -> Result<Option<BudgetAllocation>> {
+    if budget_info.owner != &crate::ID || budget_info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = budget_info.try_borrow_data()?;
+    Ok(Some(BudgetAllocation::try_deserialize(&mut &data[..])?))
+}
+
+/// Writes back a budget read by load_owner_budget.
+pub fn store_owner_budget(budget_info: &AccountInfo, budget: &BudgetAllocation) -> Result<()> {
+    let mut data = budget_info.try_borrow_mut_data()?;
+    budget.try_serialize(&mut &mut data[..])
+}
+
+/// Attributes a payment of `gross` to the owning agent's budget at
+/// `budget_info` when it exists, adding agent_revenue_share_bps of the
+/// payment's `operations` share to the allocation. Returns whether the payment
+/// was attributed and the credit.
+pub fn attribute_to_owner(
+    budget_info: &AccountInfo,
+    config: &TreasuryConfig,
+    vault: &mut TreasuryVault,
+    gross: u64,
+    operations: u64,
+    now: i64,
+) -> Result<(bool, u64)> {
+    let mut budget = match load_owner_budget(budget_info)? {
+        Some(budget) => budget,
+        None => return Ok((false, 0)),
+    };
+    let credit = apply_bps(operations, config.agent_revenue_share_bps, Rounding::Down)
+        .ok_or(TreasuryError::ArithmeticOverflow)?;
+    budget.attribute_revenue(gross, credit, now)?;
+    vault.credit_attributed_budget(budget.agent_type, credit)?;
+    store_owner_budget(budget_info, &budget)?;
+    Ok((true, credit))
+}
+
+/// Reverses a refunded payment's attribution on the budget at `budget_info`.
+/// Nothing is reversed when the payment was not attributed or the budget has
+/// since been closed (close_agent_budget already released its outstanding
+/// allocation). Returns the credit actually reversed.
+pub fn reverse_owner_attribution(
+    budget_info: &AccountInfo,
+    vault: &mut TreasuryVault,
+    attributed: bool,
+    gross: u64,
+    credit: u64,
+    now: i64,
+) -> Result<u64> {
+    if !attributed {
+        return Ok(0);
+    }
+    let mut budget = match load_owner_budget(budget_info)? {
+        Some(budget) => budget,
+        None => return Ok(0),
+    };
+    let reversed = budget.reverse_attribution(gross, credit, now)?;
+    vault.release_outstanding_budget(budget.agent_type, reversed)?;
+    store_owner_budget(budget_info, &budget)?;
+    Ok(reversed)
+}
+
+#[account]
+pub struct DonationReceipt {
+    pub nonce: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub source_wallet_hash: [u8; 32],
+    pub disclosure_mode: u8,
+    pub receipt_status: u8,
+    pub counts_as_donation: bool,
+    pub correlated_payments: u32,
+    pub correlated_flag: bool,
+    pub receipt_hash: [u8; 32],
+    pub bump: u8,
+    pub _reserved: [u8; 42],
+}
+
+impl DonationReceipt {
+    pub const LEN: usize = 8  // discriminator
+        + 8   // nonce
+        + 8   // amount
+        + 8   // timestamp
+        + 32  // source_wallet_hash
+        + 1   // disclosure_mode
+        + 1   // receipt_status
+        + 1   // counts_as_donation
+        + 4   // correlated_payments
+        + 1   // correlated_flag
+        + 32  // receipt_hash
+        + 1   // bump
+        + 42; // _reserved
+
+    /// Archive tree leaf: leaf hash of the receipt's full serialized state.
+    pub fn archive_leaf(&self) -> Result<[u8; 32]> {
+        Ok(merkle_leaf_hash(&self.try_to_vec()?))
+    }
+}
+
+/// VolumeDiscountTracker: Tracks user's service usage for volume-based discounts.
+/// Monthly counter auto-resets every 30 days.
+/// Discount tiers: 0% (0-9), 10% (10-49), 20% (50-99), 30% (100+).
+#[account]
+pub struct VolumeDiscountTracker {
+    pub wallet: Pubkey,
+    pub monthly_scan_count: u16,
+    pub monthly_reset_at: i64,
+    pub lifetime_scans: u32,
+    pub current_discount_tier: u8, // 0=none, 1=10%, 2=20%, 3=30%
+    pub total_spent_lamports: u64,
+    pub bump: u8,
+    // Carved from `_reserved` (was 32 bytes); the account size is unchanged.
+    pub last_payment_at: i64,
+    pub _reserved: [u8; 24],
+}
+
+impl VolumeDiscountTracker {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // wallet
+        + 2   // monthly_scan_count
+        + 8   // monthly_reset_at
+        + 4   // lifetime_scans
+        + 1   // current_discount_tier
+        + 8   // total_spent_lamports
+        + 1   // bump
+        + 8   // last_payment_at
+        + 24; // _reserved
+}
+
+/// RevenueHistory: ring buffer of daily service revenue and swept donations,
+/// indexed by unix day modulo REVENUE_HISTORY_DAYS. Source of truth for the
+/// CCS 7d/30d averages; written only by process_service_payment and
+/// sweep_donations.
+#[account]
+pub struct RevenueHistory {
+    pub daily_revenue: [u64; REVENUE_HISTORY_DAYS],
+    pub daily_donations: [u64; REVENUE_HISTORY_DAYS],
+    pub current_day: i64,   // unix day of the newest bucket
+    pub first_day: i64,     // unix day the history started
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl RevenueHistory {
+    pub const LEN: usize = 8  // discriminator
+        + 8 * REVENUE_HISTORY_DAYS  // daily_revenue
+        + 8 * REVENUE_HISTORY_DAYS  // daily_donations
+        + 8   // current_day
+        + 8   // first_day
+        + 1   // bump
+        + 32; // _reserved
+}
+
+/// PaymentRecord: one SOL service payment and its split, kept for the refund
+/// window. Seeds: [b"payment", payer, payment_nonce]; the payer picks the nonce
+/// and pays the rent, which is returned by close_payment_record.
+#[account]
+pub struct PaymentRecord {
+    pub payer: Pubkey,
+    pub nonce: u64,
+    pub service_id: u16,
+    pub amount_lamports: u64,
+    pub operations_split: u64,
+    pub treasury_reserve_split: u64,
+    pub creator_split: u64,
+    pub paid_at: i64,
+    pub refunded: bool,
+    pub refunded_at: i64,
+    pub donation_correlated: bool,
+    /// Referrer credited by this payment (default when none)
+    pub referrer: Pubkey,
+    pub referral_split: u64,
+    pub owning_agent_id: u16,
+    /// Whether the payment was attributed to the owning agent's budget
+    pub agent_attributed: bool,
+    /// Operations revenue credited to the owning agent's budget
+    pub agent_credit: u64,
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl PaymentRecord {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // payer
+        + 8   // nonce
+        + 2   // service_id
+        + 8   // amount_lamports
+        + 8   // operations_split
+        + 8   // treasury_reserve_split
+        + 8   // creator_split
+        + 8   // paid_at
+        + 1   // refunded
+        + 8   // refunded_at
+        + 1   // donation_correlated
+        + 32  // referrer
+        + 8   // referral_split
+        + 2   // owning_agent_id
+        + 1   // agent_attributed
+        + 8   // agent_credit
+        + 1   // bump
+        + 32; // _reserved
+}
+
+/// Referrer: a registered referral partner. Referral shares accrue here and are
+/// paid out by claim_referral_rewards.
+#[account]
+pub struct Referrer {
+    pub wallet: Pubkey,
+    pub is_active: bool,
+    /// Earned and not yet claimed
+    pub accrued_lamports: u64,
+    /// Lifetime earned, net of refund clawbacks; bounded by the lifetime cap
+    pub total_earned: u64,
+    pub total_claimed: u64,
+    pub referred_payments: u32,
+    pub registered_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    // Carved from `_reserved` (was 32 bytes); the account size is unchanged.
+    /// TreasuryConfig.evacuation_epoch that accrued_lamports belongs to
+    pub evacuation_epoch: u32,
+    pub _reserved: [u8; 28],
+}
+
+impl Referrer {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // wallet
+        + 1   // is_active
+        + 8   // accrued_lamports
+        + 8   // total_earned
+        + 8   // total_claimed
+        + 4   // referred_payments
+        + 8   // registered_at
+        + 8   // updated_at
+        + 1   // bump
+        + 4   // evacuation_epoch
+        + 28; // _reserved
+
+    /// Drops an accrual from before the last evacuation: its lamports left
+    /// with the rest of the vault and are settled from the cold wallet.
+    /// Returns the amount dropped.
+    pub fn settle_evacuated(&mut self, config: &TreasuryConfig) -> u64 {
+        if self.evacuation_epoch == config.evacuation_epoch {
+            return 0;
+        }
+        let settled = self.accrued_lamports;
+        self.accrued_lamports = 0;
+        self.evacuation_epoch = config.evacuation_epoch;
+        settled
+    }
+
+    /// Referral share of a payment: referral_share_bps of `amount` (rounded
+    /// down), bounded by the operations bucket it is carved from and by what is
+    /// left of this referrer's lifetime cap. Zero for inactive referrers.
+    pub fn referral_share(
+        &self,
+        amount: u64,
+        config: &TreasuryConfig,
+        operations: u64,
+    ) -> Result<u64> {
+        if !self.is_active {
+            return Ok(0);
+        }
+        let share = apply_bps(amount, config.referral_share_bps, Rounding::Down)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        let cap_left = config
+            .referral_lifetime_cap_lamports
+            .saturating_sub(self.total_earned);
+        Ok(share.min(operations).min(cap_left))
+    }
+
+    pub fn record_referral(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.accrued_lamports = self
+            .accrued_lamports
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.total_earned = self
+            .total_earned
+            .checked_add(amount)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.referred_payments = self
+            .referred_payments
+            .checked_add(1)
+            .ok_or(TreasuryError::ArithmeticOverflow)?;
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Reverses up to `amount` of unclaimed referral share for a refund.
+    /// Returns the amount actually clawed back.
+    pub fn claw_back(&mut self, amount: u64, now: i64) -> u64 {
+        let clawed_back = amount.min(self.accrued_lamports);
+        self.accrued_lamports -= clawed_back;
+        self.total_earned = self.total_earned.saturating_sub(clawed_back);
+        self.updated_at = now;
+        clawed_back
+    }
+}
+
+/// ReferralLink: binds a payer to the first approved referrer it paid through.
+/// Written once; every later referred payment must name the same referrer, so
+/// a payer cannot route its own payments through fresh referrer wallets.
+#[account]
+pub struct ReferralLink {
+    pub payer: Pubkey,
+    /// Pubkey::default() until the first payment through an active referrer
+    pub referrer: Pubkey,
+    pub linked_at: i64,
+    pub bump: u8,
+    pub _reserved: [u8; 16],
+}
+
+impl ReferralLink {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // payer
+        + 32  // referrer
+        + 8   // linked_at
+        + 1   // bump
+        + 16; // _reserved
+
+    /// Binds an unbound link to `referrer` if it is active; a bound link only
+    /// accepts the referrer it holds (ReferrerMismatch). Returns true when this
+    /// call created the binding.
+    pub fn bind(&mut self, payer: Pubkey, referrer: &Referrer, bump: u8, now: i64) -> Result<bool> {
+        if self.referrer != Pubkey::default() {
+            require!(self.referrer == referrer.wallet, TreasuryError::ReferrerMismatch);
+            return Ok(false);
+        }
+        self.payer = payer;
+        self.bump = bump;
+        if !referrer.is_active {
+            return Ok(false);
+        }
+        self.referrer = referrer.wallet;
+        self.linked_at = now;
+        Ok(true)
+    }
+}
+
+/// PrepaidBalance: a user's prepaid SOL for API-style usage. The PDA itself
+/// holds the lamports; `balance_lamports` excludes its rent-exempt minimum.
+#[account]
+pub struct PrepaidBalance {
+    pub user: Pubkey,
+    pub balance_lamports: u64,
+    pub daily_ceiling_lamports: u64,
+    pub daily_drawn_lamports: u64,
+    pub daily_reset_at: i64,
+    pub total_deposited: u64,
+    pub total_drawn: u64,
+    pub total_withdrawn: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl PrepaidBalance {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // user
+        + 8   // balance_lamports
+        + 8   // daily_ceiling_lamports
+        + 8   // daily_drawn_lamports
+        + 8   // daily_reset_at
+        + 8   // total_deposited
+        + 8   // total_drawn
+        + 8   // total_withdrawn
+        + 8   // created_at
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // _reserved
+}
+
+/// PrepaidDraw: one query billed to a PrepaidBalance, kept for the refund
+/// window. Seeds: [b"prepaid_draw", user, query_id]; existing once per query,
+/// it stops the same query from being drawn twice. The drawer pays the rent,
+/// which is returned by close_prepaid_draw.
+#[account]
+pub struct PrepaidDraw {
+    pub user: Pubkey,
+    /// Drawer-attested id of the off-chain query
+    pub query_id: [u8; 32],
+    pub service_id: u16,
+    pub amount_lamports: u64,
+    pub operations_split: u64,
+    pub treasury_reserve_split: u64,
+    pub creator_split: u64,
+    /// Keeper or agent authority that drew the query and paid the rent
+    pub drawn_by: Pubkey,
+    pub drawn_at: i64,
+    pub refunded: bool,
+    pub bump: u8,
+    // Carved from `_reserved` (was 16 bytes); the account size is unchanged.
+    pub owning_agent_id: u16,
+    /// Whether the draw was attributed to the owning agent's budget
+    pub agent_attributed: bool,
+    /// Operations revenue credited to the owning agent's budget
+    pub agent_credit: u64,
+    pub _reserved: [u8; 5],
+}
+
+impl PrepaidDraw {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // user
+        + 32  // query_id
+        + 2   // service_id
+        + 8   // amount_lamports
+        + 8   // operations_split
+        + 8   // treasury_reserve_split
+        + 8   // creator_split
+        + 32  // drawn_by
+        + 8   // drawn_at
+        + 1   // refunded
+        + 1   // bump
+        + 2   // owning_agent_id
+        + 1   // agent_attributed
+        + 8   // agent_credit
+        + 5;  // _reserved
+}
+
+/// SubscriptionPlan: a recurring billing plan (e.g. Pro, Institutional).
+#[account]
+pub struct SubscriptionPlan {
+    pub plan_id: u16,
+    /// App-defined plan tier (e.g. 1 = Pro, 2 = Institutional)
+    pub tier: u8,
+    pub price_lamports: u64,
+    pub period_seconds: i64,
+    /// How long a due period may stay unpaid before the subscription lapses
+    pub grace_seconds: i64,
+    pub is_active: bool,
+    /// Subscriptions to this plan that have not been closed
+    pub open_subscriptions: u32,
+    pub total_revenue: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    // Carved from `_reserved` (was 32 bytes); the account size is unchanged.
+    /// Whether the plan belongs to an agent; its charges are then attributed
+    pub agent_owned: bool,
+    /// Owning agent when agent_owned (0 otherwise)
+    pub owning_agent_id: u16,
+    pub _reserved: [u8; 29],
+}
+
+impl SubscriptionPlan {
+    pub const LEN: usize = 8  // discriminator
+        + 2   // plan_id
+        + 1   // tier
+        + 8   // price_lamports
+        + 8   // period_seconds
+        + 8   // grace_seconds
+        + 1   // is_active
+        + 4   // open_subscriptions
+        + 8   // total_revenue
+        + 8   // created_at
+        + 8   // updated_at
+        + 1   // bump
+        + 1   // agent_owned
+        + 2   // owning_agent_id
+        + 29; // _reserved
+
+    /// Owning agent, if any.
+    pub fn owner(&self) -> Option<u16> {
+        self.agent_owned.then_some(self.owning_agent_id)
+    }
+}
+
+/// Subscription: one user's subscription to a plan. The PDA also holds the
+/// user-funded escrow (escrow_lamports above rent) that charges are pulled from.
 #[account]
-pub struct CCSConfig {
-    pub bands: [CCSBand; 4],
-    pub cap_total_bps: u16,
-    pub floor_base_split_bps: u16,
-    pub cap_stipend_bps: u16,
-    pub avg_7d_revenue: u64,
-    pub avg_30d_revenue: u64,
-    pub total_creator_paid: u64,
+pub struct Subscription {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    /// 0 = Active, 1 = PastDue, 2 = Lapsed, 3 = Cancelled
+    pub status: u8,
+    /// Highest per-period price the user approved
+    pub max_price_lamports: u64,
+    /// Total the user still allows to be charged
+    pub allowance_remaining: u64,
+    pub escrow_lamports: u64,
+    /// End of the last paid period; the next charge is due at this time
+    pub paid_through: i64,
+    pub periods_charged: u32,
+    pub total_charged: u64,
+    pub started_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl Subscription {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // user
+        + 2   // plan_id
+        + 1   // status
+        + 8   // max_price_lamports
+        + 8   // allowance_remaining
+        + 8   // escrow_lamports
+        + 8   // paid_through
+        + 4   // periods_charged
+        + 8   // total_charged
+        + 8   // started_at
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // _reserved
+
+    /// Lapsed and cancelled subscriptions take no charges, funding or
+    /// allowance changes.
+    pub fn require_open(&self) -> Result<()> {
+        require!(
+            self.status == SUBSCRIPTION_ACTIVE || self.status == SUBSCRIPTION_PAST_DUE,
+            TreasuryError::SubscriptionNotOpen
+        );
+        Ok(())
+    }
+}
+
+/// SplTreasuryVault: per-mint accounting for the SPL payment rail, mirroring
+/// TreasuryVault. Tokens are held in `token_account`, a treasury-owned token
+/// account whose authority is this PDA. Only allowlisted mints accept payments.
+#[account]
+pub struct SplTreasuryVault {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub is_allowed: bool,
+    pub token_units_per_sol: u64,     // governed rate: base units per 1 SOL of list price
+    pub total_balance: u64,
+    pub reserved: u64,
+    pub free_balance: u64,
+    pub operations: u64,
+    pub total_revenue_lifetime: u64,
+    pub total_creator_paid: u64,        // withdrawn by the creator
+    pub allowlist_proposal_id: u32,
+    pub updated_at: i64,
+    pub token_account_bump: u8,
+    pub bump: u8,
+    pub governed_token_units_per_sol: u64, // rate of the last governed update; centre of the keeper band
+    pub rate_updated_at: i64,
+    pub total_withdrawn: u64,
+    /// Vested creator share not yet withdrawn
     pub creator_accumulated: u64,
+    /// Escrowed creator share still vesting
+    pub creator_unvested: u64,
+    pub creator_stream_updated_at: i64,
+    /// Time at which creator_unvested is fully vested
+    pub creator_stream_end: i64,
+    pub total_creator_accrued: u64,
+    pub _reserved: [u8; 32],
+}
+
+impl SplTreasuryVault {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // mint
+        + 32  // token_account
+        + 1   // is_allowed
+        + 8   // token_units_per_sol
+        + 8   // total_balance
+        + 8   // reserved
+        + 8   // free_balance
+        + 8   // operations
+        + 8   // total_revenue_lifetime
+        + 8   // total_creator_paid
+        + 4   // allowlist_proposal_id
+        + 8   // updated_at
+        + 1   // token_account_bump
+        + 1   // bump
+        + 8   // governed_token_units_per_sol
+        + 8   // rate_updated_at
+        + 8   // total_withdrawn
+        + 8   // creator_accumulated
+        + 8   // creator_unvested
+        + 8   // creator_stream_updated_at
+        + 8   // creator_stream_end
+        + 8   // total_creator_accrued
+        + 32; // _reserved
+}
+
+/// ExpensePayee: registry entry for a wallet that may receive operational
+/// expense payouts from operations_lamports.
+#[account]
+pub struct ExpensePayee {
+    pub wallet: Pubkey,
+    /// Hash of the off-chain payee description (vendor, contract)
+    pub label_hash: [u8; 32],
+    pub is_active: bool,
+    pub total_paid: u64,
+    pub payment_count: u32,
+    pub registered_at: i64,
+    pub updated_at: i64,
     pub bump: u8,
-    pub _reserved: [u8; 48],
+    pub _reserved: [u8; 32],
+}
+
+impl ExpensePayee {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // wallet
+        + 32  // label_hash
+        + 1   // is_active
+        + 8   // total_paid
+        + 4   // payment_count
+        + 8   // registered_at
+        + 8   // updated_at
+        + 1   // bump
+        + 32; // _reserved
+}
+
+/// ExpenseRecord: expense ledger entry, one per paid invoice. Keyed by
+/// invoice_hash so every outflow is auditable against exactly one invoice.
+#[account]
+pub struct ExpenseRecord {
+    pub invoice_hash: [u8; 32],
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub paid_at: i64,
+    pub operations_after: u64,
+    pub bump: u8,
+    pub _reserved: [u8; 16],
+}
+
+impl ExpenseRecord {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // invoice_hash
+        + 32  // payee
+        + 8   // amount
+        + 8   // paid_at
+        + 8   // operations_after
+        + 1   // bump
+        + 16; // _reserved
+}
+
+/// TreasuryStake: bookkeeping for one native stake account funded from the
+/// reserve. Kept after withdrawal as an audit record.
+#[account]
+pub struct TreasuryStake {
+    pub index: u32,
+    pub stake_account: Pubkey,
+    pub vote_account: Pubkey,
+    pub principal_lamports: u64,
+    pub status: u8, // 0=Active, 1=Deactivating, 2=Withdrawn
+    pub delegated_at: i64,
+    pub deactivated_at: i64,
+    pub withdrawn_at: i64,
+    pub withdrawn_lamports: u64,
+    pub proposal_id: u32,
+    pub bump: u8,
+    pub _reserved: [u8; 32],
+}
+
+impl TreasuryStake {
+    pub const LEN: usize = 8  // discriminator
+        + 4   // index
+        + 32  // stake_account
+        + 32  // vote_account
+        + 8   // principal_lamports
+        + 1   // status
+        + 8   // delegated_at
+        + 8   // deactivated_at
+        + 8   // withdrawn_at
+        + 8   // withdrawn_lamports
+        + 4   // proposal_id
+        + 1   // bump
+        + 32; // _reserved
+}
+
+/// AppliedProposal: marks a noumen-core PolicyProposal as consumed by the
+/// treasury. Created with `init`, so each proposal can be applied only once.
+#[account]
+pub struct AppliedProposal {
+    pub proposal_id: u32,
+    pub change_hash: [u8; 32],
+    pub applied_at: i64,
+    pub bump: u8,
+}
+
+impl AppliedProposal {
+    pub const LEN: usize = 8  // discriminator
+        + 4   // proposal_id
+        + 32  // change_hash
+        + 8   // applied_at
+        + 1;  // bump
+}
+
+// ──────────────────────────────────────────────
+// Instruction Contexts
+// ──────────────────────────────────────────────
+
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(mut)]
+    pub super_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = super_authority,
+        space = TreasuryConfig::LEN,
+        seeds = [b"treasury_config"],
+        bump,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        init,
+        payer = super_authority,
+        space = TreasuryVault::LEN,
+        seeds = [b"treasury_vault"],
+        bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDonations<'info> {
+    #[account(mut)]
+    pub super_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        init,
+        payer = super_authority,
+        space = DonationVault::LEN,
+        seeds = [b"donation_vault"],
+        bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    #[account(
+        init,
+        payer = super_authority,
+        space = CCSConfig::LEN,
+        seeds = [b"ccs_config"],
+        bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    #[account(
+        init,
+        payer = super_authority,
+        space = RevenueHistory::LEN,
+        seeds = [b"revenue_history"],
+        bump,
+    )]
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(service_id: u16, amount_lamports: u64, payment_nonce: u64)]
+pub struct ProcessServicePayment<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Account<'info, CCSConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Account<'info, RevenueHistory>,
+
+    /// Catalog entry for `service_id`; Anchor checks it is owned by noumen-service.
+    /// Supplies level, tier, owning agent, and list/floor price (A0-8).
+    #[account(
+        seeds = [b"service", service_id.to_le_bytes().as_ref()],
+        bump = service_entry.bump,
+        seeds::program = noumen_service::ID,
+    )]
+    pub service_entry: Account<'info, noumen_service::ServiceEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"volume_tracker", payer.key().as_ref()],
+        bump = volume_tracker.bump,
+    )]
+    pub volume_tracker: Account<'info, VolumeDiscountTracker>,
+
+    /// Created for every payment; the payer picks an unused nonce.
+    #[account(
+        init,
+        payer = payer,
+        space = PaymentRecord::LEN,
+        seeds = [b"payment", payer.key().as_ref(), payment_nonce.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional registered referrer; pass the program id to omit.
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer.wallet.as_ref()],
+        bump = referrer.bump,
+        constraint = referrer.wallet != payer.key() @ TreasuryError::SelfReferral,
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+
+    /// CHECK: BudgetAllocation PDA of the service's owning agent, credited with
+    /// its revenue share; may be uninitialized (agent has no budget). Address is
+    /// pinned so a payer cannot leave out an existing budget.
+    #[account(
+        mut,
+        seeds = [b"budget", service_entry.owning_agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub owner_budget: UncheckedAccount<'info>,
+
+    /// The payer's write-once referrer binding; required with `referrer`, pass
+    /// the program id otherwise.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ReferralLink::LEN,
+        seeds = [b"referral_link", payer.key().as_ref()],
+        bump,
+    )]
+    pub referral_link: Option<Box<Account<'info, ReferralLink>>>,
+}
+
+#[derive(Accounts)]
+pub struct RefundServicePayment<'info> {
+    #[account(
+        constraint = authority.key() == treasury_config.keeper_authority
+            || authority.key() == auditor_config.authority @ TreasuryError::UnauthorizedRefundAuthority,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    /// Cross-program read of the noumen-auditor authority.
+    #[account(
+        seeds = [b"auditor_config"],
+        bump = auditor_config.bump,
+        seeds::program = noumen_auditor::ID,
+    )]
+    pub auditor_config: Box<Account<'info, noumen_auditor::AuditorConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+
+    #[account(
+        mut,
+        seeds = [b"payment", payment_record.payer.as_ref(), payment_record.nonce.to_le_bytes().as_ref()],
+        bump = payment_record.bump,
+    )]
+    pub payment_record: Box<Account<'info, PaymentRecord>>,
+
+    /// CHECK: Refund destination, validated against payment_record.payer
+    #[account(
+        mut,
+        constraint = payer.key() == payment_record.payer @ TreasuryError::InvalidRefundRecipient,
+    )]
+    pub payer: AccountInfo<'info>,
+
+    /// Referrer credited by the payment; required when payment_record.referral_split > 0,
+    /// otherwise pass the program id.
+    #[account(
+        mut,
+        seeds = [b"referrer", payment_record.referrer.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+
+    /// CHECK: BudgetAllocation PDA of the payment's owning agent; may be
+    /// uninitialized (never budgeted, or closed since). Ignored when the payment
+    /// was not attributed.
+    #[account(
+        mut,
+        seeds = [b"budget", payment_record.owning_agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub owner_budget: UncheckedAccount<'info>,
+
+    /// The payer's tracker; the refunded call is taken back out of its usage.
+    #[account(
+        mut,
+        seeds = [b"volume_tracker", payment_record.payer.as_ref()],
+        bump = volume_tracker.bump,
+    )]
+    pub volume_tracker: Box<Account<'info, VolumeDiscountTracker>>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePaymentRecord<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"payment", payment_record.payer.as_ref(), payment_record.nonce.to_le_bytes().as_ref()],
+        bump = payment_record.bump,
+    )]
+    pub payment_record: Account<'info, PaymentRecord>,
+
+    /// CHECK: Rent destination, validated against payment_record.payer
+    #[account(
+        mut,
+        constraint = payer.key() == payment_record.payer @ TreasuryError::InvalidRefundRecipient,
+    )]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPrepaidBalance<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = PrepaidBalance::LEN,
+        seeds = [b"prepaid", user.key().as_ref()],
+        bump,
+    )]
+    pub prepaid_balance: Account<'info, PrepaidBalance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositPrepaid<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid", user.key().as_ref()],
+        bump = prepaid_balance.bump,
+    )]
+    pub prepaid_balance: Account<'info, PrepaidBalance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPrepaid<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid", user.key().as_ref()],
+        bump = prepaid_balance.bump,
+    )]
+    pub prepaid_balance: Account<'info, PrepaidBalance>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrepaidDailyCeiling<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid", user.key().as_ref()],
+        bump = prepaid_balance.bump,
+    )]
+    pub prepaid_balance: Account<'info, PrepaidBalance>,
+}
+
+#[derive(Accounts)]
+#[instruction(service_id: u16, query_id: [u8; 32])]
+pub struct DrawPrepaidQuery<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == treasury_config.keeper_authority
+            || (authority.key() == agent_manifest.authority
+                && agent_manifest.status == AgentStatus::Active as u8)
+            @ TreasuryError::UnauthorizedPrepaidDraw,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid", prepaid_balance.user.as_ref()],
+        bump = prepaid_balance.bump,
+    )]
+    pub prepaid_balance: Box<Account<'info, PrepaidBalance>>,
+
+    /// Catalog entry for `service_id`; Anchor checks it is owned by noumen-service.
+    #[account(
+        seeds = [b"service", service_id.to_le_bytes().as_ref()],
+        bump = service_entry.bump,
+        seeds::program = noumen_service::ID,
+    )]
+    pub service_entry: Box<Account<'info, noumen_service::ServiceEntry>>,
+
+    /// Manifest of the service's owning agent, whose authority may draw.
+    /// Derived from the service, so an agent cannot draw for another's service.
+    #[account(
+        seeds = [b"agent", service_entry.owning_agent_id.to_le_bytes().as_ref()],
+        bump = agent_manifest.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub agent_manifest: Box<Account<'info, noumen_core::AgentManifest>>,
+
+    #[account(
+        mut,
+        seeds = [b"volume_tracker", prepaid_balance.user.as_ref()],
+        bump = volume_tracker.bump,
+    )]
+    pub volume_tracker: Box<Account<'info, VolumeDiscountTracker>>,
+
+    /// Fails if the query was already drawn.
+    #[account(
+        init,
+        payer = authority,
+        space = PrepaidDraw::LEN,
+        seeds = [b"prepaid_draw", prepaid_balance.user.as_ref(), query_id.as_ref()],
+        bump,
+    )]
+    pub prepaid_draw: Box<Account<'info, PrepaidDraw>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: BudgetAllocation PDA of the service's owning agent; may be
+    /// uninitialized (agent has no budget). Address is pinned so a drawer
+    /// cannot leave out an existing budget.
+    #[account(
+        mut,
+        seeds = [b"budget", service_entry.owning_agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub owner_budget: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPrepaidDraw<'info> {
+    #[account(
+        constraint = authority.key() == treasury_config.keeper_authority
+            || authority.key() == auditor_config.authority @ TreasuryError::UnauthorizedRefundAuthority,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    /// Cross-program read of the noumen-auditor authority.
+    #[account(
+        seeds = [b"auditor_config"],
+        bump = auditor_config.bump,
+        seeds::program = noumen_auditor::ID,
+    )]
+    pub auditor_config: Box<Account<'info, noumen_auditor::AuditorConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid_draw", prepaid_draw.user.as_ref(), prepaid_draw.query_id.as_ref()],
+        bump = prepaid_draw.bump,
+    )]
+    pub prepaid_draw: Box<Account<'info, PrepaidDraw>>,
+
+    #[account(
+        mut,
+        seeds = [b"prepaid", prepaid_draw.user.as_ref()],
+        bump = prepaid_balance.bump,
+    )]
+    pub prepaid_balance: Box<Account<'info, PrepaidBalance>>,
+
+    #[account(
+        mut,
+        seeds = [b"volume_tracker", prepaid_draw.user.as_ref()],
+        bump = volume_tracker.bump,
+    )]
+    pub volume_tracker: Box<Account<'info, VolumeDiscountTracker>>,
+
+    /// CHECK: BudgetAllocation PDA of the draw's owning agent; may be
+    /// uninitialized (never budgeted, or closed since). Ignored when the draw
+    /// was not attributed.
+    #[account(
+        mut,
+        seeds = [b"budget", prepaid_draw.owning_agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub owner_budget: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePrepaidDraw<'info> {
+    #[account(
+        mut,
+        close = drawn_by,
+        seeds = [b"prepaid_draw", prepaid_draw.user.as_ref(), prepaid_draw.query_id.as_ref()],
+        bump = prepaid_draw.bump,
+    )]
+    pub prepaid_draw: Account<'info, PrepaidDraw>,
+
+    /// CHECK: Rent destination, validated against prepaid_draw.drawn_by
+    #[account(
+        mut,
+        constraint = drawn_by.key() == prepaid_draw.drawn_by @ TreasuryError::InvalidRefundRecipient,
+    )]
+    pub drawn_by: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct CreateSubscriptionPlan<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = SubscriptionPlan::LEN,
+        seeds = [b"subscription_plan", plan_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSubscriptionPlan<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_plan", subscription_plan.plan_id.to_le_bytes().as_ref()],
+        bump = subscription_plan.bump,
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct Subscribe<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_plan", plan_id.to_le_bytes().as_ref()],
+        bump = subscription_plan.bump,
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Subscription::LEN,
+        seeds = [b"subscription", user.key().as_ref(), plan_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundSubscription<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref(), subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSubscriptionAllowance<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref(), subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+pub struct ChargeSubscription<'info> {
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_plan", subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription_plan.bump,
+    )]
+    pub subscription_plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.user.as_ref(), subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    /// CHECK: BudgetAllocation PDA of the plan's owning agent; may be
+    /// uninitialized (agent has no budget). Required for an agent-owned plan,
+    /// so its charges cannot skip an existing budget; pass the program id for
+    /// a plan without an owner.
+    #[account(
+        mut,
+        seeds = [b"budget", subscription_plan.owning_agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub owner_budget: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref(), subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSubscription<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_plan", subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription_plan.bump,
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"subscription", user.key().as_ref(), subscription.plan_id.to_le_bytes().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+#[instruction(service_id: u16)]
+pub struct ProcessServicePaymentSpl<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault", mint.key().as_ref()],
+        bump = spl_vault.bump,
+    )]
+    pub spl_vault: Box<Account<'info, SplTreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault_tokens", mint.key().as_ref()],
+        bump = spl_vault.token_account_bump,
+        constraint = vault_token_account.key() == spl_vault.token_account @ TreasuryError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Catalog entry for `service_id`; Anchor checks it is owned by noumen-service.
+    #[account(
+        seeds = [b"service", service_id.to_le_bytes().as_ref()],
+        bump = service_entry.bump,
+        seeds::program = noumen_service::ID,
+    )]
+    pub service_entry: Box<Account<'info, noumen_service::ServiceEntry>>,
+
+    #[account(
+        mut,
+        seeds = [b"volume_tracker", payer.key().as_ref()],
+        bump = volume_tracker.bump,
+    )]
+    pub volume_tracker: Box<Account<'info, VolumeDiscountTracker>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetSplMintAllowlist<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = aeon_authority,
+        space = SplTreasuryVault::LEN,
+        seeds = [b"spl_vault", mint.key().as_ref()],
+        bump,
+    )]
+    pub spl_vault: Box<Account<'info, SplTreasuryVault>>,
+
+    /// CHECK: Treasury token account PDA for `mint`, created by the handler on
+    /// first use (authority: spl_vault).
+    #[account(
+        mut,
+        seeds = [b"spl_vault_tokens", mint.key().as_ref()],
+        bump,
+    )]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Box<Account<'info, noumen_core::PolicyProposal>>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Box<Account<'info, AppliedProposal>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSplTokenRate<'info> {
+    #[account(
+        constraint = keeper_authority.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
+    )]
+    pub keeper_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault", spl_vault.mint.as_ref()],
+        bump = spl_vault.bump,
+    )]
+    pub spl_vault: Box<Account<'info, SplTreasuryVault>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSplTreasury<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault", mint.key().as_ref()],
+        bump = spl_vault.bump,
+    )]
+    pub spl_vault: Box<Account<'info, SplTreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault_tokens", mint.key().as_ref()],
+        bump = spl_vault.token_account_bump,
+        constraint = vault_token_account.key() == spl_vault.token_account @ TreasuryError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Any token account of `mint`; committed to by the proposal's change_hash.
+    #[account(mut, token::mint = mint)]
+    pub destination: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Box<Account<'info, noumen_core::PolicyProposal>>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Box<Account<'info, AppliedProposal>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSplCreatorSplit<'info> {
+    #[account(
+        constraint = creator_wallet.key() == treasury_config.creator_wallet @ TreasuryError::InvalidCreatorWallet,
+    )]
+    pub creator_wallet: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault", mint.key().as_ref()],
+        bump = spl_vault.bump,
+    )]
+    pub spl_vault: Box<Account<'info, SplTreasuryVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"spl_vault_tokens", mint.key().as_ref()],
+        bump = spl_vault.token_account_bump,
+        constraint = vault_token_account.key() == spl_vault.token_account @ TreasuryError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = creator_token_account.owner == treasury_config.creator_wallet @ TreasuryError::InvalidCreatorWallet,
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u16)]
+pub struct AllocateAgentBudget<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    /// Cross-program read of the agent's type for per-type budget caps; only
+    /// Active agents can be given a budget.
+    #[account(
+        seeds = [b"agent", agent_id.to_le_bytes().as_ref()],
+        bump = agent_manifest.bump,
+        seeds::program = noumen_core::ID,
+        constraint = agent_manifest.status == AgentStatus::Active as u8 @ TreasuryError::AgentNotActive,
+    )]
+    pub agent_manifest: Account<'info, noumen_core::AgentManifest>,
+
+    /// H-TREAS-2: Changed from init_if_needed to init to prevent silent resets
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = BudgetAllocation::LEN,
+        seeds = [b"budget", agent_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub budget_allocation: Account<'info, BudgetAllocation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// H-TREAS-2: Separate context for updating an existing budget allocation
+#[derive(Accounts)]
+#[instruction(_agent_id: u16)]
+pub struct UpdateAgentBudget<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"budget", _agent_id.to_le_bytes().as_ref()],
+        bump = budget_allocation.bump,
+    )]
+    pub budget_allocation: Account<'info, BudgetAllocation>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u16)]
+pub struct DisburseAgentBudget<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"budget", agent_id.to_le_bytes().as_ref()],
+        bump = budget_allocation.bump,
+    )]
+    pub budget_allocation: Account<'info, BudgetAllocation>,
+
+    /// Cross-program check: the agent must exist in noumen-core and be Active.
+    #[account(
+        seeds = [b"agent", agent_id.to_le_bytes().as_ref()],
+        bump = agent_manifest.bump,
+        seeds::program = noumen_core::ID,
+        constraint = agent_manifest.status == AgentStatus::Active as u8 @ TreasuryError::AgentNotActive,
+    )]
+    pub agent_manifest: Account<'info, noumen_core::AgentManifest>,
+
+    /// CHECK: Validated against agent_manifest.authority
+    #[account(
+        mut,
+        constraint = agent_authority.key() == agent_manifest.authority @ TreasuryError::InvalidAgentAuthority,
+    )]
+    pub agent_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepDonations<'info> {
+    #[account(
+        constraint = keeper.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
+    )]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"donation_vault"],
+        bump = donation_vault.bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
+    )]
+    pub revenue_history: Account<'info, RevenueHistory>,
+}
+
+#[derive(Accounts)]
+pub struct Donate<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"donation_vault"],
+        bump = donation_vault.bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = DonationReceipt::LEN,
+        seeds = [b"donation_receipt", donation_vault.receipt_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub donation_receipt: Account<'info, DonationReceipt>,
+
+    /// CHECK: donor's VolumeDiscountTracker PDA; may be uninitialized (never paid).
+    /// Address is pinned to the donor so a correlated donor cannot pass another one.
+    #[account(
+        seeds = [b"volume_tracker", donor.key().as_ref()],
+        bump,
+    )]
+    pub donor_volume_tracker: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CorrelateDonation<'info> {
+    #[account(
+        mut,
+        seeds = [b"donation_vault"],
+        bump = donation_vault.bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"donation_receipt", donation_receipt.nonce.to_le_bytes().as_ref()],
+        bump = donation_receipt.bump,
+    )]
+    pub donation_receipt: Account<'info, DonationReceipt>,
+
+    #[account(
+        mut,
+        seeds = [b"payment", payment_record.payer.as_ref(), payment_record.nonce.to_le_bytes().as_ref()],
+        bump = payment_record.bump,
+    )]
+    pub payment_record: Account<'info, PaymentRecord>,
+}
+
+#[derive(Accounts)]
+pub struct SetReceiptRetention<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"donation_vault"],
+        bump = donation_vault.bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Account<'info, noumen_core::PolicyProposal>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveDonationReceipt<'info> {
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"donation_vault"],
+        bump = donation_vault.bump,
+    )]
+    pub donation_vault: Box<Account<'info, DonationVault>>,
+
+    /// Oldest unarchived receipt: the seeds pin archival to nonce order.
+    #[account(
+        mut,
+        close = treasury_vault,
+        seeds = [b"donation_receipt", donation_vault.archived_receipt_count.to_le_bytes().as_ref()],
+        bump = donation_receipt.bump,
+    )]
+    pub donation_receipt: Account<'info, DonationReceipt>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u16)]
+pub struct CloseAgentBudget<'info> {
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    /// Cross-program read: only budgets of killed agents can be closed.
+    #[account(
+        seeds = [b"agent", agent_id.to_le_bytes().as_ref()],
+        bump = agent_manifest.bump,
+        seeds::program = noumen_core::ID,
+        constraint = agent_manifest.status == AgentStatus::Killed as u8 @ TreasuryError::AgentNotKilled,
+    )]
+    pub agent_manifest: Account<'info, noumen_core::AgentManifest>,
+
+    #[account(
+        mut,
+        close = aeon_authority,
+        seeds = [b"budget", agent_id.to_le_bytes().as_ref()],
+        bump = budget_allocation.bump,
+    )]
+    pub budget_allocation: Account<'info, BudgetAllocation>,
+
+    /// CHECK: Rent destination, validated against treasury_config.aeon_authority
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCreatorSplit<'info> {
+    #[account(
+        mut,
+        constraint = creator_wallet.key() == treasury_config.creator_wallet @ TreasuryError::InvalidCreatorWallet,
+    )]
+    pub creator_wallet: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Account<'info, CCSConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeCreatorWallet<'info> {
+    #[account(
+        constraint = authority.key() == treasury_config.creator_wallet
+            || authority.key() == treasury_config.super_authority
+            @ TreasuryError::InvalidCreatorWallet,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCreatorWallet<'info> {
+    #[account(
+        constraint = new_creator_wallet.key() == treasury_config.pending_creator_wallet
+            @ TreasuryError::InvalidCreatorWallet,
+    )]
+    pub new_creator_wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterExpensePayee<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    /// CHECK: Any wallet; recorded as the payee.
+    pub payee_wallet: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = ExpensePayee::LEN,
+        seeds = [b"payee", payee_wallet.key().as_ref()],
+        bump,
+    )]
+    pub expense_payee: Account<'info, ExpensePayee>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetExpensePayeeActive<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"payee", expense_payee.wallet.as_ref()],
+        bump = expense_payee.bump,
+    )]
+    pub expense_payee: Account<'info, ExpensePayee>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralPolicy<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Account<'info, noumen_core::PolicyProposal>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAgentRevenueShare<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Account<'info, noumen_core::PolicyProposal>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = Referrer::LEN,
+        seeds = [b"referrer", wallet.key().as_ref()],
+        bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferrerActive<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", referrer.wallet.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer", wallet.key().as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+}
+
+#[derive(Accounts)]
+#[instruction(payee: Pubkey, amount: u64, invoice_hash: [u8; 32])]
+pub struct PayOperationalExpense<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"payee", payee.as_ref()],
+        bump = expense_payee.bump,
+        constraint = expense_payee.is_active @ TreasuryError::PayeeNotActive,
+    )]
+    pub expense_payee: Account<'info, ExpensePayee>,
+
+    /// CHECK: Validated against the `payee` argument (and thus the registry seeds)
+    #[account(
+        mut,
+        constraint = payee_wallet.key() == payee @ TreasuryError::InvalidPayeeWallet,
+    )]
+    pub payee_wallet: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = ExpenseRecord::LEN,
+        seeds = [b"expense", invoice_hash.as_ref()],
+        bump,
+    )]
+    pub expense_record: Account<'info, ExpenseRecord>,
+
+    pub system_program: Program<'info, System>,
 }
 
-impl CCSConfig {
-    pub const LEN: usize = 8  // discriminator
-        + CCSBand::LEN * 4    // 4 bands
-        + 2   // cap_total_bps
-        + 2   // floor_base_split_bps
-        + 2   // cap_stipend_bps
-        + 8   // avg_7d_revenue
-        + 8   // avg_30d_revenue
-        + 8   // total_creator_paid
-        + 8   // creator_accumulated
-        + 1   // bump
-        + 48; // _reserved
-}
+#[derive(Accounts)]
+pub struct UpdateCcsBands<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Account<'info, CCSConfig>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Account<'info, noumen_core::PolicyProposal>,
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
-pub struct CCSBand {
-    pub threshold_lamports: u64,
-    pub base_split_bps: u16,
-    pub max_stipend_bps: u16,
-}
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
 
-impl CCSBand {
-    pub const LEN: usize = 8  // threshold_lamports
-        + 2  // base_split_bps
-        + 2; // max_stipend_bps
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct DonationVault {
-    pub total_received: u64,
-    pub pending_sweep: u64,
-    pub sweep_count: u32,
-    pub last_sweep_at: i64,
-    pub bump: u8,
-    pub _reserved: [u8; 21],
-}
+#[derive(Accounts)]
+pub struct ReleaseReserve<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
 
-impl DonationVault {
-    pub const LEN: usize = 8  // discriminator
-        + 8   // total_received
-        + 8   // pending_sweep
-        + 4   // sweep_count
-        + 8   // last_sweep_at
-        + 1   // bump
-        + 21; // _reserved
-}
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
 
-#[account]
-pub struct BudgetAllocation {
-    pub agent_id: u16,
-    pub allocated: u64,
-    pub spent: u64,
-    pub daily_cap: u64,
-    pub daily_spent: u64,
-    pub daily_reset_at: i64,
-    pub updated_at: i64,
-    pub bump: u8,
-    pub _reserved: [u8; 32],
-}
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
 
-impl BudgetAllocation {
-    pub const LEN: usize = 8  // discriminator
-        + 2   // agent_id
-        + 8   // allocated
-        + 8   // spent
-        + 8   // daily_cap
-        + 8   // daily_spent
-        + 8   // daily_reset_at
-        + 8   // updated_at
-        + 1   // bump
-        + 32; // _reserved
-}
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Account<'info, noumen_core::PolicyProposal>,
 
-#[account]
-pub struct DonationReceipt {
-    pub nonce: u64,
-    pub amount: u64,
-    pub timestamp: i64,
-    pub source_wallet_hash: [u8; 32],
-    pub disclosure_mode: u8,
-    pub receipt_status: u8,
-    pub counts_as_donation: bool,
-    pub correlated_payments: u32,
-    pub correlated_flag: bool,
-    pub receipt_hash: [u8; 32],
-    pub bump: u8,
-    pub _reserved: [u8; 42],
-}
+    #[account(
+        init,
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
 
-impl DonationReceipt {
-    pub const LEN: usize = 8  // discriminator
-        + 8   // nonce
-        + 8   // amount
-        + 8   // timestamp
-        + 32  // source_wallet_hash
-        + 1   // disclosure_mode
-        + 1   // receipt_status
-        + 1   // counts_as_donation
-        + 4   // correlated_payments
-        + 1   // correlated_flag
-        + 32  // receipt_hash
-        + 1   // bump
-        + 42; // _reserved
+    pub system_program: Program<'info, System>,
 }
 
-/// VolumeDiscountTracker: Tracks user's service usage for volume-based discounts.
-/// Monthly counter auto-resets every 30 days.
-/// Discount tiers: 0% (0-9), 10% (10-49), 20% (50-99), 30% (100+).
-#[account]
-pub struct VolumeDiscountTracker {
-    pub wallet: Pubkey,
-    pub monthly_scan_count: u16,
-    pub monthly_reset_at: i64,
-    pub lifetime_scans: u32,
-    pub current_discount_tier: u8, // 0=none, 1=10%, 2=20%, 3=30%
-    pub total_spent_lamports: u64,
-    pub bump: u8,
-    pub _reserved: [u8; 32],
-}
+#[derive(Accounts)]
+pub struct DelegateReserveStake<'info> {
+    #[account(
+        mut,
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
 
-impl VolumeDiscountTracker {
-    pub const LEN: usize = 8  // discriminator
-        + 32  // wallet
-        + 2   // monthly_scan_count
-        + 8   // monthly_reset_at
-        + 4   // lifetime_scans
-        + 1   // current_discount_tier
-        + 8   // total_spent_lamports
-        + 1   // bump
-        + 32; // _reserved
-}
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
 
-// ──────────────────────────────────────────────
-// Instruction Contexts
-// ──────────────────────────────────────────────
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
 
-#[derive(Accounts)]
-pub struct InitializeTreasury<'info> {
-    #[account(mut)]
-    pub super_authority: Signer<'info>,
+    /// CHECK: PDA used only as staker/withdrawer authority; holds nothing.
+    #[account(seeds = [b"stake_authority"], bump)]
+    pub stake_authority: UncheckedAccount<'info>,
+
+    /// CHECK: New native stake account PDA; funded, allocated and assigned here.
+    #[account(
+        mut,
+        seeds = [b"stake_account", treasury_vault.stake_account_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
 
     #[account(
         init,
-        payer = super_authority,
-        space = TreasuryConfig::LEN,
-        seeds = [b"treasury_config"],
+        payer = aeon_authority,
+        space = TreasuryStake::LEN,
+        seeds = [b"treasury_stake", treasury_vault.stake_account_count.to_le_bytes().as_ref()],
         bump,
     )]
-    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+    pub treasury_stake: Box<Account<'info, TreasuryStake>>,
+
+    /// CHECK: Validator vote account; the stake program validates it on delegation.
+    #[account(owner = anchor_lang::solana_program::vote::program::ID @ TreasuryError::InvalidStakeAccount)]
+    pub vote_account: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = policy_proposal.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub policy_proposal: Box<Account<'info, noumen_core::PolicyProposal>>,
 
     #[account(
         init,
-        payer = super_authority,
-        space = TreasuryVault::LEN,
-        seeds = [b"treasury_vault"],
+        payer = aeon_authority,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied_proposal", policy_proposal.proposal_id.to_le_bytes().as_ref()],
         bump,
     )]
-    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+    pub applied_proposal: Box<Account<'info, AppliedProposal>>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: StakeHistory sysvar
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    /// CHECK: Stake config account required by DelegateStake
+    #[account(address = STAKE_CONFIG_ID)]
+    pub stake_config: UncheckedAccount<'info>,
+
+    /// CHECK: Native stake program
+    #[account(address = native_stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateReserveStake<'info> {
+    #[account(
+        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub aeon_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_stake", treasury_stake.index.to_le_bytes().as_ref()],
+        bump = treasury_stake.bump,
+        constraint = treasury_stake.status == 0 @ TreasuryError::StakeNotActive,
+    )]
+    pub treasury_stake: Account<'info, TreasuryStake>,
+
+    /// CHECK: Must be the stake account recorded in treasury_stake
+    #[account(
+        mut,
+        address = treasury_stake.stake_account @ TreasuryError::InvalidStakeAccount,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// CHECK: PDA staker/withdrawer authority
+    #[account(seeds = [b"stake_authority"], bump)]
+    pub stake_authority: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: Native stake program
+    #[account(address = native_stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawReserveStake<'info> {
+    #[account(
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Account<'info, TreasuryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_stake", treasury_stake.index.to_le_bytes().as_ref()],
+        bump = treasury_stake.bump,
+        constraint = treasury_stake.status == 1 @ TreasuryError::StakeNotDeactivated,
+    )]
+    pub treasury_stake: Account<'info, TreasuryStake>,
+
+    /// CHECK: Must be the stake account recorded in treasury_stake
+    #[account(
+        mut,
+        address = treasury_stake.stake_account @ TreasuryError::InvalidStakeAccount,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// CHECK: PDA staker/withdrawer authority
+    #[account(seeds = [b"stake_authority"], bump)]
+    pub stake_authority: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+
+    /// CHECK: StakeHistory sysvar
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+
+    /// CHECK: Native stake program
+    #[account(address = native_stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeDonations<'info> {
-    #[account(mut)]
-    pub super_authority: Signer<'info>,
-
+pub struct ReconcileTreasury<'info> {
     #[account(
         seeds = [b"treasury_config"],
         bump = treasury_config.bump,
         constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
-        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
     )]
     pub treasury_config: Account<'info, TreasuryConfig>,
 
     #[account(
-        init,
-        payer = super_authority,
-        space = DonationVault::LEN,
-        seeds = [b"donation_vault"],
-        bump,
-    )]
-    pub donation_vault: Box<Account<'info, DonationVault>>,
-
-    #[account(
-        init,
-        payer = super_authority,
-        space = CCSConfig::LEN,
-        seeds = [b"ccs_config"],
-        bump,
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
     )]
-    pub ccs_config: Box<Account<'info, CCSConfig>>,
-
-    pub system_program: Program<'info, System>,
+    pub treasury_vault: Account<'info, TreasuryVault>,
 }
 
 #[derive(Accounts)]
-#[instruction(service_id: u16, amount_lamports: u64)]
-pub struct ProcessServicePayment<'info> {
+pub struct CloseTreasuryPeriod<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -971,275 +7546,570 @@ pub struct ProcessServicePayment<'info> {
         bump = treasury_config.bump,
         constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
     )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
 
     #[account(
         mut,
         seeds = [b"treasury_vault"],
         bump = treasury_vault.bump,
     )]
-    pub treasury_vault: Account<'info, TreasuryVault>,
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
 
     #[account(
-        mut,
         seeds = [b"ccs_config"],
         bump = ccs_config.bump,
     )]
-    pub ccs_config: Account<'info, CCSConfig>,
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
 
-    /// CHECK: Validated against treasury_config.creator_wallet
     #[account(
-        mut,
-        constraint = creator_wallet.key() == treasury_config.creator_wallet @ TreasuryError::InvalidCreatorWallet,
+        init,
+        payer = payer,
+        space = TreasuryStatement::LEN,
+        seeds = [b"treasury_statement", treasury_vault.statement_count.to_le_bytes().as_ref()],
+        bump,
     )]
-    pub creator_wallet: AccountInfo<'info>,
+    pub treasury_statement: Box<Account<'info, TreasuryStatement>>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(agent_id: u16)]
-pub struct AllocateAgentBudget<'info> {
+pub struct RegisterColdWallet<'info> {
     #[account(
-        mut,
-        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
     )]
-    pub aeon_authority: Signer<'info>,
+    pub super_authority: Signer<'info>,
 
+    /// Current cold wallet; on first registration, pass the new cold wallet.
     #[account(
-        seeds = [b"treasury_config"],
-        bump = treasury_config.bump,
-        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+        constraint = treasury_config.cold_wallet == Pubkey::default()
+            || current_cold_wallet.key() == treasury_config.cold_wallet
+            @ TreasuryError::InvalidColdWallet,
     )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub current_cold_wallet: Signer<'info>,
 
     #[account(
-        seeds = [b"treasury_vault"],
-        bump = treasury_vault.bump,
+        constraint = new_cold_wallet.key() != Pubkey::default() @ TreasuryError::InvalidColdWallet,
     )]
-    pub treasury_vault: Account<'info, TreasuryVault>,
+    pub new_cold_wallet: Signer<'info>,
 
-    /// H-TREAS-2: Changed from init_if_needed to init to prevent silent resets
     #[account(
-        init,
-        payer = aeon_authority,
-        space = BudgetAllocation::LEN,
-        seeds = [b"budget", agent_id.to_le_bytes().as_ref()],
-        bump,
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
     )]
-    pub budget_allocation: Account<'info, BudgetAllocation>,
-
-    pub system_program: Program<'info, System>,
+    pub treasury_config: Account<'info, TreasuryConfig>,
 }
 
-/// H-TREAS-2: Separate context for updating an existing budget allocation
 #[derive(Accounts)]
-#[instruction(_agent_id: u16)]
-pub struct UpdateAgentBudget<'info> {
+pub struct ArmTreasuryEvacuation<'info> {
     #[account(
-        constraint = aeon_authority.key() == treasury_config.aeon_authority @ TreasuryError::UnauthorizedAeon,
+        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
     )]
-    pub aeon_authority: Signer<'info>,
+    pub super_authority: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [b"treasury_config"],
         bump = treasury_config.bump,
         constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
     )]
     pub treasury_config: Account<'info, TreasuryConfig>,
 
+    /// Cross-program read of the circuit breaker mode.
     #[account(
-        seeds = [b"treasury_vault"],
-        bump = treasury_vault.bump,
+        seeds = [b"aeon_config"],
+        bump = aeon_config.bump,
+        seeds::program = noumen_core::ID,
     )]
-    pub treasury_vault: Account<'info, TreasuryVault>,
+    pub aeon_config: Account<'info, noumen_core::AeonConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTreasuryEvacuation<'info> {
+    #[account(
+        constraint = authority.key() == treasury_config.super_authority
+            || authority.key() == treasury_config.cold_wallet
+            @ TreasuryError::InvalidColdWallet,
+    )]
+    pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"budget", _agent_id.to_le_bytes().as_ref()],
-        bump = budget_allocation.bump,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
     )]
-    pub budget_allocation: Account<'info, BudgetAllocation>,
+    pub treasury_config: Account<'info, TreasuryConfig>,
 }
 
 #[derive(Accounts)]
-pub struct SweepDonations<'info> {
+pub struct EvacuateTreasury<'info> {
     #[account(
-        constraint = keeper.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
+        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
     )]
-    pub keeper: Signer<'info>,
+    pub super_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = cold_wallet.key() == treasury_config.cold_wallet @ TreasuryError::InvalidColdWallet,
+    )]
+    pub cold_wallet: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [b"treasury_config"],
         bump = treasury_config.bump,
         constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
     )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub treasury_config: Box<Account<'info, TreasuryConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault"],
+        bump = treasury_vault.bump,
+    )]
+    pub treasury_vault: Box<Account<'info, TreasuryVault>>,
+
+    /// Cross-program read of the circuit breaker mode.
+    #[account(
+        seeds = [b"aeon_config"],
+        bump = aeon_config.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub aeon_config: Box<Account<'info, noumen_core::AeonConfig>>,
+
+    /// The creator stream is zeroed along with its escrow.
+    #[account(
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
+    )]
+    pub ccs_config: Box<Account<'info, CCSConfig>>,
 
+    /// Pending donations are swept into the vault before it is emptied.
     #[account(
         mut,
         seeds = [b"donation_vault"],
         bump = donation_vault.bump,
     )]
-    pub donation_vault: Account<'info, DonationVault>,
+    pub donation_vault: Box<Account<'info, DonationVault>>,
 
     #[account(
         mut,
-        seeds = [b"treasury_vault"],
-        bump = treasury_vault.bump,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
     )]
-    pub treasury_vault: Account<'info, TreasuryVault>,
+    pub revenue_history: Box<Account<'info, RevenueHistory>>,
+}
 
-    pub system_program: Program<'info, System>,
+#[derive(Accounts)]
+pub struct ReinitializeTreasury<'info> {
+    #[account(
+        constraint = super_authority.key() == treasury_config.super_authority @ TreasuryError::UnauthorizedAeon,
+    )]
+    pub super_authority: Signer<'info>,
+
+    #[account(
+        constraint = cold_wallet.key() == treasury_config.cold_wallet @ TreasuryError::InvalidColdWallet,
+    )]
+    pub cold_wallet: Signer<'info>,
+
+    /// Frozen by evacuate_treasury, so is_initialized is not required here.
+    #[account(
+        mut,
+        seeds = [b"treasury_config"],
+        bump = treasury_config.bump,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+
+    /// Cross-program read of the circuit breaker mode.
+    #[account(
+        seeds = [b"aeon_config"],
+        bump = aeon_config.bump,
+        seeds::program = noumen_core::ID,
+    )]
+    pub aeon_config: Account<'info, noumen_core::AeonConfig>,
 }
 
 #[derive(Accounts)]
-#[instruction(nonce: u64)]
-pub struct RecordDonationReceipt<'info> {
+pub struct MigrateTreasuryAccounts<'info> {
     #[account(mut)]
-    pub keeper: Signer<'info>,
+    pub super_authority: Signer<'info>,
+
+    /// CHECK: May still have the genesis layout, so it cannot be deserialized
+    /// yet. Seeds and owner are checked here; discriminator and
+    /// super_authority are checked in the handler.
+    #[account(mut, seeds = [b"treasury_config"], bump, owner = crate::ID)]
+    pub treasury_config: UncheckedAccount<'info>,
+
+    /// CHECK: As treasury_config.
+    #[account(mut, seeds = [b"treasury_vault"], bump, owner = crate::ID)]
+    pub treasury_vault: UncheckedAccount<'info>,
+
+    /// CHECK: As treasury_config.
+    #[account(mut, seeds = [b"donation_vault"], bump, owner = crate::ID)]
+    pub donation_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct UpdateRevenueAverages<'info> {
     #[account(
         seeds = [b"treasury_config"],
         bump = treasury_config.bump,
         constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
-        constraint = keeper.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
     )]
     pub treasury_config: Account<'info, TreasuryConfig>,
 
     #[account(
-        init,
-        payer = keeper,
-        space = DonationReceipt::LEN,
-        seeds = [b"donation_receipt", nonce.to_le_bytes().as_ref()],
-        bump,
+        mut,
+        seeds = [b"ccs_config"],
+        bump = ccs_config.bump,
     )]
-    pub donation_receipt: Account<'info, DonationReceipt>,
+    pub ccs_config: Account<'info, CCSConfig>,
 
     #[account(
         mut,
-        seeds = [b"donation_vault"],
-        bump = donation_vault.bump,
+        seeds = [b"revenue_history"],
+        bump = revenue_history.bump,
     )]
-    pub donation_vault: Account<'info, DonationVault>,
-
-    pub system_program: Program<'info, System>,
+    pub revenue_history: Account<'info, RevenueHistory>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawCreatorSplit<'info> {
-    #[account(
-        mut,
-        constraint = creator_wallet.key() == treasury_config.creator_wallet @ TreasuryError::InvalidCreatorWallet,
-    )]
-    pub creator_wallet: Signer<'info>,
+pub struct InitializeVolumeTracker<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
 
     #[account(
-        seeds = [b"treasury_config"],
-        bump = treasury_config.bump,
-        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
+        init,
+        payer = wallet,
+        space = VolumeDiscountTracker::LEN,
+        seeds = [b"volume_tracker", wallet.key().as_ref()],
+        bump
     )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub volume_tracker: Account<'info, VolumeDiscountTracker>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ──────────────────────────────────────────────
+// Events
+// ──────────────────────────────────────────────
+
+#[event]
+pub struct TreasuryInitialized {
+    pub super_authority: Pubkey,
+    pub aeon_authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub creator_wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ServicePaymentProcessed {
+    pub service_id: u16,
+    pub payment_nonce: u64,
+    pub amount_lamports: u64,
+    pub list_price_lamports: u64,
+    pub discount_tier: u8,
+    pub service_tier: u8,
+    pub owning_agent_id: u16,
+    pub creator_split: u64,
+    pub treasury_reserve_split: u64,
+    pub operations_split: u64,
+    pub referral_split: u64,
+    /// Default pubkey when the payment had no referrer
+    pub referrer: Pubkey,
+    /// Operations revenue credited to the owning agent's budget
+    pub agent_credit: u64,
+    pub ccs_band: u8,
+    pub creator_base_bps: u16,
+    pub creator_stipend_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ServicePaymentRefunded {
+    pub payer: Pubkey,
+    pub payment_nonce: u64,
+    pub service_id: u16,
+    pub amount_lamports: u64,
+    pub refund_lamports: u64,
+    pub operations_reversed: u64,
+    pub reserve_reversed: u64,
+    pub creator_clawed_back: u64,
+    pub referral_clawed_back: u64,
+    pub agent_credit_reversed: u64,
+    pub reason_hash: [u8; 32],
+    pub refunded_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralPolicyUpdated {
+    pub proposal_id: u32,
+    pub share_bps: u16,
+    pub lifetime_cap_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentRevenueShareUpdated {
+    pub proposal_id: u32,
+    pub share_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferrerUpdated {
+    pub wallet: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralLinked {
+    pub payer: Pubkey,
+    pub referrer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralClaimed {
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentRecordClosed {
+    pub payer: Pubkey,
+    pub payment_nonce: u64,
+    pub refunded: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrepaidBalanceOpened {
+    pub user: Pubkey,
+    pub daily_ceiling_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrepaidDeposited {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub balance_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"treasury_vault"],
-        bump = treasury_vault.bump,
-    )]
-    pub treasury_vault: Account<'info, TreasuryVault>,
+#[event]
+pub struct PrepaidWithdrawn {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub balance_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"ccs_config"],
-        bump = ccs_config.bump,
-    )]
-    pub ccs_config: Account<'info, CCSConfig>,
+#[event]
+pub struct PrepaidDailyCeilingUpdated {
+    pub user: Pubkey,
+    pub daily_ceiling_lamports: u64,
+    pub timestamp: i64,
+}
 
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct PrepaidQueryDrawn {
+    pub user: Pubkey,
+    pub query_id: [u8; 32],
+    pub service_id: u16,
+    pub amount_lamports: u64,
+    pub list_price_lamports: u64,
+    pub discount_tier: u8,
+    pub service_tier: u8,
+    pub owning_agent_id: u16,
+    pub balance_lamports: u64,
+    pub creator_split: u64,
+    pub treasury_reserve_split: u64,
+    pub operations_split: u64,
+    pub agent_credit: u64,
+    pub drawn_by: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct UpdateRevenueAverages<'info> {
-    #[account(
-        constraint = keeper.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
-    )]
-    pub keeper: Signer<'info>,
+#[event]
+pub struct PrepaidDrawRefunded {
+    pub user: Pubkey,
+    pub query_id: [u8; 32],
+    pub service_id: u16,
+    pub amount_lamports: u64,
+    pub refund_lamports: u64,
+    pub operations_reversed: u64,
+    pub reserve_reversed: u64,
+    pub creator_clawed_back: u64,
+    pub agent_credit_reversed: u64,
+    pub balance_lamports: u64,
+    pub reason_hash: [u8; 32],
+    pub refunded_by: Pubkey,
+    pub timestamp: i64,
+}
 
-    #[account(
-        seeds = [b"treasury_config"],
-        bump = treasury_config.bump,
-        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
-    )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+#[event]
+pub struct PrepaidDrawClosed {
+    pub user: Pubkey,
+    pub query_id: [u8; 32],
+    pub refunded: bool,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"ccs_config"],
-        bump = ccs_config.bump,
-    )]
-    pub ccs_config: Account<'info, CCSConfig>,
+#[event]
+pub struct SubscriptionPlanUpdated {
+    pub plan_id: u16,
+    pub tier: u8,
+    pub price_lamports: u64,
+    pub period_seconds: i64,
+    pub grace_seconds: i64,
+    pub is_active: bool,
+    pub owning_agent_id: Option<u16>,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct InitializeVolumeTracker<'info> {
-    #[account(mut)]
-    pub wallet: Signer<'info>,
+#[event]
+pub struct SubscriptionStarted {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub max_price_lamports: u64,
+    pub allowance_lamports: u64,
+    pub escrow_lamports: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init,
-        payer = wallet,
-        space = VolumeDiscountTracker::LEN,
-        seeds = [b"volume_tracker", wallet.key().as_ref()],
-        bump
-    )]
-    pub volume_tracker: Account<'info, VolumeDiscountTracker>,
+#[event]
+pub struct SubscriptionFunded {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub amount: u64,
+    pub escrow_lamports: u64,
+    pub timestamp: i64,
+}
 
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct SubscriptionAllowanceUpdated {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub max_price_lamports: u64,
+    pub allowance_remaining: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-#[instruction(service_id: u16)]
-pub struct TrackVolumeUsage<'info> {
-    #[account(
-        constraint = keeper.key() == treasury_config.keeper_authority @ TreasuryError::UnauthorizedKeeper,
-    )]
-    pub keeper: Signer<'info>,
+#[event]
+pub struct SubscriptionCharged {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub amount_lamports: u64,
+    pub period_index: u32,
+    pub paid_through: i64,
+    pub escrow_lamports: u64,
+    pub creator_split: u64,
+    pub treasury_reserve_split: u64,
+    pub operations_split: u64,
+    pub agent_credit: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        seeds = [b"treasury_config"],
-        bump = treasury_config.bump,
-        constraint = treasury_config.is_initialized @ TreasuryError::NotInitialized,
-    )]
-    pub treasury_config: Account<'info, TreasuryConfig>,
+#[event]
+pub struct SubscriptionPastDue {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub grace_until: i64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"volume_tracker", volume_tracker.wallet.as_ref()],
-        bump = volume_tracker.bump,
-    )]
-    pub volume_tracker: Account<'info, VolumeDiscountTracker>,
+#[event]
+pub struct SubscriptionLapsed {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub paid_through: i64,
+    pub timestamp: i64,
 }
 
-// ──────────────────────────────────────────────
-// Events
-// ──────────────────────────────────────────────
+#[event]
+pub struct SubscriptionCancelled {
+    pub user: Pubkey,
+    pub plan_id: u16,
+    pub paid_through: i64,
+    pub refunded_lamports: u64,
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct TreasuryInitialized {
-    pub super_authority: Pubkey,
-    pub aeon_authority: Pubkey,
-    pub keeper_authority: Pubkey,
-    pub creator_wallet: Pubkey,
+pub struct SubscriptionClosed {
+    pub user: Pubkey,
+    pub plan_id: u16,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ServicePaymentProcessed {
+pub struct SplServicePaymentProcessed {
     pub service_id: u16,
-    pub amount_lamports: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub price_lamports: u64,
+    pub list_price_lamports: u64,
+    pub discount_tier: u8,
+    pub service_tier: u8,
+    pub owning_agent_id: u16,
     pub creator_split: u64,
     pub treasury_reserve_split: u64,
     pub operations_split: u64,
+    pub ccs_band: u8,
+    pub creator_base_bps: u16,
+    pub creator_stipend_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplMintAllowlistUpdated {
+    pub mint: Pubkey,
+    pub allowed: bool,
+    pub token_units_per_sol: u64,
+    pub proposal_id: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplTokenRateUpdated {
+    pub mint: Pubkey,
+    pub previous_rate: u64,
+    pub token_units_per_sol: u64,
+    pub governed_token_units_per_sol: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplTreasuryWithdrawn {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub from_reserve: bool,
+    pub proposal_id: u32,
+    pub total_balance: u64,
+    pub reserved: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplCreatorWithdrawal {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub remaining_accumulated: u64,
+    pub remaining_unvested: u64,
     pub timestamp: i64,
 }
 
@@ -1254,6 +8124,8 @@ pub struct BudgetAllocated {
 #[event]
 pub struct DonationSwept {
     pub amount: u64,
+    /// Part of `amount` from flagged receipts, booked as unattributed inflow
+    pub flagged_amount: u64,
     pub sweep_count: u32,
     pub timestamp: i64,
 }
@@ -1262,7 +8134,46 @@ pub struct DonationSwept {
 pub struct DonationReceiptCreated {
     pub nonce: u64,
     pub amount: u64,
+    pub source_wallet_hash: [u8; 32],
     pub disclosure_mode: u8,
+    pub receipt_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DonationCorrelated {
+    pub receipt_nonce: u64,
+    /// None when detected at donation time from the donor's volume tracker
+    pub payment_nonce: Option<u64>,
+    pub correlated_payments: u32,
+    pub counts_as_donation: bool,
+    /// The receipt's lamports were already swept as a donation
+    pub already_swept: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptRetentionUpdated {
+    pub proposal_id: u32,
+    pub retention_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DonationReceiptArchived {
+    pub nonce: u64,
+    pub leaf: [u8; 32],
+    pub archive_root: [u8; 32],
+    pub archived_receipt_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentBudgetClosed {
+    pub agent_id: u16,
+    /// Undisbursed allocation released back to free balance
+    pub released: u64,
+    pub spent: u64,
     pub timestamp: i64,
 }
 
@@ -1270,6 +8181,30 @@ pub struct DonationReceiptCreated {
 pub struct CreatorWithdrawal {
     pub amount: u64,
     pub remaining_accumulated: u64,
+    pub remaining_unvested: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorWalletChangeProposed {
+    pub current_wallet: Pubkey,
+    pub pending_wallet: Pubkey,
+    pub effective_at: i64,
+    pub proposed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorWalletRotated {
+    pub previous_wallet: Pubkey,
+    pub new_wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorWalletChangeCancelled {
+    pub cancelled_wallet: Pubkey,
+    pub cancelled_by: Pubkey,
     pub timestamp: i64,
 }
 
@@ -1281,6 +8216,158 @@ pub struct BudgetUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct BudgetDisbursed {
+    pub agent_id: u16,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub spent: u64,
+    pub daily_spent: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExpensePayeeUpdated {
+    pub wallet: Pubkey,
+    pub label_hash: [u8; 32],
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationalExpensePaid {
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub invoice_hash: [u8; 32],
+    pub operations_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CcsBandsUpdated {
+    pub proposal_id: u32,
+    pub bands: [CCSBand; 4],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveReleased {
+    pub proposal_id: u32,
+    pub amount: u64,
+    pub reserved_lamports: u64,
+    pub free_balance_lamports: u64,
+    pub released_in_window: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveStakeDelegated {
+    pub index: u32,
+    pub stake_account: Pubkey,
+    pub vote_account: Pubkey,
+    pub amount: u64,
+    pub staked_lamports: u64,
+    pub proposal_id: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveStakeDeactivated {
+    pub index: u32,
+    pub stake_account: Pubkey,
+    pub principal_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveStakeWithdrawn {
+    pub index: u32,
+    pub stake_account: Pubkey,
+    pub principal_lamports: u64,
+    pub withdrawn_lamports: u64,
+    /// Booked as non-revenue treasury income
+    pub rewards: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryReconciled {
+    /// Vault PDA lamports minus its rent-exempt minimum
+    pub actual_lamports: u64,
+    /// total_balance_lamports before reconciliation
+    pub booked_lamports: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryPeriodClosed {
+    pub period_index: u32,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub revenue: u64,
+    pub closing_balance: u64,
+    pub statement_hash: [u8; 32],
+    pub prev_statement_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ColdWalletRegistered {
+    pub previous_wallet: Pubkey,
+    pub cold_wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryEvacuationArmed {
+    pub cold_wallet: Pubkey,
+    pub unlocks_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryEvacuationCancelled {
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryEvacuated {
+    pub cold_wallet: Pubkey,
+    pub amount: u64,
+    /// Reserve left in stake accounts
+    pub staked_lamports: u64,
+    /// Liabilities that left with the evacuated lamports
+    pub creator_escrow_lamports: u64,
+    /// Creator stream at evacuation (vested, unvested), zeroed on-chain
+    pub creator_accumulated: u64,
+    pub creator_unvested: u64,
+    pub referral_payable_lamports: u64,
+    /// Pending donations swept in first and included in `amount`
+    pub donations_swept: u64,
+    /// New epoch; referrer accruals from earlier epochs are dropped on next use
+    pub evacuation_epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryReinitialized {
+    pub aeon_authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub evacuated_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryAccountsMigrated {
+    pub config_grown: bool,
+    pub vault_grown: bool,
+    pub donation_grown: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RevenueAveragesUpdated {
     pub avg_7d_revenue: u64,
@@ -1315,7 +8402,7 @@ pub enum TreasuryError {
     ArithmeticOverflow,
     #[msg("Amount must be greater than zero")]
     ZeroAmount,
-    #[msg("Operations + treasury reserve + dev fund + creator split does not equal total amount")]
+    #[msg("Operations + treasury reserve + creator split does not equal total amount")]
     SplitMismatch,
     #[msg("Invalid creator wallet")]
     InvalidCreatorWallet,
@@ -1335,4 +8422,148 @@ pub enum TreasuryError {
     InsufficientTreasuryBalance,
     #[msg("Daily spend cap exceeded (A0-3: daily treasury spend <= 3% of free balance)")]
     DailySpendCapExceeded,
+    #[msg("Agent is not Active in noumen-core")]
+    AgentNotActive,
+    #[msg("Recipient does not match the agent's authority")]
+    InvalidAgentAuthority,
+    #[msg("Disbursement exceeds the agent's remaining allocation")]
+    AgentBudgetExhausted,
+    #[msg("Disbursement exceeds the agent's daily cap")]
+    AgentDailyCapExceeded,
+    #[msg("Insufficient operations balance")]
+    InsufficientOperationsBalance,
+    #[msg("Invalid agent type")]
+    InvalidAgentType,
+    #[msg("Sum of outstanding agent budgets would exceed free balance")]
+    AggregateBudgetCapExceeded,
+    #[msg("Evaluator budgets would exceed 25% cap of free balance")]
+    EvaluatorBudgetCapExceeded,
+    #[msg("New allocation is below the amount already spent")]
+    AllocationBelowSpent,
+    #[msg("Policy proposal has not been executed")]
+    ProposalNotExecuted,
+    #[msg("Policy proposal is on the wrong governance layer")]
+    InvalidPolicyLayer,
+    #[msg("Policy proposal delay is shorter than required for this parameter")]
+    InsufficientPolicyDelay,
+    #[msg("Policy proposal change_hash does not match the requested change")]
+    PolicyChangeHashMismatch,
+    #[msg("CCS bands violate the A0-28 envelope (floor 4%, stipend cap 5%, total cap 15%)")]
+    InvalidCcsBands,
+    #[msg("Revenue average window must be between 1 and 30 days")]
+    InvalidRevenueWindow,
+    #[msg("Service is not Active in the noumen-service catalog")]
+    ServiceNotActive,
+    #[msg("Payment amount does not match the service price after volume discount")]
+    PaymentAmountMismatch,
+    #[msg("Mint is not on the SPL payment allowlist")]
+    MintNotAllowed,
+    #[msg("Allowlisted mint requires a non-zero token_units_per_sol rate")]
+    InvalidTokenRate,
+    #[msg("Token account does not match the treasury token account for this mint")]
+    InvalidTokenAccount,
+    #[msg("Signer is neither keeper_authority nor the auditor authority")]
+    UnauthorizedRefundAuthority,
+    #[msg("Refund window for this payment has expired")]
+    RefundWindowExpired,
+    #[msg("Payment has already been refunded")]
+    PaymentAlreadyRefunded,
+    #[msg("Payment record is still inside its refund window")]
+    RefundWindowOpen,
+    #[msg("Account does not match the payer on the payment record")]
+    InvalidRefundRecipient,
+    #[msg("Prepaid balance is insufficient")]
+    InsufficientPrepaidBalance,
+    #[msg("Prepaid daily drawdown ceiling would be exceeded")]
+    PrepaidDailyCeilingExceeded,
+    #[msg("Signer is neither keeper_authority nor the owning agent's authority")]
+    UnauthorizedPrepaidDraw,
+    #[msg("Disclosure mode must be 0 (Pseudonymous) or 1 (Disclosed)")]
+    InvalidDisclosureMode,
+    #[msg("Payment record is not from the donation's source wallet")]
+    DonationNotCorrelated,
+    #[msg("Donation and payment are further apart than the correlation window")]
+    CorrelationWindowExceeded,
+    #[msg("Payment record has already been correlated with a donation")]
+    PaymentAlreadyCorrelated,
+    #[msg("Annual reserve release cap would be exceeded")]
+    ReserveReleaseCapExceeded,
+    #[msg("Treasury vault holds fewer lamports than booked; outflows are blocked")]
+    TreasuryDeficit,
+    #[msg("Expense payee is not active")]
+    PayeeNotActive,
+    #[msg("Payee wallet does not match the payee argument")]
+    InvalidPayeeWallet,
+    #[msg("Stake amount must exceed the stake account rent-exempt minimum")]
+    StakeAmountTooSmall,
+    #[msg("Staked reserve would exceed MAX_STAKED_RESERVE_BPS of the reserve")]
+    StakeCapExceeded,
+    #[msg("Reserve would drop below the amount currently staked")]
+    ReserveBelowStaked,
+    #[msg("Account does not match the recorded stake or vote account")]
+    InvalidStakeAccount,
+    #[msg("Treasury stake is not active")]
+    StakeNotActive,
+    #[msg("Treasury stake has not been deactivated")]
+    StakeNotDeactivated,
+    #[msg("No creator wallet change is pending")]
+    NoPendingCreatorWallet,
+    #[msg("Creator wallet rotation delay has not elapsed")]
+    CreatorWalletDelayNotElapsed,
+    #[msg("Subscription plan is not active")]
+    SubscriptionPlanInactive,
+    #[msg("Billing period must be at least one day and grace between one hour and one period")]
+    InvalidSubscriptionPeriod,
+    #[msg("Plan price exceeds the subscriber's approved maximum")]
+    SubscriptionPriceAboveMax,
+    #[msg("Subscription is lapsed or cancelled")]
+    SubscriptionNotOpen,
+    #[msg("Subscription period is not due yet")]
+    SubscriptionNotDue,
+    #[msg("Subscription must be cancelled or lapsed and past its paid-through time")]
+    SubscriptionStillOpen,
+    #[msg("Treasury period is shorter than TREASURY_PERIOD_SECONDS")]
+    PeriodNotElapsed,
+    #[msg("Payer cannot be its own referrer")]
+    SelfReferral,
+    #[msg("Referral share exceeds MAX_REFERRAL_SHARE_BPS")]
+    InvalidReferralPolicy,
+    #[msg("Referrer account does not match the payment's referrer")]
+    ReferrerMismatch,
+    #[msg("Agent revenue share exceeds MAX_AGENT_REVENUE_SHARE_BPS")]
+    InvalidAgentRevenueShare,
+    #[msg("Owning agent's BudgetAllocation PDA is required for an agent-owned plan")]
+    MissingAgentBudget,
+    #[msg("No cold wallet registered")]
+    ColdWalletNotRegistered,
+    #[msg("Signer is not the registered cold wallet")]
+    InvalidColdWallet,
+    #[msg("noumen-core circuit breaker is not Halted")]
+    CircuitBreakerNotHalted,
+    #[msg("noumen-core circuit breaker is still Halted")]
+    CircuitBreakerStillHalted,
+    #[msg("An evacuation is already armed")]
+    EvacuationAlreadyArmed,
+    #[msg("No evacuation is armed")]
+    EvacuationNotArmed,
+    #[msg("Evacuation delay has not elapsed")]
+    EvacuationDelayNotElapsed,
+    #[msg("Treasury has not been evacuated")]
+    TreasuryNotEvacuated,
+    #[msg("Receipt retention is below MIN_RECEIPT_RETENTION_SECONDS")]
+    InvalidReceiptRetention,
+    #[msg("Receipt archival is disabled (no retention period set)")]
+    ReceiptArchivalDisabled,
+    #[msg("Receipt is younger than the retention period")]
+    ReceiptRetentionNotElapsed,
+    #[msg("Donation archive tree is full")]
+    ArchiveFull,
+    #[msg("Agent is not Killed in noumen-core")]
+    AgentNotKilled,
+    #[msg("Token rate is outside the keeper band around the governed rate")]
+    TokenRateOutOfBand,
+    #[msg("Token rate was updated less than an hour ago")]
+    SplRateUpdateTooSoon,
+    #[msg("Paid before the last evacuation; settled from the cold wallet")]
+    SettledByEvacuation,
 }
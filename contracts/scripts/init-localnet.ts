@@ -108,6 +108,7 @@ const [treasuryConfigPDA] = findPDA([Buffer.from("treasury_config")], PROGRAM_ID
 const [treasuryVaultPDA] = findPDA([Buffer.from("treasury_vault")], PROGRAM_IDS.treasury);
 const [donationVaultPDA] = findPDA([Buffer.from("donation_vault")], PROGRAM_IDS.treasury);
 const [ccsConfigPDA] = findPDA([Buffer.from("ccs_config")], PROGRAM_IDS.treasury);
+const [revenueHistoryPDA] = findPDA([Buffer.from("revenue_history")], PROGRAM_IDS.treasury);
 const [apolloConfigPDA] = findPDA([Buffer.from("apollo_config")], PROGRAM_IDS.apollo);
 const [hermesConfigPDA] = findPDA([Buffer.from("hermes_config")], PROGRAM_IDS.hermes);
 const [auditorConfigPDA] = findPDA([Buffer.from("auditor_config")], PROGRAM_IDS.auditor);
@@ -240,6 +241,7 @@ async function initializeAll(
           { pubkey: treasuryConfigPDA, isSigner: false, isWritable: false },
           { pubkey: donationVaultPDA, isSigner: false, isWritable: true },
           { pubkey: ccsConfigPDA, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPDA, isSigner: false, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
         programId: PROGRAM_IDS.treasury,
@@ -538,6 +540,11 @@ async function testAllInstructions(
 
   // ── PROOF: log_decision ──
   console.log("─── noumen_proof: log_decision ───");
+  // Nonces are strictly sequential per agent, tracked by its ProofCursor
+  const [proofCursorPDA] = findPDA(
+    [Buffer.from("proof_cursor"), serializeU16(agentId)],
+    PROGRAM_IDS.proof,
+  );
   const decisionNonce = BigInt(1);
   const [decisionLogPDA] = findPDA(
     [Buffer.from("decision"), serializeU16(agentId), serializeU64(decisionNonce)],
@@ -559,6 +566,7 @@ async function testAllInstructions(
       keys: [
         { pubkey: proofConfigPDA, isSigner: false, isWritable: false },
         { pubkey: decisionLogPDA, isSigner: false, isWritable: true },
+        { pubkey: proofCursorPDA, isSigner: false, isWritable: true },
         { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       ],
@@ -594,6 +602,7 @@ async function testAllInstructions(
       keys: [
         { pubkey: proofConfigPDA, isSigner: false, isWritable: false },
         { pubkey: decisionLogPDA2, isSigner: false, isWritable: true },
+        { pubkey: proofCursorPDA, isSigner: false, isWritable: true },
         { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       ],
@@ -1089,74 +1098,151 @@ async function testAllInstructions(
 // FASE 2C: Revenue Split Test (3 tiers)
 // ═══════════════════════════════════════════════
 
+/** Creates the payer's VolumeDiscountTracker, required by process_service_payment. */
+async function ensureVolumeTracker(connection: Connection, payer: Keypair) {
+  const [trackerPDA] = findPDA([Buffer.from("volume_tracker"), payer.publicKey.toBuffer()], PROGRAM_IDS.treasury);
+  if (await connection.getAccountInfo(trackerPDA)) return;
+  const ix = new TransactionInstruction({
+    keys: [
+      { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+      { pubkey: trackerPDA, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: PROGRAM_IDS.treasury,
+    data: ixDiscriminator("initialize_volume_tracker"),
+  });
+  await sendAndConfirmTransaction(connection, new Transaction().add(ix), [payer], { commitment: "confirmed" });
+}
+
+// PaymentRecord nonces: seeded from the clock so re-running the script
+// against the same validator never reuses a record PDA
+let nextPaymentNonce = BigInt(Date.now());
+
+/**
+ * process_service_payment without a referrer or referral link (the program id
+ * fills each optional slot). Every payment creates a PaymentRecord under a
+ * fresh nonce. owner_budget is always the owning agent's budget PDA,
+ * initialized or not.
+ */
+function servicePaymentIx(payer: PublicKey, serviceId: number, amount: bigint, owningAgentId: number): TransactionInstruction {
+  const [serviceEntryPDA] = findPDA([Buffer.from("service"), serializeU16(serviceId)], PROGRAM_IDS.service);
+  const [trackerPDA] = findPDA([Buffer.from("volume_tracker"), payer.toBuffer()], PROGRAM_IDS.treasury);
+  const [ownerBudgetPDA] = findPDA([Buffer.from("budget"), serializeU16(owningAgentId)], PROGRAM_IDS.treasury);
+  const paymentNonce = nextPaymentNonce++;
+  const [paymentRecordPDA] = findPDA(
+    [Buffer.from("payment"), payer.toBuffer(), serializeU64(paymentNonce)],
+    PROGRAM_IDS.treasury
+  );
+  const argsData = Buffer.concat([
+    serializeU16(serviceId),
+    serializeU64(amount),
+    serializeU64(paymentNonce),
+  ]);
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: treasuryConfigPDA, isSigner: false, isWritable: false },
+      { pubkey: treasuryVaultPDA, isSigner: false, isWritable: true },
+      { pubkey: ccsConfigPDA, isSigner: false, isWritable: true },
+      { pubkey: revenueHistoryPDA, isSigner: false, isWritable: true },
+      { pubkey: serviceEntryPDA, isSigner: false, isWritable: false },
+      { pubkey: trackerPDA, isSigner: false, isWritable: true },
+      { pubkey: paymentRecordPDA, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: PROGRAM_IDS.treasury, isSigner: false, isWritable: false }, // referrer: none
+      { pubkey: ownerBudgetPDA, isSigner: false, isWritable: true },
+      { pubkey: PROGRAM_IDS.treasury, isSigner: false, isWritable: false }, // referral_link: none
+    ],
+    programId: PROGRAM_IDS.treasury,
+    data: Buffer.concat([ixDiscriminator("process_service_payment"), argsData]),
+  });
+}
+
+/** Undiscounted quote: max(price_lamports, min_price_lamports) of the ServiceEntry. */
+async function servicePrice(connection: Connection, serviceId: number): Promise<bigint> {
+  const [serviceEntryPDA] = findPDA([Buffer.from("service"), serializeU16(serviceId)], PROGRAM_IDS.service);
+  const info = await connection.getAccountInfo(serviceEntryPDA);
+  if (!info) throw new Error(`Service #${serviceId} not registered`);
+  // ServiceEntry: disc(8) id(2) agent(2) tier(1) level(1) price(8) cost(8) min_price(8)
+  const price = info.data.readBigUInt64LE(14);
+  const minPrice = info.data.readBigUInt64LE(30);
+  return price > minPrice ? price : minPrice;
+}
+
+/** Steps a service up to Active (level 2), one level per transition. */
+async function ensureServiceActive(connection: Connection, aeonAuth: Keypair, serviceId: number) {
+  const [serviceEntryPDA] = findPDA([Buffer.from("service"), serializeU16(serviceId)], PROGRAM_IDS.service);
+  const info = await connection.getAccountInfo(serviceEntryPDA);
+  if (!info) throw new Error(`Service #${serviceId} not registered`);
+  for (let level = info.data.readUInt8(13) + 1; level <= 2; level++) {
+    const ix = new TransactionInstruction({
+      keys: [
+        { pubkey: serviceConfigPDA, isSigner: false, isWritable: false },
+        { pubkey: serviceEntryPDA, isSigner: false, isWritable: true },
+        { pubkey: aeonAuth.publicKey, isSigner: true, isWritable: false },
+      ],
+      programId: PROGRAM_IDS.service,
+      data: Buffer.concat([ixDiscriminator("update_service_level"), serializeU16(serviceId), serializeU8(level)]),
+    });
+    await sendAndConfirmTransaction(connection, new Transaction().add(ix), [aeonAuth], { commitment: "confirmed" });
+  }
+}
+
+/** CCSConfig.total_creator_accrued (offset 119): the creator share is streamed, not paid out. */
+async function creatorAccrued(connection: Connection): Promise<number> {
+  const info = await connection.getAccountInfo(ccsConfigPDA);
+  return info ? Number(info.data.readBigUInt64LE(119)) : 0;
+}
+
 async function testRevenueSplit(
   connection: Connection,
+  aeonAuth: Keypair,
   payer: Keypair,
 ) {
   console.log("\n╔═══════════════════════════════════════════════╗");
-  console.log("║  FASE 2C: Revenue Split Test (40/30/15/15)     ║");
+  console.log("║  FASE 2C: Revenue Split Test (3 tiers, CCS)    ║");
   console.log("╚═══════════════════════════════════════════════╝\n");
 
-  // Fund the creator wallet so it has rent exemption
-  await fundAccount(connection, CREATOR_WALLET, 0.01);
+  await ensureVolumeTracker(connection, payer);
 
   const tiers = [
-    { name: "Entry (0.02 SOL)", serviceId: 1, amount: 20_000_000 },
-    { name: "Premium (0.15 SOL)", serviceId: 2, amount: 150_000_000 },
-    { name: "Institutional (2 SOL)", serviceId: 3, amount: 2_000_000_000 },
+    { name: "Entry", serviceId: 1 },
+    { name: "Premium", serviceId: 2 },
+    { name: "Institutional", serviceId: 3 },
   ];
 
   for (const tier of tiers) {
     console.log(`─── Testing: ${tier.name} ───`);
 
-    // Get creator balance before
-    const creatorBalanceBefore = await connection.getBalance(CREATOR_WALLET);
-    const vaultInfoBefore = await connection.getAccountInfo(treasuryVaultPDA);
-
     try {
-      const argsData = Buffer.concat([
-        serializeU16(tier.serviceId),
-        serializeU64(BigInt(tier.amount)),
-      ]);
-      const data = Buffer.concat([ixDiscriminator("process_service_payment"), argsData]);
-      const ix = new TransactionInstruction({
-        keys: [
-          { pubkey: payer.publicKey, isSigner: true, isWritable: true },
-          { pubkey: treasuryConfigPDA, isSigner: false, isWritable: false },
-          { pubkey: treasuryVaultPDA, isSigner: false, isWritable: true },
-          { pubkey: ccsConfigPDA, isSigner: false, isWritable: true },
-          { pubkey: CREATOR_WALLET, isSigner: false, isWritable: true },
-          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-        ],
-        programId: PROGRAM_IDS.treasury,
-        data,
-      });
+      // Payments must match the on-chain quote of an Active service
+      await ensureServiceActive(connection, aeonAuth, tier.serviceId);
+      const amount = await servicePrice(connection, tier.serviceId);
 
-      const sig = await sendAndConfirmTransaction(connection, new Transaction().add(ix), [payer], { commitment: "confirmed" });
+      const vaultBefore = await connection.getBalance(treasuryVaultPDA);
+      const creatorAccruedBefore = await creatorAccrued(connection);
 
-      // Verify split
-      const creatorBalanceAfter = await connection.getBalance(CREATOR_WALLET);
-      const creatorReceived = creatorBalanceAfter - creatorBalanceBefore;
-
-      const expectedOps = Math.floor(tier.amount * 0.40);
-      const expectedReserve = Math.floor(tier.amount * 0.30);
-      const expectedDev = Math.floor(tier.amount * 0.15);
-      const expectedCreator = tier.amount - expectedOps - expectedReserve - expectedDev;
+      // All three services are owned by agent 1 (APOLLO)
+      const ix = servicePaymentIx(payer.publicKey, tier.serviceId, amount, 1);
+      const sig = await sendAndConfirmTransaction(connection, new Transaction().add(ix), [payer], { commitment: "confirmed" });
 
-      const creatorMatch = Math.abs(creatorReceived - expectedCreator) <= 1;
+      // Verify: the whole payment lands in the vault; the creator share is
+      // streamed from escrow (CCS band split), not paid out directly
+      const vaultReceived = (await connection.getBalance(treasuryVaultPDA)) - vaultBefore;
+      const creatorShare = (await creatorAccrued(connection)) - creatorAccruedBefore;
 
-      if (creatorMatch) {
+      if (vaultReceived === Number(amount) && creatorShare > 0 && creatorShare < Number(amount)) {
         recordTest(
           `payment_${tier.name}`,
           "PASS",
-          `Split verified: Ops=${expectedOps/1e9}, Reserve=${expectedReserve/1e9}, Dev=${expectedDev/1e9}, Creator=${creatorReceived/1e9} SOL`,
+          `${Number(amount) / 1e9} SOL in vault, creator accrued ${creatorShare / 1e9} SOL`,
           sig,
         );
       } else {
         recordTest(
           `payment_${tier.name}`,
           "FAIL",
-          `Creator mismatch: expected ${expectedCreator}, got ${creatorReceived}`,
+          `Vault received ${vaultReceived} of ${amount}, creator accrued ${creatorShare}`,
           sig,
         );
       }
@@ -1185,14 +1271,14 @@ async function testEndToEnd(
 
   // Step 1: Log a new decision
   console.log("─── E2E Step 1: Log decision ───");
-  const e2eDecisionNonce = BigInt(100);
+  const e2eDecisionNonce = BigInt(3); // next in agent 1's sequence
   const [e2eDecisionPDA] = findPDA(
-    [Buffer.from("decision"), serializeU16(1), serializeU64(e2eDecisionNonce)],
+    [Buffer.from("decision"), serializeU16(agentId), serializeU64(e2eDecisionNonce)],
     PROGRAM_IDS.proof,
   );
   try {
     const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(e2eDecisionNonce),
+      serializeU16(agentId), serializeU64(e2eDecisionNonce),
       serializeHash(), serializeHash(), serializeHash(),
       serializeU8(0b00011111), // 5 evidence families
       serializeU8(1), serializeBool(false),
@@ -1202,6 +1288,7 @@ async function testEndToEnd(
       keys: [
         { pubkey: proofConfigPDA, isSigner: false, isWritable: false },
         { pubkey: e2eDecisionPDA, isSigner: false, isWritable: true },
+        { pubkey: proofCursorPDA, isSigner: false, isWritable: true },
         { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       ],
@@ -1209,7 +1296,7 @@ async function testEndToEnd(
       data,
     });
     const sig = await sendAndConfirmTransaction(connection, new Transaction().add(ix), [keeperAuth], { commitment: "confirmed" });
-    recordTest("e2e_log_decision", "PASS", "Decision #100 logged with 5 evidence families", sig);
+    recordTest("e2e_log_decision", "PASS", "Decision #3 logged with 5 evidence families", sig);
   } catch (err: any) {
     recordTest("e2e_log_decision", "FAIL", err.message);
   }
@@ -1287,25 +1374,12 @@ async function testEndToEnd(
   // Step 4: Process payment
   console.log("─── E2E Step 4: Process service payment ───");
   try {
-    const argsData = Buffer.concat([
-      serializeU16(1),
-      serializeU64(BigInt(100_000_000)), // 0.1 SOL
-    ]);
-    const data = Buffer.concat([ixDiscriminator("process_service_payment"), argsData]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: payer.publicKey, isSigner: true, isWritable: true },
-        { pubkey: treasuryConfigPDA, isSigner: false, isWritable: false },
-        { pubkey: treasuryVaultPDA, isSigner: false, isWritable: true },
-        { pubkey: ccsConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: CREATOR_WALLET, isSigner: false, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.treasury,
-      data,
-    });
+    await ensureVolumeTracker(connection, payer);
+    await ensureServiceActive(connection, aeonAuth, 1);
+    const amount = await servicePrice(connection, 1);
+    const ix = servicePaymentIx(payer.publicKey, 1, amount, 1);
     const sig = await sendAndConfirmTransaction(connection, new Transaction().add(ix), [payer], { commitment: "confirmed" });
-    recordTest("e2e_process_payment", "PASS", "0.1 SOL payment processed with 4-way split", sig);
+    recordTest("e2e_process_payment", "PASS", `${Number(amount) / 1e9} SOL payment processed with CCS split`, sig);
   } catch (err: any) {
     recordTest("e2e_process_payment", "FAIL", err.message);
   }
@@ -1420,7 +1494,7 @@ async function main() {
   // Run all phases
   await initializeAll(connection, superAuth, aeonAuth, keeperAuth);
   await testAllInstructions(connection, superAuth, aeonAuth, keeperAuth, payer);
-  await testRevenueSplit(connection, payer);
+  await testRevenueSplit(connection, aeonAuth, payer);
   await testEndToEnd(connection, superAuth, aeonAuth, keeperAuth, payer);
   await verifyAllPDAs(connection);
 
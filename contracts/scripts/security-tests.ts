@@ -148,6 +148,53 @@ const [hermesConfigPDA] = findPDA([Buffer.from("hermes_config")], PROGRAM_IDS.he
 const [auditorConfigPDA] = findPDA([Buffer.from("auditor_config")], PROGRAM_IDS.auditor);
 const [serviceConfigPDA] = findPDA([Buffer.from("service_config")], PROGRAM_IDS.service);
 
+// ──────────────────────────────────────────
+// log_decision helpers
+// ──────────────────────────────────────────
+
+/**
+ * Next in-sequence nonce for an agent: ProofCursor.last_nonce + 1 (1 before
+ * its first decision). Probes use it so they fail on the check under test,
+ * not on NonceOutOfSequence.
+ */
+async function nextDecisionNonce(connection: Connection, agentId: number): Promise<bigint> {
+  const [cursorPDA] = findPDA([Buffer.from("proof_cursor"), serializeU16(agentId)], PROGRAM_IDS.proof);
+  const info = await connection.getAccountInfo(cursorPDA);
+  // ProofCursor: disc(8) agent_id(2) last_nonce(8)
+  return info ? info.data.readBigUInt64LE(10) + BigInt(1) : BigInt(1);
+}
+
+/** log_decision with LogDecisionArgs in order and the agent's ProofCursor PDA. */
+function logDecisionIx(
+  signer: PublicKey,
+  agentId: number,
+  nonce: bigint,
+  opts: { bitmap?: number; decisionClass?: number; isExecutionClass?: boolean; decisionPDA?: PublicKey } = {},
+): TransactionInstruction {
+  const [decisionPDA] = opts.decisionPDA
+    ? [opts.decisionPDA]
+    : findPDA([Buffer.from("decision"), serializeU16(agentId), serializeU64(nonce)], PROGRAM_IDS.proof);
+  const [cursorPDA] = findPDA([Buffer.from("proof_cursor"), serializeU16(agentId)], PROGRAM_IDS.proof);
+  const argsData = Buffer.concat([
+    serializeU16(agentId), serializeU64(nonce),
+    serializeHash(), serializeHash(), serializeHash(),  // input, decision, justification hashes
+    serializeU8(opts.bitmap ?? 0b00111),
+    serializeU8(opts.decisionClass ?? 0),
+    serializeBool(opts.isExecutionClass ?? false),
+  ]);
+  return new TransactionInstruction({
+    keys: [
+      { pubkey: proofConfigPDA, isSigner: false, isWritable: false },
+      { pubkey: decisionPDA, isSigner: false, isWritable: true },
+      { pubkey: cursorPDA, isSigner: false, isWritable: true },
+      { pubkey: signer, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    programId: PROGRAM_IDS.proof,
+    data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
+  });
+}
+
 // ──────────────────────────────────────────
 // Fund helper
 // ──────────────────────────────────────────
@@ -218,26 +265,8 @@ async function testUnauthorizedAccess(
 
   // 1.3 — Attacker tries log_decision (requires keeper_authority)
   {
-    const nonce = BigInt(999);
-    const [decisionPDA] = findPDA(
-      [Buffer.from("decision"), serializeU16(1), serializeU64(nonce)],
-      PROGRAM_IDS.proof,
-    );
-    const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(nonce), serializeU8(0),
-      serializeU8(0b00111), serializeBool(false), serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: decisionPDA, isSigner: false, isWritable: true },
-        { pubkey: attacker.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const nonce = await nextDecisionNonce(connection, 1);
+    const ix = logDecisionIx(attacker.publicKey, 1, nonce);
     const r = await expectTxFail(connection, ix, [attacker], "0x1779");
     recordTest(CAT, "proof_log_decision_attacker",
       r.failed ? "PASS" : "FAIL",
@@ -389,26 +418,8 @@ async function testUnauthorizedAccess(
 
   // 1.9 — AEON tries log_decision (only keeper can do this)
   {
-    const nonce = BigInt(998);
-    const [decisionPDA] = findPDA(
-      [Buffer.from("decision"), serializeU16(1), serializeU64(nonce)],
-      PROGRAM_IDS.proof,
-    );
-    const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(nonce), serializeU8(0),
-      serializeU8(0b00111), serializeBool(false), serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: decisionPDA, isSigner: false, isWritable: true },
-        { pubkey: aeonAuth.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const nonce = await nextDecisionNonce(connection, 1);
+    const ix = logDecisionIx(aeonAuth.publicKey, 1, nonce);
     const r = await expectTxFail(connection, ix, [aeonAuth], "0x1779");
     recordTest(CAT, "proof_log_decision_aeon_role",
       r.failed ? "PASS" : "FAIL",
@@ -665,27 +676,13 @@ async function testPDATampering(
   {
     const wrongAgentId = 99;
     const realAgentId = 1;
-    const nonce = BigInt(997);
+    const nonce = await nextDecisionNonce(connection, realAgentId);
     // PDA derived with wrong agent_id
     const [wrongPDA] = findPDA(
       [Buffer.from("decision"), serializeU16(wrongAgentId), serializeU64(nonce)],
       PROGRAM_IDS.proof,
     );
-    const argsData = Buffer.concat([
-      serializeU16(realAgentId), serializeU64(nonce), serializeU8(0),
-      serializeU8(0b00111), serializeBool(false), serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: wrongPDA, isSigner: false, isWritable: true },
-        { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const ix = logDecisionIx(keeperAuth.publicKey, realAgentId, nonce, { decisionPDA: wrongPDA });
     const r = await expectTxFail(connection, ix, [keeperAuth], "0x7d6");
     recordTest(CAT, "proof_decision_pda_mismatch",
       r.failed ? "PASS" : "FAIL",
@@ -830,27 +827,9 @@ async function testAxiomEnforcement(
 
   // 4.2 — A0-18: Invalid evidence bitmap (bits > 4)
   {
-    const nonce = BigInt(996);
-    const [decisionPDA] = findPDA(
-      [Buffer.from("decision"), serializeU16(1), serializeU64(nonce)],
-      PROGRAM_IDS.proof,
-    );
-    const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(nonce), serializeU8(0),
-      serializeU8(0b11100000), // Invalid: bits 5,6,7 set (only 0-4 valid)
-      serializeBool(false), serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: decisionPDA, isSigner: false, isWritable: true },
-        { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const nonce = await nextDecisionNonce(connection, 1);
+    // Invalid: bits 5,6,7 set (only 0-4 valid)
+    const ix = logDecisionIx(keeperAuth.publicKey, 1, nonce, { bitmap: 0b11100000 });
     const r = await expectTxFail(connection, ix, [keeperAuth], "any");
     recordTest(CAT, "A0_18_invalid_evidence_bitmap",
       r.failed ? "PASS" : "FAIL",
@@ -859,28 +838,9 @@ async function testAxiomEnforcement(
 
   // 4.3 — A0-17: Execution-class decision with < 2 evidence families
   {
-    const nonce = BigInt(995);
-    const [decisionPDA] = findPDA(
-      [Buffer.from("decision"), serializeU16(1), serializeU64(nonce)],
-      PROGRAM_IDS.proof,
-    );
-    const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(nonce), serializeU8(0),
-      serializeU8(0b00001), // Only 1 family (Price) — need >= 2 for execution class
-      serializeBool(true),  // is_execution_class = TRUE
-      serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: decisionPDA, isSigner: false, isWritable: true },
-        { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const nonce = await nextDecisionNonce(connection, 1);
+    // Only 1 family (Price) — need >= 2 for execution class
+    const ix = logDecisionIx(keeperAuth.publicKey, 1, nonce, { bitmap: 0b00001, isExecutionClass: true });
     const r = await expectTxFail(connection, ix, [keeperAuth], "any");
     recordTest(CAT, "A0_17_exec_insufficient_evidence",
       r.failed ? "PASS" : "FAIL",
@@ -1357,27 +1317,9 @@ async function testOverflowBoundary(
 
   // 6.1 — Invalid decision class (>3)
   {
-    const nonce = BigInt(990);
-    const [decisionPDA] = findPDA(
-      [Buffer.from("decision"), serializeU16(1), serializeU64(nonce)],
-      PROGRAM_IDS.proof,
-    );
-    const argsData = Buffer.concat([
-      serializeU16(1), serializeU64(nonce),
-      serializeU8(10), // decision_class = 10 (INVALID: 0-3 only)
-      serializeU8(0b00111), serializeBool(false), serializeHash(),
-      serializeI64(BigInt(Math.floor(Date.now() / 1000))),
-    ]);
-    const ix = new TransactionInstruction({
-      keys: [
-        { pubkey: proofConfigPDA, isSigner: false, isWritable: true },
-        { pubkey: decisionPDA, isSigner: false, isWritable: true },
-        { pubkey: keeperAuth.publicKey, isSigner: true, isWritable: true },
-        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      ],
-      programId: PROGRAM_IDS.proof,
-      data: Buffer.concat([ixDiscriminator("log_decision"), argsData]),
-    });
+    const nonce = await nextDecisionNonce(connection, 1);
+    // decision_class = 10 (INVALID: 0-3 only)
+    const ix = logDecisionIx(keeperAuth.publicKey, 1, nonce, { decisionClass: 10 });
     const r = await expectTxFail(connection, ix, [keeperAuth], "any");
     recordTest(CAT, "proof_invalid_decision_class",
       r.failed ? "PASS" : "FAIL",
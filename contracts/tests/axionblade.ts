@@ -6,6 +6,10 @@ import {
   TransactionInstruction,
   Transaction,
   LAMPORTS_PER_SOL,
+  StakeProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_STAKE_HISTORY_PUBKEY,
 } from "@solana/web3.js";
 import { expect } from "chai";
 import BN from "bn.js";
@@ -21,6 +25,7 @@ const APOLLO_PROGRAM_ID = new PublicKey("92WeuJoJdh3o1jLcvSLKuTUitQMnUhMRzoTYaSz
 const HERMES_PROGRAM_ID = new PublicKey("Hfv5AS3sydnniyqgF8dwXgN76NU4aKAysgcQJ3uncmTj");
 const AUDITOR_PROGRAM_ID = new PublicKey("CGLy91mAXwz761z6soTnap2pNVVA8d8zfsGZjLkqwvTe");
 const SERVICE_PROGRAM_ID = new PublicKey("9ArzMqH6jSWVwvQyYfsdtUQ595wCQXFQAQzXxcoM4LbY");
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
 // ──────────────────────────────────────────────
 // Discriminator Helpers
@@ -95,6 +100,15 @@ function findPda(seeds: (Buffer | Uint8Array)[], programId: PublicKey): [PublicK
   return PublicKey.findProgramAddressSync(seeds, programId);
 }
 
+let nextPaymentNonce = 1_000;
+
+/** Unused PaymentRecord PDA [b"payment", payer, nonce]; every SOL service payment creates one. */
+function newPaymentRecord(payer: PublicKey): { nonce: BN; pda: PublicKey } {
+  const nonce = new BN(nextPaymentNonce++);
+  const [pda] = findPda([Buffer.from("payment"), payer.toBuffer(), encodeU64LE(nonce)], TREASURY_PROGRAM_ID);
+  return { nonce, pda };
+}
+
 function randomHash(): Buffer {
   return Buffer.from(
     createHash("sha256")
@@ -103,16 +117,122 @@ function randomHash(): Buffer {
   );
 }
 
+/**
+ * Mirrors DecisionLog::chain_hash over raw DecisionLog account data:
+ * sha256("decision_chain" || prev_hash || agent_id || nonce || input_hash ||
+ *   decision_hash || justification_hash || bitmap || class || is_execution_class || timestamp).
+ */
+function decisionChainHash(log: Buffer): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("decision_chain"))
+    .update(log.subarray(127, 159))   // prev_hash
+    .update(log.subarray(8, 114))     // agent_id .. justification_hash
+    .update(log.subarray(114, 116))   // evidence_families_bitmap, decision_class
+    .update(log.subarray(124, 125))   // is_execution_class
+    .update(log.subarray(116, 124))   // timestamp
+    .digest();
+}
+
+/** Mirrors shared_types::merkle_leaf_hash: sha256(0x00 || data). */
+function merkleLeafHash(data: Buffer): Buffer {
+  return createHash("sha256").update(Buffer.from([0x00])).update(data).digest();
+}
+
+/** Mirrors shared_types::merkle_node_hash: sha256(0x01 || left || right). */
+function merkleNodeHash(left: Buffer, right: Buffer): Buffer {
+  return createHash("sha256").update(Buffer.from([0x01])).update(left).update(right).digest();
+}
+
+// ──────────────────────────────────────────────
+// Program Error Codes (Anchor: 6000 + variant index)
+// ──────────────────────────────────────────────
+
+const AnchorErr = {
+  ConstraintSeeds: 2006,
+  AccountNotInitialized: 3012,
+} as const;
+
+const CoreErr = {
+  DelayNotElapsed: 6012,
+} as const;
+
+const TreasuryErr = {
+  ZeroAmount: 6002,
+  InvalidCreatorWallet: 6004,
+  UnauthorizedAeon: 6005,
+  UnauthorizedKeeper: 6006,
+  InsufficientCreatorBalance: 6009,
+  ReserveRatioBreach: 6010,
+  AgentNotActive: 6013,
+  AgentBudgetExhausted: 6015,
+  InsufficientOperationsBalance: 6017,
+  ProposalNotExecuted: 6022,
+  InvalidPolicyLayer: 6023,
+  PolicyChangeHashMismatch: 6025,
+  ServiceNotActive: 6028,
+  PaymentAmountMismatch: 6029,
+  UnauthorizedRefundAuthority: 6033,
+  PaymentAlreadyRefunded: 6035,
+  RefundWindowOpen: 6036,
+  UnauthorizedPrepaidDraw: 6040,
+  PaymentAlreadyCorrelated: 6043,
+  StakeCapExceeded: 6050,
+  InvalidSubscriptionPeriod: 6058,
+  SubscriptionNotDue: 6061,
+  SelfReferral: 6064,
+  InvalidReferralPolicy: 6065,
+  ReferrerMismatch: 6066,
+  InvalidAgentRevenueShare: 6067,
+  MissingAgentBudget: 6068,
+  InvalidReceiptRetention: 6077,
+  ReceiptRetentionNotElapsed: 6079,
+  AgentNotKilled: 6081,
+  TokenRateOutOfBand: 6082,
+  SplRateUpdateTooSoon: 6083,
+  SettledByEvacuation: 6084,
+} as const;
+
+function errorText(err: any): string {
+  return err.toString() + (err.logs ? "\n" + err.logs.join("\n") : "");
+}
+
+function hasCustomError(err: any, code: number): boolean {
+  return errorText(err).includes(`custom program error: 0x${code.toString(16)}`);
+}
+
+/** Asserts that a failed send carried the given custom program error. */
+function expectCustomError(err: any, code: number): void {
+  expect(errorText(err)).to.contain(`custom program error: 0x${code.toString(16)}`);
+}
+
+/** Asserts that a governed instruction failed because its AppliedProposal marker already exists. */
+function expectProposalReplayRejected(err: any): void {
+  expect(errorText(err)).to.contain("already in use");
+}
+
 // ──────────────────────────────────────────────
 // Transaction Helpers
 // ──────────────────────────────────────────────
 
 async function sendTx(
   provider: anchor.AnchorProvider,
-  ix: TransactionInstruction,
+  ix: TransactionInstruction | TransactionInstruction[],
   signers: Keypair[]
 ): Promise<string> {
-  const tx = new Transaction().add(ix);
+  const tx = new Transaction().add(...(Array.isArray(ix) ? ix : [ix]));
+  tx.feePayer = provider.wallet.publicKey;
+  tx.recentBlockhash = (
+    await provider.connection.getRecentBlockhash()
+  ).blockhash;
+  return await provider.sendAndConfirm(tx, signers);
+}
+
+async function sendIxs(
+  provider: anchor.AnchorProvider,
+  ixs: TransactionInstruction[],
+  signers: Keypair[]
+): Promise<string> {
+  const tx = new Transaction().add(...ixs);
   tx.feePayer = provider.wallet.publicKey;
   tx.recentBlockhash = (
     await provider.connection.getRecentBlockhash()
@@ -120,6 +240,194 @@ async function sendTx(
   return await provider.sendAndConfirm(tx, signers);
 }
 
+/**
+ * Simulates a single instruction and returns its program return data.
+ * The runtime strips trailing zero bytes, so a borsh `false` comes back empty.
+ */
+async function simulateReturnData(
+  provider: anchor.AnchorProvider,
+  ix: TransactionInstruction
+): Promise<Buffer> {
+  const tx = new Transaction().add(ix);
+  tx.feePayer = provider.wallet.publicKey;
+  const sim = await provider.connection.simulateTransaction(tx);
+  expect(sim.value.err).to.be.null;
+  const returnData = sim.value.returnData;
+  return returnData ? Buffer.from(returnData.data[0], "base64") : Buffer.alloc(0);
+}
+
+// ──────────────────────────────────────────────
+// Governance Fixture
+// ──────────────────────────────────────────────
+//
+// Governed treasury instructions consume an executed noumen-core
+// PolicyProposal. The suite runs against the `short-policy-delays` build
+// (`npm run test:localnet`), where the layer minimum delays are seconds.
+
+const TEST_POLICY_DELAY: Record<number, number> = { 1: 4, 2: 2 };
+let nextProposalId = 1_000;
+
+/** `shared_types::policy_change_hash`: SHA-256(domain || borsh(payload)). */
+function policyChangeHash(domain: string, payload: Buffer): Buffer {
+  return createHash("sha256").update(Buffer.concat([Buffer.from(domain), payload])).digest();
+}
+
+interface GovernanceProposal {
+  proposalId: number;
+  proposalPda: PublicKey;
+  appliedPda: PublicKey;
+}
+
+async function proposePolicy(
+  provider: anchor.AnchorProvider,
+  aeonAuthority: Keypair,
+  layer: number,
+  changeHash: Buffer
+): Promise<GovernanceProposal> {
+  const proposalId = nextProposalId++;
+  const idBuf = encodeU32LE(proposalId);
+  const [aeonConfigPda] = findPda([Buffer.from("aeon_config")], CORE_PROGRAM_ID);
+  const [proposalPda] = findPda([Buffer.from("proposal"), idBuf], CORE_PROGRAM_ID);
+  const [appliedPda] = findPda([Buffer.from("applied_proposal"), idBuf], TREASURY_PROGRAM_ID);
+
+  // ProposePolicyChangeArgs: proposal_id, policy_layer, change_hash, delay_seconds
+  await sendTx(
+    provider,
+    new TransactionInstruction({
+      programId: CORE_PROGRAM_ID,
+      keys: [
+        { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+        { pubkey: proposalPda, isSigner: false, isWritable: true },
+        { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.concat([
+        ixDiscriminator("propose_policy_change"),
+        idBuf,
+        encodeU8(layer),
+        encodeBytes32(changeHash),
+        encodeI64LE(new BN(TEST_POLICY_DELAY[layer])),
+      ]),
+    }),
+    [aeonAuthority]
+  );
+  return { proposalId, proposalPda, appliedPda };
+}
+
+/**
+ * Proposes `changeHash` on `layer` and executes it once the delay has passed
+ * on-chain. The validator clock only loosely tracks wall time, so execution
+ * is retried while noumen-core still reports DelayNotElapsed.
+ */
+async function executedProposal(
+  provider: anchor.AnchorProvider,
+  aeonAuthority: Keypair,
+  layer: number,
+  changeHash: Buffer
+): Promise<GovernanceProposal> {
+  const proposal = await proposePolicy(provider, aeonAuthority, layer, changeHash);
+  const [aeonConfigPda] = findPda([Buffer.from("aeon_config")], CORE_PROGRAM_ID);
+  const executeIx = new TransactionInstruction({
+    programId: CORE_PROGRAM_ID,
+    keys: [
+      { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+      { pubkey: proposal.proposalPda, isSigner: false, isWritable: true },
+      { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+    ],
+    data: ixDiscriminator("execute_policy_change"),
+  });
+
+  await new Promise((resolve) => setTimeout(resolve, TEST_POLICY_DELAY[layer] * 1_000));
+  for (let attempt = 0; ; attempt++) {
+    try {
+      await sendTx(provider, executeIx, [aeonAuthority]);
+      return proposal;
+    } catch (err: any) {
+      if (attempt >= 30 || !hasCustomError(err, CoreErr.DelayNotElapsed)) throw err;
+      await new Promise((resolve) => setTimeout(resolve, 1_000));
+    }
+  }
+}
+
+// ──────────────────────────────────────────────
+// SPL Token Helpers (raw instructions, no spl-token dependency)
+// ──────────────────────────────────────────────
+
+const MINT_SPACE = 82;
+const TOKEN_ACCOUNT_SPACE = 165;
+
+/** Creates a local mint with no freeze authority. */
+async function createMint(
+  provider: anchor.AnchorProvider,
+  mint: Keypair,
+  mintAuthority: PublicKey,
+  decimals: number
+): Promise<void> {
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(MINT_SPACE);
+  const createIx = SystemProgram.createAccount({
+    fromPubkey: provider.wallet.publicKey,
+    newAccountPubkey: mint.publicKey,
+    lamports,
+    space: MINT_SPACE,
+    programId: TOKEN_PROGRAM_ID,
+  });
+  // InitializeMint2 (20): decimals, mint_authority, freeze_authority: None
+  const initIx = new TransactionInstruction({
+    programId: TOKEN_PROGRAM_ID,
+    keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+    data: Buffer.concat([encodeU8(20), encodeU8(decimals), encodePubkey(mintAuthority), encodeU8(0)]),
+  });
+  await sendIxs(provider, [createIx, initIx], [mint]);
+}
+
+/** Creates a token account for `mint` owned by `owner`. */
+async function createTokenAccount(
+  provider: anchor.AnchorProvider,
+  account: Keypair,
+  mint: PublicKey,
+  owner: PublicKey
+): Promise<void> {
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(TOKEN_ACCOUNT_SPACE);
+  const createIx = SystemProgram.createAccount({
+    fromPubkey: provider.wallet.publicKey,
+    newAccountPubkey: account.publicKey,
+    lamports,
+    space: TOKEN_ACCOUNT_SPACE,
+    programId: TOKEN_PROGRAM_ID,
+  });
+  // InitializeAccount3 (18): owner
+  const initIx = new TransactionInstruction({
+    programId: TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: account.publicKey, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([encodeU8(18), encodePubkey(owner)]),
+  });
+  await sendIxs(provider, [createIx, initIx], [account]);
+}
+
+/** Mints `amount` base units to `destination`. */
+async function mintTo(
+  provider: anchor.AnchorProvider,
+  mint: PublicKey,
+  destination: PublicKey,
+  mintAuthority: Keypair,
+  amount: BN
+): Promise<void> {
+  // MintTo (7): amount
+  const ix = new TransactionInstruction({
+    programId: TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: mint, isSigner: false, isWritable: true },
+      { pubkey: destination, isSigner: false, isWritable: true },
+      { pubkey: mintAuthority.publicKey, isSigner: true, isWritable: false },
+    ],
+    data: Buffer.concat([encodeU8(7), encodeU64LE(amount)]),
+  });
+  await sendTx(provider, ix, [mintAuthority]);
+}
+
 async function getAccountData(
   provider: anchor.AnchorProvider,
   address: PublicKey
@@ -144,6 +452,8 @@ describe("NOUMEN Protocol Integration Tests", () => {
   const creatorWallet = Keypair.generate();
   const apolloAuthority = Keypair.generate();
   const hermesAuthority = Keypair.generate();
+  // Registered by the treasury evacuation tests; evacuates at the very end
+  const coldWallet = Keypair.generate();
   const auditorAuthority = Keypair.generate();
 
   // ── PDAs (derived in before()) ──
@@ -153,6 +463,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
   let treasuryVaultPda: PublicKey;
   let donationVaultPda: PublicKey;
   let ccsConfigPda: PublicKey;
+  let revenueHistoryPda: PublicKey;
   let apolloConfigPda: PublicKey;
   let hermesConfigPda: PublicKey;
   let auditorConfigPda: PublicKey;
@@ -166,6 +477,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
     [treasuryVaultPda] = findPda([Buffer.from("treasury_vault")], TREASURY_PROGRAM_ID);
     [donationVaultPda] = findPda([Buffer.from("donation_vault")], TREASURY_PROGRAM_ID);
     [ccsConfigPda] = findPda([Buffer.from("ccs_config")], TREASURY_PROGRAM_ID);
+    [revenueHistoryPda] = findPda([Buffer.from("revenue_history")], TREASURY_PROGRAM_ID);
     [apolloConfigPda] = findPda([Buffer.from("apollo_config")], APOLLO_PROGRAM_ID);
     [hermesConfigPda] = findPda([Buffer.from("hermes_config")], HERMES_PROGRAM_ID);
     [auditorConfigPda] = findPda([Buffer.from("auditor_config")], AUDITOR_PROGRAM_ID);
@@ -473,6 +785,12 @@ describe("NOUMEN Protocol Integration Tests", () => {
   //  2. NOUMEN PROOF
   // ================================================================
   describe("noumen_proof", () => {
+    // Agent 1's ProofCursor: head of its DecisionLog hash chain
+    const [proofCursorPda] = findPda(
+      [Buffer.from("proof_cursor"), encodeU16LE(1)],
+      PROOF_PROGRAM_ID
+    );
+
     it("initialize_proof: creates ProofConfig PDA", async () => {
       const data = Buffer.concat([
         ixDiscriminator("initialize_proof"),
@@ -534,6 +852,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
         keys: [
           { pubkey: proofConfigPda, isSigner: false, isWritable: false },
           { pubkey: decisionLogPda, isSigner: false, isWritable: true },
+          { pubkey: proofCursorPda, isSigner: false, isWritable: true },
           { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
@@ -546,10 +865,18 @@ describe("NOUMEN Protocol Integration Tests", () => {
       expect(acct).to.not.be.null;
       expect(acct!.subarray(0, 8).equals(accountDiscriminator("DecisionLog"))).to.be.true;
       expect(acct!.readUInt16LE(8)).to.equal(agentId);
+      // First decision of the agent: prev_hash (offset 127) is zero
+      expect(acct!.subarray(127, 159).equals(Buffer.alloc(32))).to.be.true;
+
+      const cursor = await getAccountData(provider, proofCursorPda);
+      expect(cursor!.subarray(0, 8).equals(accountDiscriminator("ProofCursor"))).to.be.true;
+      // last_nonce at offset 10, chain_hash at offset 18
+      expect(cursor!.readBigUInt64LE(10)).to.equal(BigInt(1));
+      expect(cursor!.subarray(18, 50).equals(decisionChainHash(acct!))).to.be.true;
     });
 
     it("log_decision: execution-class with < 2 evidence families fails (A0-17)", async () => {
-      const nonce = new BN(900);
+      const nonce = new BN(2);
       const agentIdBuf = encodeU16LE(1);
       const nonceBuf = encodeU64LE(nonce);
 
@@ -575,6 +902,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
         keys: [
           { pubkey: proofConfigPda, isSigner: false, isWritable: false },
           { pubkey: decisionLogPda, isSigner: false, isWritable: true },
+          { pubkey: proofCursorPda, isSigner: false, isWritable: true },
           { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
@@ -590,7 +918,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
     });
 
     it("log_decision: non-execution-class with 1 family succeeds (A0-17 only applies to execution)", async () => {
-      const nonce = new BN(901);
+      const nonce = new BN(2);
       const agentIdBuf = encodeU16LE(1);
       const nonceBuf = encodeU64LE(nonce);
 
@@ -616,6 +944,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
         keys: [
           { pubkey: proofConfigPda, isSigner: false, isWritable: false },
           { pubkey: decisionLogPda, isSigner: false, isWritable: true },
+          { pubkey: proofCursorPda, isSigner: false, isWritable: true },
           { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
@@ -626,6 +955,63 @@ describe("NOUMEN Protocol Integration Tests", () => {
 
       const acct = await getAccountData(provider, decisionLogPda);
       expect(acct).to.not.be.null;
+
+      // Linked to decision 1 through the cursor's previous chain hash
+      const [firstLogPda] = findPda(
+        [Buffer.from("decision"), agentIdBuf, encodeU64LE(new BN(1))],
+        PROOF_PROGRAM_ID
+      );
+      const firstLog = await getAccountData(provider, firstLogPda);
+      expect(acct!.subarray(127, 159).equals(decisionChainHash(firstLog!))).to.be.true;
+
+      const cursor = await getAccountData(provider, proofCursorPda);
+      expect(cursor!.readBigUInt64LE(10)).to.equal(BigInt(2));
+      expect(cursor!.subarray(18, 50).equals(decisionChainHash(acct!))).to.be.true;
+    });
+
+    it("log_decision: nonce gap or replay is rejected (NonceOutOfSequence)", async () => {
+      const agentIdBuf = encodeU16LE(1);
+      for (const nonce of [new BN(4), new BN(1)]) {
+        const nonceBuf = encodeU64LE(nonce);
+        const [decisionLogPda] = findPda(
+          [Buffer.from("decision"), agentIdBuf, nonceBuf],
+          PROOF_PROGRAM_ID
+        );
+
+        const data = Buffer.concat([
+          ixDiscriminator("log_decision"),
+          agentIdBuf,
+          nonceBuf,
+          encodeBytes32(randomHash()),
+          encodeBytes32(randomHash()),
+          encodeBytes32(randomHash()),
+          encodeU8(0b00001),
+          encodeU8(0),
+          encodeBool(false),
+        ]);
+
+        const ix = new TransactionInstruction({
+          programId: PROOF_PROGRAM_ID,
+          keys: [
+            { pubkey: proofConfigPda, isSigner: false, isWritable: false },
+            { pubkey: decisionLogPda, isSigner: false, isWritable: true },
+            { pubkey: proofCursorPda, isSigner: false, isWritable: true },
+            { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data,
+        });
+
+        try {
+          await sendTx(provider, ix, [keeperAuthority]);
+          expect.fail("Should have rejected an out-of-sequence nonce");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+      }
+
+      const cursor = await getAccountData(provider, proofCursorPda);
+      expect(cursor!.readBigUInt64LE(10)).to.equal(BigInt(2));
     });
 
     it("confirm_execution (log_execution_result): links ExecutionResult to DecisionLog", async () => {
@@ -676,6 +1062,99 @@ describe("NOUMEN Protocol Integration Tests", () => {
       const execConfOffset = 8 + 2 + 8 + 32 + 32 + 32 + 1 + 1 + 8 + 1;
       expect(logAcct!.readUInt8(execConfOffset)).to.equal(1);
     });
+
+    describe("batch inclusion proofs", () => {
+      // Three decisions padded to four slots: root = H(H(l0, l1), H(l2, 0))
+      const leaves = [randomHash(), randomHash(), randomHash()];
+      const hashed = leaves.map(merkleLeafHash);
+      const empty = Buffer.alloc(32);
+      const left = merkleNodeHash(hashed[0], hashed[1]);
+      const right = merkleNodeHash(hashed[2], empty);
+      const root = merkleNodeHash(left, right);
+
+      const [batchProofPda] = findPda(
+        [Buffer.from("batch"), encodeU16LE(1), encodeU64LE(new BN(1))],
+        PROOF_PROGRAM_ID
+      );
+
+      function verifyIx(decisionHash: Buffer, leafIndex: number, proof: Buffer[]): TransactionInstruction {
+        // VerifyDecisionInBatchArgs: decision_hash, leaf_index, proof (Vec<[u8; 32]>)
+        return new TransactionInstruction({
+          programId: PROOF_PROGRAM_ID,
+          keys: [{ pubkey: batchProofPda, isSigner: false, isWritable: false }],
+          data: Buffer.concat([
+            ixDiscriminator("verify_decision_in_batch"),
+            encodeBytes32(decisionHash),
+            encodeU32LE(leafIndex),
+            encodeU32LE(proof.length),
+            ...proof,
+          ]),
+        });
+      }
+
+      it("submit_batch_proof: stores the Merkle root of three decisions", async () => {
+        const now = Math.floor(Date.now() / 1000);
+        // SubmitBatchProofArgs: agent_id, batch_nonce, merkle_root, leaf_count,
+        //   start_timestamp, end_timestamp
+        const data = Buffer.concat([
+          ixDiscriminator("submit_batch_proof"),
+          encodeU16LE(1),
+          encodeU64LE(new BN(1)),
+          encodeBytes32(root),
+          encodeU32LE(leaves.length),
+          encodeI64LE(new BN(now - 60)),
+          encodeI64LE(new BN(now)),
+        ]);
+
+        const ix = new TransactionInstruction({
+          programId: PROOF_PROGRAM_ID,
+          keys: [
+            { pubkey: proofConfigPda, isSigner: false, isWritable: false },
+            { pubkey: batchProofPda, isSigner: false, isWritable: true },
+            { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data,
+        });
+
+        await sendTx(provider, ix, [keeperAuthority]);
+
+        const acct = await getAccountData(provider, batchProofPda);
+        // merkle_root at offset 8 + 2 + 8 = 18
+        expect(acct!.subarray(18, 50).equals(root)).to.be.true;
+      });
+
+      it("verify_decision_in_batch: returns true for every real leaf", async () => {
+        const proofs = [
+          [hashed[1], right],
+          [hashed[0], right],
+          [empty, left],
+        ];
+        for (let i = 0; i < leaves.length; i++) {
+          const ret = await simulateReturnData(provider, verifyIx(leaves[i], i, proofs[i]));
+          expect(ret.length).to.equal(1);
+          expect(ret.readUInt8(0)).to.equal(1);
+        }
+
+        // Permissionless: any fee payer can land it on-chain
+        await sendTx(provider, verifyIx(leaves[2], 2, proofs[2]), []);
+      });
+
+      it("verify_decision_in_batch: returns false for a wrong leaf, index or path", async () => {
+        const cases: [Buffer, number, Buffer[]][] = [
+          [randomHash(), 1, [hashed[0], right]],           // leaf not in batch
+          [leaves[1], 0, [hashed[0], right]],              // wrong index
+          [leaves[1], 1, [hashed[0]]],                     // path too short
+          [leaves[1], 1, [right, hashed[0]]],              // siblings out of order
+          [empty, 3, [hashed[2], left]],                   // padding slot past leaf_count
+          [hashed[0], 0, [hashed[1], right]],              // leaf hash replayed as leaf data
+        ];
+        for (const [leaf, index, proof] of cases) {
+          const ret = await simulateReturnData(provider, verifyIx(leaf, index, proof));
+          expect(ret.length === 0 || ret.readUInt8(0) === 0).to.be.true;
+        }
+      });
+    });
   });
 
   // ================================================================
@@ -716,7 +1195,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
       expect(vaultAcct!.subarray(0, 8).equals(accountDiscriminator("TreasuryVault"))).to.be.true;
     });
 
-    it("initialize_donations: creates DonationVault and CCSConfig (step 2)", async () => {
+    it("initialize_donations: creates DonationVault, CCSConfig and RevenueHistory (step 2)", async () => {
       const data = Buffer.concat([
         ixDiscriminator("initialize_donations"),
       ]);
@@ -728,6 +1207,7 @@ describe("NOUMEN Protocol Integration Tests", () => {
           { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
           { pubkey: donationVaultPda, isSigner: false, isWritable: true },
           { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
         data,
@@ -744,47 +1224,72 @@ describe("NOUMEN Protocol Integration Tests", () => {
       const ccsAcct = await getAccountData(provider, ccsConfigPda);
       expect(ccsAcct).to.not.be.null;
       expect(ccsAcct!.subarray(0, 8).equals(accountDiscriminator("CCSConfig"))).to.be.true;
+
+      // RevenueHistory
+      const historyAcct = await getAccountData(provider, revenueHistoryPda);
+      expect(historyAcct).to.not.be.null;
+      expect(historyAcct!.subarray(0, 8).equals(accountDiscriminator("RevenueHistory"))).to.be.true;
     });
 
-    it("record_donation_receipt (accept_donation): records donation with hashed wallet (A0-27)", async () => {
-      const nonce = new BN(1);
+    it("donate: any wallet donates SOL and gets a receipt with a salted wallet hash (A0-27)", async () => {
       const amount = new BN(500_000_000); // 0.5 SOL
-      const sourceWalletHash = randomHash();
-      const receiptHash = randomHash();
+      const salt = randomHash();
 
+      // First receipt: nonce = DonationVault.receipt_count = 0
       const [donationReceiptPda] = findPda(
-        [Buffer.from("donation_receipt"), encodeU64LE(nonce)],
+        [Buffer.from("donation_receipt"), encodeU64LE(new BN(0))],
+        TREASURY_PROGRAM_ID
+      );
+
+      // Donor has never paid for a service: tracker is uninitialized, no correlation
+      const [donorTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), creatorWallet.publicKey.toBuffer()],
         TREASURY_PROGRAM_ID
       );
 
-      // Args: nonce, amount, source_wallet_hash, disclosure_mode, receipt_hash
+      // Args: amount, salt, disclosure_mode
       const data = Buffer.concat([
-        ixDiscriminator("record_donation_receipt"),
-        encodeU64LE(nonce),
+        ixDiscriminator("donate"),
         encodeU64LE(amount),
-        encodeBytes32(sourceWalletHash),
+        encodeBytes32(salt),
         encodeU8(0),                           // Pseudonymous
-        encodeBytes32(receiptHash),
       ]);
 
       const ix = new TransactionInstruction({
         programId: TREASURY_PROGRAM_ID,
         keys: [
-          { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: creatorWallet.publicKey, isSigner: true, isWritable: true },
           { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
-          { pubkey: donationReceiptPda, isSigner: false, isWritable: true },
           { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: donationReceiptPda, isSigner: false, isWritable: true },
+          { pubkey: donorTrackerPda, isSigner: false, isWritable: false },
           { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
         data,
       });
 
-      await sendTx(provider, ix, [keeperAuthority]);
+      const vaultBefore = await provider.connection.getBalance(donationVaultPda);
+      await sendTx(provider, ix, [creatorWallet]);
+      const vaultAfter = await provider.connection.getBalance(donationVaultPda);
+      expect(vaultAfter - vaultBefore).to.equal(amount.toNumber());
 
-      // Receipt PDA
+      // Receipt PDA: amount at 16, source_wallet_hash at 32 (never the raw address)
       const receiptAcct = await getAccountData(provider, donationReceiptPda);
       expect(receiptAcct).to.not.be.null;
       expect(receiptAcct!.subarray(0, 8).equals(accountDiscriminator("DonationReceipt"))).to.be.true;
+      expect(new BN(receiptAcct!.subarray(16, 24), "le").eq(amount)).to.be.true;
+      const expectedHash = createHash("sha256")
+        .update(Buffer.concat([Buffer.from("donor_wallet"), salt, creatorWallet.publicKey.toBuffer()]))
+        .digest();
+      expect(receiptAcct!.subarray(32, 64).equals(expectedHash)).to.be.true;
+      // counts_as_donation at 8 + 8*3 + 32 + 2 = 66, correlated_flag at 71
+      expect(receiptAcct!.readUInt8(66)).to.equal(1);
+      expect(receiptAcct!.readUInt8(71)).to.equal(0);
+      // receipt_hash at 72: domain-tagged hash over nonce..receipt_status (bytes 8..66)
+      const expectedReceiptHash = createHash("sha256")
+        .update(Buffer.concat([Buffer.from("donation_receipt"), receiptAcct!.subarray(8, 66)]))
+        .digest();
+      expect(receiptAcct!.subarray(72, 104).equals(expectedReceiptHash)).to.be.true;
 
       // DonationVault: total_received=0.5SOL, pending_sweep=0.5SOL
       const donAcct = await getAccountData(provider, donationVaultPda);
@@ -794,30 +1299,519 @@ describe("NOUMEN Protocol Integration Tests", () => {
       expect(pendingSweep.eq(amount)).to.be.true;
     });
 
-    it("update_revenue_averages (record_revenue): keeper updates CCS averages", async () => {
-      const avg7d = new BN(2_000_000_000);
-      const avg30d = new BN(5_000_000_000);
+    it("release_reserve: rejected without an executed policy proposal", async () => {
+      const proposalIdBuf = encodeU32LE(9_998);
+      const [proposalPda] = findPda([Buffer.from("proposal"), proposalIdBuf], CORE_PROGRAM_ID);
+      const [appliedPda] = findPda([Buffer.from("applied_proposal"), proposalIdBuf], TREASURY_PROGRAM_ID);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: proposalPda, isSigner: false, isWritable: false },
+          { pubkey: appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("release_reserve"), encodeU64LE(new BN(1))]),
+      });
+
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have rejected reserve release without a proposal");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.AccountNotInitialized);
+      }
+    });
+
+    it("delegate_reserve_stake: rejected without an executed policy proposal", async () => {
+      const { current } = await provider.connection.getVoteAccounts();
+      const voteAccount = new PublicKey(current[0].votePubkey);
+      const proposalIdBuf = encodeU32LE(9_997);
+      const [proposalPda] = findPda([Buffer.from("proposal"), proposalIdBuf], CORE_PROGRAM_ID);
+      const [appliedPda] = findPda([Buffer.from("applied_proposal"), proposalIdBuf], TREASURY_PROGRAM_ID);
+      const [stakeAuthorityPda] = findPda([Buffer.from("stake_authority")], TREASURY_PROGRAM_ID);
+      // First stake: index = TreasuryVault.stake_account_count = 0
+      const indexBuf = encodeU32LE(0);
+      const [stakeAccountPda] = findPda([Buffer.from("stake_account"), indexBuf], TREASURY_PROGRAM_ID);
+      const [treasuryStakePda] = findPda([Buffer.from("treasury_stake"), indexBuf], TREASURY_PROGRAM_ID);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: stakeAuthorityPda, isSigner: false, isWritable: false },
+          { pubkey: stakeAccountPda, isSigner: false, isWritable: true },
+          { pubkey: treasuryStakePda, isSigner: false, isWritable: true },
+          { pubkey: voteAccount, isSigner: false, isWritable: false },
+          { pubkey: proposalPda, isSigner: false, isWritable: false },
+          { pubkey: appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: SYSVAR_STAKE_HISTORY_PUBKEY, isSigner: false, isWritable: false },
+          { pubkey: new PublicKey("StakeConfig11111111111111111111111111111111"), isSigner: false, isWritable: false },
+          { pubkey: StakeProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("delegate_reserve_stake"), encodeU64LE(new BN(LAMPORTS_PER_SOL))]),
+      });
+
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have rejected stake delegation without a proposal");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.AccountNotInitialized);
+      }
+      expect(await getAccountData(provider, treasuryStakePda)).to.be.null;
+    });
+
+    it("register_expense_payee + pay_operational_expense: payout beyond operations_lamports is rejected", async () => {
+      const payee = Keypair.generate().publicKey;
+      const [payeePda] = findPda([Buffer.from("payee"), payee.toBuffer()], TREASURY_PROGRAM_ID);
+
+      const registerIx = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: payee, isSigner: false, isWritable: false },
+          { pubkey: payeePda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("register_expense_payee"), encodeBytes32(randomHash())]),
+      });
+      await sendTx(provider, registerIx, [aeonAuthority]);
+
+      const payeeAcct = await getAccountData(provider, payeePda);
+      expect(payeeAcct!.subarray(0, 8).equals(accountDiscriminator("ExpensePayee"))).to.be.true;
+      expect(payeeAcct!.readUInt8(8 + 32 + 32)).to.equal(1); // is_active
+
+      const invoiceHash = randomHash();
+      const [expensePda] = findPda([Buffer.from("expense"), invoiceHash], TREASURY_PROGRAM_ID);
+      const payIx = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: payeePda, isSigner: false, isWritable: true },
+          { pubkey: payee, isSigner: false, isWritable: true },
+          { pubkey: expensePda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("pay_operational_expense"),
+          encodePubkey(payee),
+          encodeU64LE(new BN(1)),
+          encodeBytes32(invoiceHash),
+        ]),
+      });
+
+      // operations_lamports is 0: nothing can be paid out
+      try {
+        await sendTx(provider, payIx, [aeonAuthority]);
+        expect.fail("Should have thrown InsufficientOperationsBalance");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InsufficientOperationsBalance);
+      }
+      expect(await getAccountData(provider, expensePda)).to.be.null;
+    });
 
+    it("update_revenue_averages: permissionless crank derives CCS averages from RevenueHistory", async () => {
       const data = Buffer.concat([
         ixDiscriminator("update_revenue_averages"),
-        encodeU64LE(avg7d),
-        encodeU64LE(avg30d),
       ]);
 
       const ix = new TransactionInstruction({
         programId: TREASURY_PROGRAM_ID,
         keys: [
-          { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: false },
           { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
           { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
         ],
         data,
       });
 
-      await sendTx(provider, ix, [keeperAuthority]);
+      // Fee payer only; no keeper signature required
+      await sendTx(provider, ix, []);
 
+      // No completed days yet: both averages are zero
+      // CCSConfig: disc(8) + 4 bands * 12 + 3 * u16 -> avg_7d at 62, avg_30d at 70
       const ccsAcct = await getAccountData(provider, ccsConfigPda);
       expect(ccsAcct).to.not.be.null;
+      expect(new BN(ccsAcct!.subarray(62, 70), "le").isZero()).to.be.true;
+      expect(new BN(ccsAcct!.subarray(70, 78), "le").isZero()).to.be.true;
+    });
+
+    it("allocate_agent_budget: creates BudgetAllocation for agent_id=1", async () => {
+      const agentIdBuf = encodeU16LE(1);
+      const [budgetPda] = findPda([Buffer.from("budget"), agentIdBuf], TREASURY_PROGRAM_ID);
+      const [agentManifestPda] = findPda([Buffer.from("agent"), agentIdBuf], CORE_PROGRAM_ID);
+
+      // Vault is empty, so the only allocation within the 15% cap is zero
+      const data = Buffer.concat([
+        ixDiscriminator("allocate_agent_budget"),
+        agentIdBuf,
+        encodeU64LE(new BN(0)),                // allocated
+        encodeU64LE(new BN(0)),                // daily_cap
+      ]);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+          { pubkey: budgetPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data,
+      });
+
+      await sendTx(provider, ix, [aeonAuthority]);
+
+      const acct = await getAccountData(provider, budgetPda);
+      expect(acct).to.not.be.null;
+      expect(acct!.subarray(0, 8).equals(accountDiscriminator("BudgetAllocation"))).to.be.true;
+      expect(acct!.readUInt16LE(8)).to.equal(1);
+    });
+
+    it("disburse_agent_budget: amount above remaining allocation is rejected", async () => {
+      const agentIdBuf = encodeU16LE(1);
+      const [budgetPda] = findPda([Buffer.from("budget"), agentIdBuf], TREASURY_PROGRAM_ID);
+      const [agentManifestPda] = findPda([Buffer.from("agent"), agentIdBuf], CORE_PROGRAM_ID);
+
+      const data = Buffer.concat([
+        ixDiscriminator("disburse_agent_budget"),
+        agentIdBuf,
+        encodeU64LE(new BN(1)),
+      ]);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: budgetPda, isSigner: false, isWritable: true },
+          { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+          { pubkey: aeonAuthority.publicKey, isSigner: false, isWritable: true }, // agent authority
+        ],
+        data,
+      });
+
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have thrown AgentBudgetExhausted");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.AgentBudgetExhausted);
+      }
+    });
+
+    describe("SPL payment rail (local USDC-like mint)", () => {
+      const usdcMint = Keypair.generate();
+      const payerTokenAccount = Keypair.generate();
+      let splVaultPda: PublicKey;
+      let vaultTokenAccountPda: PublicKey;
+
+      before(async () => {
+        await createMint(provider, usdcMint, superAuthority.publicKey, 6);
+        await createTokenAccount(provider, payerTokenAccount, usdcMint.publicKey, superAuthority.publicKey);
+        await mintTo(provider, usdcMint.publicKey, payerTokenAccount.publicKey, superAuthority, new BN(1_000_000_000));
+
+        [splVaultPda] = findPda([Buffer.from("spl_vault"), usdcMint.publicKey.toBuffer()], TREASURY_PROGRAM_ID);
+        [vaultTokenAccountPda] = findPda(
+          [Buffer.from("spl_vault_tokens"), usdcMint.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+      });
+
+      it("local mint: payer holds 1000 tokens (6 decimals)", async () => {
+        const acct = await getAccountData(provider, payerTokenAccount.publicKey);
+        expect(acct).to.not.be.null;
+        // SPL token account: mint(32) owner(32) amount(8)
+        expect(new PublicKey(acct!.subarray(0, 32)).equals(usdcMint.publicKey)).to.be.true;
+        expect(new BN(acct!.subarray(64, 72), "le").eq(new BN(1_000_000_000))).to.be.true;
+      });
+
+      it("set_spl_mint_allowlist: rejected while the proposal is still pending", async () => {
+        const rate = new BN(150_000_000);
+        const changeHash = policyChangeHash(
+          "spl_mint_allowlist",
+          Buffer.concat([usdcMint.publicKey.toBuffer(), encodeBool(true), encodeU64LE(rate)])
+        );
+        const { proposalPda, appliedPda } = await proposePolicy(provider, aeonAuthority, 1, changeHash);
+
+        const data = Buffer.concat([
+          ixDiscriminator("set_spl_mint_allowlist"),
+          encodeBool(true),                          // allowed
+          encodeU64LE(new BN(150_000_000)),          // token_units_per_sol (150 USDC)
+        ]);
+
+        const ix = new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: usdcMint.publicKey, isSigner: false, isWritable: false },
+            { pubkey: splVaultPda, isSigner: false, isWritable: true },
+            { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+            { pubkey: proposalPda, isSigner: false, isWritable: false },
+            { pubkey: appliedPda, isSigner: false, isWritable: true },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data,
+        });
+
+        try {
+          await sendTx(provider, ix, [aeonAuthority]);
+          expect.fail("Should have thrown ProposalNotExecuted");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.ProposalNotExecuted);
+        }
+
+        expect(await getAccountData(provider, splVaultPda)).to.be.null;
+      });
+
+      it("process_service_payment_spl: mint not on the allowlist is rejected", async () => {
+        const serviceIdBuf = encodeU16LE(1);
+        const [serviceEntryPda] = findPda([Buffer.from("service"), serviceIdBuf], SERVICE_PROGRAM_ID);
+        const [volumeTrackerPda] = findPda(
+          [Buffer.from("volume_tracker"), superAuthority.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+
+        const data = Buffer.concat([
+          ixDiscriminator("process_service_payment_spl"),
+          serviceIdBuf,
+          encodeU64LE(new BN(1_500_000)),
+        ]);
+
+        const ix = new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: false },
+            { pubkey: usdcMint.publicKey, isSigner: false, isWritable: false },
+            { pubkey: splVaultPda, isSigner: false, isWritable: true },
+            { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+            { pubkey: payerTokenAccount.publicKey, isSigner: false, isWritable: true },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+            { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data,
+        });
+
+        try {
+          await sendTx(provider, ix, []);
+          expect.fail("Should have rejected a mint without an SplTreasuryVault");
+        } catch (err: any) {
+          expectCustomError(err, AnchorErr.AccountNotInitialized);
+        }
+
+        // Payer balance unchanged
+        const acct = await getAccountData(provider, payerTokenAccount.publicKey);
+        expect(new BN(acct!.subarray(64, 72), "le").eq(new BN(1_000_000_000))).to.be.true;
+      });
+    });
+
+    describe("account migration", () => {
+      function migrateIx(authority: PublicKey): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: authority, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("migrate_treasury_accounts"),
+        });
+      }
+
+      it("migrate_treasury_accounts: rejects a signer other than the super authority", async () => {
+        try {
+          await sendTx(provider, migrateIx(aeonAuthority.publicKey), [aeonAuthority]);
+          expect.fail("Should have thrown UnauthorizedAeon");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.UnauthorizedAeon);
+        }
+      });
+
+      it("migrate_treasury_accounts: leaves accounts already at full size untouched", async () => {
+        const before = await Promise.all(
+          [treasuryConfigPda, treasuryVaultPda, donationVaultPda].map((pda) => getAccountData(provider, pda))
+        );
+        await sendTx(provider, migrateIx(superAuthority.publicKey), []);
+        const after = await Promise.all(
+          [treasuryConfigPda, treasuryVaultPda, donationVaultPda].map((pda) => getAccountData(provider, pda))
+        );
+        for (let i = 0; i < before.length; i++) {
+          expect(after[i]!.equals(before[i]!)).to.be.true;
+        }
+        // TreasuryConfig::LEN
+        expect(after[0]!.length).to.equal(270);
+      });
+    });
+
+    describe("creator wallet rotation", () => {
+      const newCreatorWallet = Keypair.generate();
+
+      function rotationIx(name: string, authority: Keypair, args: Buffer = Buffer.alloc(0)): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          ],
+          data: Buffer.concat([ixDiscriminator(name), args]),
+        });
+      }
+
+      it("propose_creator_wallet: rejects a signer that is neither creator nor super authority", async () => {
+        try {
+          await sendTx(
+            provider,
+            rotationIx("propose_creator_wallet", aeonAuthority, encodePubkey(newCreatorWallet.publicKey)),
+            [aeonAuthority]
+          );
+          expect.fail("Should have thrown InvalidCreatorWallet");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+      });
+
+      it("propose_creator_wallet: creator proposes a new wallet with a 72h delay", async () => {
+        await sendTx(
+          provider,
+          rotationIx("propose_creator_wallet", creatorWallet, encodePubkey(newCreatorWallet.publicKey)),
+          [creatorWallet]
+        );
+
+        const acct = await getAccountData(provider, treasuryConfigPda);
+        // pending_creator_wallet after is_initialized and bump: 8 + 32*4 + 1 + 1 = 138
+        expect(new PublicKey(acct!.subarray(138, 170)).equals(newCreatorWallet.publicKey)).to.be.true;
+        const changeAt = new BN(acct!.subarray(170, 178), "le").toNumber();
+        expect(changeAt).to.be.greaterThan(Math.floor(Date.now() / 1000) + 259_200 - 600);
+      });
+
+      it("accept_creator_wallet: rejected before the delay has elapsed", async () => {
+        try {
+          await sendTx(provider, rotationIx("accept_creator_wallet", newCreatorWallet), [newCreatorWallet]);
+          expect.fail("Should have thrown CreatorWalletDelayNotElapsed");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+      });
+
+      it("cancel_creator_wallet_change: super authority clears the pending wallet", async () => {
+        await sendTx(provider, rotationIx("cancel_creator_wallet_change", superAuthority), []);
+
+        const acct = await getAccountData(provider, treasuryConfigPda);
+        expect(new PublicKey(acct!.subarray(138, 170)).equals(PublicKey.default)).to.be.true;
+        expect(new BN(acct!.subarray(170, 178), "le").isZero()).to.be.true;
+        // creator_wallet unchanged at 8 + 32*3 = 104
+        expect(new PublicKey(acct!.subarray(104, 136)).equals(creatorWallet.publicKey)).to.be.true;
+      });
+    });
+
+    describe("emergency evacuation", () => {
+      // cold_wallet after referral policy (178..188) and agent_revenue_share_bps (188..190)
+      const coldWalletOffset = 190;
+
+      function registerColdWalletIx(current: PublicKey, next: PublicKey): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: current, isSigner: true, isWritable: false },
+            { pubkey: next, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          ],
+          data: ixDiscriminator("register_cold_wallet"),
+        });
+      }
+
+      it("register_cold_wallet: super authority and the new cold wallet register it", async () => {
+        await sendTx(
+          provider,
+          registerColdWalletIx(coldWallet.publicKey, coldWallet.publicKey),
+          [coldWallet]
+        );
+
+        const acct = await getAccountData(provider, treasuryConfigPda);
+        expect(
+          new PublicKey(acct!.subarray(coldWalletOffset, coldWalletOffset + 32)).equals(coldWallet.publicKey)
+        ).to.be.true;
+      });
+
+      it("register_cold_wallet: replacement without the current cold wallet is rejected", async () => {
+        const attacker = Keypair.generate();
+        try {
+          await sendTx(
+            provider,
+            registerColdWalletIx(attacker.publicKey, attacker.publicKey),
+            [attacker]
+          );
+          expect.fail("Should have thrown InvalidColdWallet");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+      });
+
+      it("arm_treasury_evacuation: rejected while noumen-core is not Halted", async () => {
+        // The core circuit breaker was left at Restricted by the noumen_core tests
+        const ix = new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+            { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("arm_treasury_evacuation"),
+        });
+        try {
+          await sendTx(provider, ix, []);
+          expect.fail("Should have thrown CircuitBreakerNotHalted");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+      });
+
+      it("evacuate_treasury: rejected while not Halted and not armed", async () => {
+        const ix = new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: coldWallet.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+            { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+            { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          ],
+          data: ixDiscriminator("evacuate_treasury"),
+        });
+        try {
+          await sendTx(provider, ix, [coldWallet]);
+          expect.fail("Should have thrown CircuitBreakerNotHalted");
+        } catch (err: any) {
+          expect(err.toString()).to.contain("custom program error");
+        }
+
+        // Treasury still live
+        const acct = await getAccountData(provider, treasuryConfigPda);
+        expect(acct!.readUInt8(136)).to.equal(1); // is_initialized
+      });
     });
   });
 
@@ -1542,6 +2536,1703 @@ describe("NOUMEN Protocol Integration Tests", () => {
     });
   });
 
+  // ================================================================
+  //  TREASURY SERVICE PAYMENTS (needs an Active ServiceEntry)
+  // ================================================================
+  describe("noumen_treasury service payments", () => {
+    const serviceIdBuf = encodeU16LE(1);
+    const paymentNonce = new BN(1);
+    let serviceEntryPda: PublicKey;
+    let volumeTrackerPda: PublicKey;
+    let paymentRecordPda: PublicKey;
+    let ownerBudgetPda: PublicKey;
+
+    before(() => {
+      [serviceEntryPda] = findPda([Buffer.from("service"), serviceIdBuf], SERVICE_PROGRAM_ID);
+      // Service 1 is owned by agent 1, whose BudgetAllocation exists
+      [ownerBudgetPda] = findPda([Buffer.from("budget"), encodeU16LE(1)], TREASURY_PROGRAM_ID);
+      [volumeTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), superAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+      [paymentRecordPda] = findPda(
+        [Buffer.from("payment"), superAuthority.publicKey.toBuffer(), encodeU64LE(paymentNonce)],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    function paymentIx(amount: BN, nonce = paymentNonce, recordPda = paymentRecordPda): TransactionInstruction {
+      const data = Buffer.concat([
+        ixDiscriminator("process_service_payment"),
+        serviceIdBuf,
+        encodeU64LE(amount),
+        encodeU64LE(nonce),
+      ]);
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+          { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+          { pubkey: recordPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+          { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+          { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referral_link: none
+        ],
+        data,
+      });
+    }
+
+    function refundIx(
+      authority: PublicKey,
+      recordPda = paymentRecordPda,
+      payer = superAuthority.publicKey,
+      trackerPda = volumeTrackerPda
+    ): TransactionInstruction {
+      const data = Buffer.concat([
+        ixDiscriminator("refund_service_payment"),
+        encodeBytes32(randomHash()),           // reason_hash
+      ]);
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: authority, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: auditorConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          { pubkey: recordPda, isSigner: false, isWritable: true },
+          { pubkey: payer, isSigner: false, isWritable: true },
+          { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+          { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+          { pubkey: trackerPda, isSigner: false, isWritable: true },
+        ],
+        data,
+      });
+    }
+
+    /** Undiscounted price: max(price_lamports, min_price_lamports). */
+    async function servicePrice(): Promise<BN> {
+      const acct = await getAccountData(provider, serviceEntryPda);
+      // ServiceEntry: disc(8) id(2) agent(2) tier(1) level(1) price(8) cost(8) min_price(8)
+      const price = new BN(acct!.subarray(14, 22), "le");
+      const minPrice = new BN(acct!.subarray(30, 38), "le");
+      return BN.max(price, minPrice);
+    }
+
+    it("setup: payer volume tracker created", async () => {
+      const trackerIx = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: ixDiscriminator("initialize_volume_tracker"),
+      });
+      await sendTx(provider, trackerIx, []);
+    });
+
+    it("process_service_payment: service that is not Active is rejected", async () => {
+      const entry = await getAccountData(provider, serviceEntryPda);
+      expect(entry!.readUInt8(13)).to.not.equal(2); // level != Active
+      try {
+        await sendTx(provider, paymentIx(await servicePrice()), []);
+        expect.fail("Should have thrown ServiceNotActive");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.ServiceNotActive);
+      }
+      expect(await getAccountData(provider, paymentRecordPda)).to.be.null;
+    });
+
+    it("setup: service promoted to Active", async () => {
+      for (const level of [1, 2]) {
+        const ix = new TransactionInstruction({
+          programId: SERVICE_PROGRAM_ID,
+          keys: [
+            { pubkey: serviceConfigPda, isSigner: false, isWritable: false },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("update_service_level"), serviceIdBuf, encodeU8(level)]),
+        });
+        await sendTx(provider, ix, [aeonAuthority]);
+      }
+
+      const entry = await getAccountData(provider, serviceEntryPda);
+      expect(entry!.readUInt8(13)).to.equal(2); // level = Active
+    });
+
+    it("process_service_payment: amount below or above the list price is rejected", async () => {
+      const price = await servicePrice();
+      for (const amount of [price.subn(1), price.addn(1)]) {
+        try {
+          await sendTx(provider, paymentIx(amount), []);
+          expect.fail("Should have thrown PaymentAmountMismatch");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.PaymentAmountMismatch);
+        }
+      }
+    });
+
+    it("process_service_payment: pays the catalog price and creates a PaymentRecord", async () => {
+      const price = await servicePrice();
+      await sendTx(provider, paymentIx(price), []);
+
+      const acct = await getAccountData(provider, paymentRecordPda);
+      expect(acct).to.not.be.null;
+      expect(acct!.subarray(0, 8).equals(accountDiscriminator("PaymentRecord"))).to.be.true;
+      expect(new PublicKey(acct!.subarray(8, 40)).equals(superAuthority.publicKey)).to.be.true;
+      // amount_lamports at 8 + 32 + 8 + 2 = 50
+      expect(new BN(acct!.subarray(50, 58), "le").eq(price)).to.be.true;
+
+      // Attributed to agent 1: revenue_generated after bump and agent_type: 8 + 2 + 8*6 + 1 + 1 = 60
+      const budgetAcct = await getAccountData(provider, ownerBudgetPda);
+      expect(new BN(budgetAcct!.subarray(60, 68), "le").eq(price)).to.be.true;
+
+      // Tracker: monthly_scan_count at 40 counts the call; still tier 0 (< 10 queries) at 54
+      const tracker = await getAccountData(provider, volumeTrackerPda);
+      expect(tracker!.readUInt16LE(40)).to.equal(1);
+      expect(tracker!.readUInt8(54)).to.equal(0);
+    });
+
+    it("refund_service_payment: non-keeper, non-auditor signer is rejected", async () => {
+      try {
+        await sendTx(provider, refundIx(creatorWallet.publicKey), [creatorWallet]);
+        expect.fail("Should have thrown UnauthorizedRefundAuthority");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("refund_service_payment: keeper reverses operations and reserve buckets", async () => {
+      await sendTx(provider, refundIx(keeperAuthority.publicKey), [keeperAuthority]);
+
+      const acct = await getAccountData(provider, paymentRecordPda);
+      // refunded at 8 + 32 + 8 + 2 + 8*5 = 90
+      expect(acct!.readUInt8(90)).to.equal(1);
+
+      // Operations + reserve were the only booked funds: books back to zero
+      // (the still-unvested creator share was clawed back from escrow)
+      const vaultAcct = await getAccountData(provider, treasuryVaultPda);
+      expect(new BN(vaultAcct!.subarray(8, 16), "le").isZero()).to.be.true;
+
+      // Attribution to agent 1 reversed
+      const budgetAcct = await getAccountData(provider, ownerBudgetPda);
+      expect(new BN(budgetAcct!.subarray(60, 68), "le").isZero()).to.be.true;
+
+      // The refunded call no longer counts toward the payer's volume discount:
+      // monthly_scan_count (40), lifetime_scans (50) and total_spent_lamports (55) back to 0
+      const tracker = await getAccountData(provider, volumeTrackerPda);
+      expect(tracker!.readUInt16LE(40)).to.equal(0);
+      expect(tracker!.readUInt32LE(50)).to.equal(0);
+      expect(new BN(tracker!.subarray(55, 63), "le").isZero()).to.be.true;
+      expect(tracker!.readUInt8(54)).to.equal(0);
+    });
+
+    it("refund_service_payment: second refund of the same payment is rejected", async () => {
+      try {
+        await sendTx(provider, refundIx(keeperAuthority.publicKey), [keeperAuthority]);
+        expect.fail("Should have thrown PaymentAlreadyRefunded");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("close_payment_record: rejected while the refund window is open", async () => {
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: paymentRecordPda, isSigner: false, isWritable: true },
+          { pubkey: superAuthority.publicKey, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("close_payment_record"),
+      });
+      try {
+        await sendTx(provider, ix, []);
+        expect.fail("Should have thrown RefundWindowOpen");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("refund_service_payment: a payment with no referrer or other options is refundable", async () => {
+      const record = newPaymentRecord(superAuthority.publicKey);
+      await sendTx(provider, paymentIx(await servicePrice(), record.nonce, record.pda), []);
+      expect((await getAccountData(provider, record.pda))!.readUInt8(90)).to.equal(0);
+
+      await sendTx(provider, refundIx(keeperAuthority.publicKey, record.pda), [keeperAuthority]);
+
+      expect((await getAccountData(provider, record.pda))!.readUInt8(90)).to.equal(1); // refunded
+      const vaultAcct = await getAccountData(provider, treasuryVaultPda);
+      expect(new BN(vaultAcct!.subarray(8, 16), "le").isZero()).to.be.true;
+    });
+
+    it("donate: donation right after a service payment is flagged as correlated (A0-24)", async () => {
+      // Second receipt overall: nonce = 1
+      const [donationReceiptPda] = findPda(
+        [Buffer.from("donation_receipt"), encodeU64LE(new BN(1))],
+        TREASURY_PROGRAM_ID
+      );
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: donationReceiptPda, isSigner: false, isWritable: true },
+          { pubkey: volumeTrackerPda, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("donate"),
+          encodeU64LE(new BN(100_000_000)),
+          encodeBytes32(randomHash()),
+          encodeU8(0),
+        ]),
+      });
+      await sendTx(provider, ix, []);
+
+      const receiptAcct = await getAccountData(provider, donationReceiptPda);
+      expect(receiptAcct!.readUInt8(65)).to.equal(1); // receipt_status = Flagged
+      expect(receiptAcct!.readUInt8(66)).to.equal(0); // counts_as_donation = false
+      expect(receiptAcct!.readUInt32LE(67)).to.equal(1); // correlated_payments
+      expect(receiptAcct!.readUInt8(71)).to.equal(1); // correlated_flag
+    });
+
+    it("correlate_donation: a donation followed by a plain payment is flagged after the fact (A0-24)", async () => {
+      // A fresh wallet with no payment history, so the donation is not flagged when made
+      const donor = Keypair.generate();
+      const salt = randomHash();
+      const sig = await provider.connection.requestAirdrop(donor.publicKey, LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig, "confirmed");
+      const [donorTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), donor.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+
+      // DonationVault: receipt_count at 37 is the next receipt nonce
+      const donAcct = await getAccountData(provider, donationVaultPda);
+      const receiptNonce = new BN(donAcct!.subarray(37, 45), "le");
+      const [donationReceiptPda] = findPda(
+        [Buffer.from("donation_receipt"), encodeU64LE(receiptNonce)],
+        TREASURY_PROGRAM_ID
+      );
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: donor.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+            { pubkey: donationReceiptPda, isSigner: false, isWritable: true },
+            { pubkey: donorTrackerPda, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("donate"),
+            encodeU64LE(new BN(50_000_000)),
+            encodeBytes32(salt),
+            encodeU8(0),
+          ]),
+        }),
+        [donor]
+      );
+      expect((await getAccountData(provider, donationReceiptPda))!.readUInt8(66)).to.equal(1); // counts_as_donation
+
+      // Then a payment with no referrer or other options; it still gets a PaymentRecord
+      const record = newPaymentRecord(donor.publicKey);
+      await sendTx(
+        provider,
+        [
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: donor.publicKey, isSigner: true, isWritable: true },
+              { pubkey: donorTrackerPda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: ixDiscriminator("initialize_volume_tracker"),
+          }),
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: donor.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+              { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+              { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+              { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+              { pubkey: donorTrackerPda, isSigner: false, isWritable: true },
+              { pubkey: record.pda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+              { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+              { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+              { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referral_link: none
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("process_service_payment"),
+              serviceIdBuf,
+              encodeU64LE(await servicePrice()),
+              encodeU64LE(record.nonce),
+            ]),
+          }),
+        ],
+        [donor]
+      );
+
+      const correlateIx = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: donationReceiptPda, isSigner: false, isWritable: true },
+          { pubkey: record.pda, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("correlate_donation"), encodeBytes32(salt)]),
+      });
+      await sendTx(provider, correlateIx, []);
+
+      const receiptAcct = await getAccountData(provider, donationReceiptPda);
+      expect(receiptAcct!.readUInt8(65)).to.equal(1); // receipt_status = Flagged
+      expect(receiptAcct!.readUInt8(66)).to.equal(0); // counts_as_donation = false
+      expect(receiptAcct!.readUInt32LE(67)).to.equal(1); // correlated_payments
+      // PaymentRecord: donation_correlated after refunded (90) and refunded_at (91)
+      expect((await getAccountData(provider, record.pda))!.readUInt8(99)).to.equal(1);
+
+      try {
+        await sendTx(provider, correlateIx, []);
+        expect.fail("Should have thrown PaymentAlreadyCorrelated");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PaymentAlreadyCorrelated);
+      }
+
+      // Refund the payment so the treasury books are back at zero for later suites
+      await sendTx(
+        provider,
+        refundIx(keeperAuthority.publicKey, record.pda, donor.publicKey, donorTrackerPda),
+        [keeperAuthority]
+      );
+      const vaultAcct = await getAccountData(provider, treasuryVaultPda);
+      expect(new BN(vaultAcct!.subarray(8, 16), "le").isZero()).to.be.true;
+    });
+  });
+
+  describe("noumen_treasury SPL payment rail", () => {
+    const mint = Keypair.generate();
+    const payerTokenAccount = Keypair.generate();
+    const creatorTokenAccount = Keypair.generate();
+    const destinationTokenAccount = Keypair.generate();
+    const serviceIdBuf = encodeU16LE(1);
+    // 150 tokens (6 decimals) per SOL of list price
+    const rate = new BN(150_000_000);
+    let splVaultPda: PublicKey;
+    let vaultTokenAccountPda: PublicKey;
+    let serviceEntryPda: PublicKey;
+    let volumeTrackerPda: PublicKey;
+
+    before(async () => {
+      await createMint(provider, mint, superAuthority.publicKey, 6);
+      await createTokenAccount(provider, payerTokenAccount, mint.publicKey, superAuthority.publicKey);
+      await createTokenAccount(provider, creatorTokenAccount, mint.publicKey, creatorWallet.publicKey);
+      await createTokenAccount(provider, destinationTokenAccount, mint.publicKey, aeonAuthority.publicKey);
+      await mintTo(provider, mint.publicKey, payerTokenAccount.publicKey, superAuthority, new BN(1_000_000_000));
+
+      [splVaultPda] = findPda([Buffer.from("spl_vault"), mint.publicKey.toBuffer()], TREASURY_PROGRAM_ID);
+      [vaultTokenAccountPda] = findPda([Buffer.from("spl_vault_tokens"), mint.publicKey.toBuffer()], TREASURY_PROGRAM_ID);
+      [serviceEntryPda] = findPda([Buffer.from("service"), serviceIdBuf], SERVICE_PROGRAM_ID);
+      [volumeTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), superAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    async function tokenBalance(account: PublicKey): Promise<BN> {
+      const acct = await getAccountData(provider, account);
+      // SPL token account: mint(32) owner(32) amount(8)
+      return new BN(acct!.subarray(64, 72), "le");
+    }
+
+    function allowlistHash(allowed: boolean, tokenUnitsPerSol: BN): Buffer {
+      return policyChangeHash(
+        "spl_mint_allowlist",
+        Buffer.concat([mint.publicKey.toBuffer(), encodeBool(allowed), encodeU64LE(tokenUnitsPerSol)])
+      );
+    }
+
+    function allowlistIx(proposal: GovernanceProposal, allowed: boolean, tokenUnitsPerSol: BN): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+          { pubkey: splVaultPda, isSigner: false, isWritable: true },
+          { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+          { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+          { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("set_spl_mint_allowlist"),
+          encodeBool(allowed),
+          encodeU64LE(tokenUnitsPerSol),
+        ]),
+      });
+    }
+
+    function rateIx(authority: Keypair, tokenUnitsPerSol: BN): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: splVaultPda, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("update_spl_token_rate"), encodeU64LE(tokenUnitsPerSol)]),
+      });
+    }
+
+    function withdrawHash(amount: BN, fromReserve: boolean): Buffer {
+      return policyChangeHash(
+        "spl_withdraw",
+        Buffer.concat([
+          mint.publicKey.toBuffer(),
+          destinationTokenAccount.publicKey.toBuffer(),
+          encodeU64LE(amount),
+          encodeBool(fromReserve),
+        ])
+      );
+    }
+
+    function withdrawIx(proposal: GovernanceProposal, amount: BN, fromReserve: boolean): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+          { pubkey: splVaultPda, isSigner: false, isWritable: true },
+          { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+          { pubkey: destinationTokenAccount.publicKey, isSigner: false, isWritable: true },
+          { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+          { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("withdraw_spl_treasury"), encodeU64LE(amount), encodeBool(fromReserve)]),
+      });
+    }
+
+    it("set_spl_mint_allowlist: proposal committing to another rate is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 1, allowlistHash(true, rate.addn(1)));
+      try {
+        await sendTx(provider, allowlistIx(proposal, true, rate), [aeonAuthority]);
+        expect.fail("Should have thrown PolicyChangeHashMismatch");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+      }
+    });
+
+    it("set_spl_mint_allowlist: Layer 2 proposal is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, allowlistHash(true, rate));
+      try {
+        await sendTx(provider, allowlistIx(proposal, true, rate), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidPolicyLayer");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+      }
+    });
+
+    it("set_spl_mint_allowlist: executed Layer 1 proposal allowlists the mint; replay is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 1, allowlistHash(true, rate));
+      await sendTx(provider, allowlistIx(proposal, true, rate), [aeonAuthority]);
+
+      const vault = await getAccountData(provider, splVaultPda);
+      expect(vault!.subarray(0, 8).equals(accountDiscriminator("SplTreasuryVault"))).to.be.true;
+      // is_allowed at 72, token_units_per_sol at 73, governed_token_units_per_sol at 143
+      expect(vault!.readUInt8(72)).to.equal(1);
+      expect(new BN(vault!.subarray(73, 81), "le").eq(rate)).to.be.true;
+      expect(new BN(vault!.subarray(143, 151), "le").eq(rate)).to.be.true;
+
+      // Treasury token account created for the mint, owned by the SplTreasuryVault PDA
+      const tokenAcct = await getAccountData(provider, vaultTokenAccountPda);
+      expect(new PublicKey(tokenAcct!.subarray(0, 32)).equals(mint.publicKey)).to.be.true;
+      expect(new PublicKey(tokenAcct!.subarray(32, 64)).equals(splVaultPda)).to.be.true;
+
+      try {
+        await sendTx(provider, allowlistIx(proposal, true, rate), [aeonAuthority]);
+        expect.fail("Should have rejected a second use of the proposal");
+      } catch (err: any) {
+        expectProposalReplayRejected(err);
+      }
+    });
+
+    function creatorWithdrawIx(signer: PublicKey, amount: BN): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: signer, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+          { pubkey: splVaultPda, isSigner: false, isWritable: true },
+          { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+          { pubkey: creatorTokenAccount.publicKey, isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("withdraw_spl_creator_split"), encodeU64LE(amount)]),
+      });
+    }
+
+    it("process_service_payment_spl: pays the converted price and escrows the creator share", async () => {
+      const entry = await getAccountData(provider, serviceEntryPda);
+      // ServiceEntry: price at 14, min_price at 30; the payer is still below the first discount tier
+      const priceLamports = BN.max(new BN(entry!.subarray(14, 22), "le"), new BN(entry!.subarray(30, 38), "le"));
+      const LAMPORTS = new BN(LAMPORTS_PER_SOL);
+      const priceTokens = priceLamports.mul(rate).add(LAMPORTS.subn(1)).div(LAMPORTS);
+
+      const payerBefore = await tokenBalance(payerTokenAccount.publicKey);
+      const vaultBefore = await tokenBalance(vaultTokenAccountPda);
+      const creatorBefore = await tokenBalance(creatorTokenAccount.publicKey);
+
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: false },
+            { pubkey: mint.publicKey, isSigner: false, isWritable: false },
+            { pubkey: splVaultPda, isSigner: false, isWritable: true },
+            { pubkey: vaultTokenAccountPda, isSigner: false, isWritable: true },
+            { pubkey: payerTokenAccount.publicKey, isSigner: false, isWritable: true },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+            { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("process_service_payment_spl"), serviceIdBuf, encodeU64LE(priceTokens)]),
+        }),
+        []
+      );
+
+      // The whole payment reaches the treasury token account; nothing goes to the creator yet
+      const paid = payerBefore.sub(await tokenBalance(payerTokenAccount.publicKey));
+      expect(paid.eq(priceTokens)).to.be.true;
+      expect((await tokenBalance(vaultTokenAccountPda)).sub(vaultBefore).eq(priceTokens)).to.be.true;
+      expect((await tokenBalance(creatorTokenAccount.publicKey)).eq(creatorBefore)).to.be.true;
+
+      // Books: operations (105) is 40%; total_balance (81) plus the creator
+      // escrow, creator_accumulated (167) + creator_unvested (175), is the payment
+      const vault = await getAccountData(provider, splVaultPda);
+      const total = new BN(vault!.subarray(81, 89), "le");
+      const escrow = new BN(vault!.subarray(167, 175), "le").add(new BN(vault!.subarray(175, 183), "le"));
+      expect(new BN(vault!.subarray(105, 113), "le").eq(priceTokens.muln(4000).divn(10_000))).to.be.true;
+      expect(escrow.gtn(0)).to.be.true;
+      expect(total.add(escrow).eq(priceTokens)).to.be.true;
+      // total_creator_accrued (199) has it all; total_creator_paid (121) nothing
+      expect(new BN(vault!.subarray(199, 207), "le").eq(escrow)).to.be.true;
+      expect(new BN(vault!.subarray(121, 129), "le").isZero()).to.be.true;
+    });
+
+    it("withdraw_spl_creator_split: non-creator signer is rejected", async () => {
+      try {
+        await sendTx(provider, creatorWithdrawIx(aeonAuthority.publicKey, new BN(1)), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidCreatorWallet");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidCreatorWallet);
+      }
+    });
+
+    it("withdraw_spl_creator_split: unvested share cannot be withdrawn", async () => {
+      const vault = await getAccountData(provider, splVaultPda);
+      const escrow = new BN(vault!.subarray(167, 175), "le").add(new BN(vault!.subarray(175, 183), "le"));
+      try {
+        await sendTx(provider, creatorWithdrawIx(creatorWallet.publicKey, escrow), [creatorWallet]);
+        expect.fail("Should have thrown InsufficientCreatorBalance");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InsufficientCreatorBalance);
+      }
+    });
+
+    it("withdraw_spl_creator_split: vested share is paid to the creator's token account", async () => {
+      // A few seconds of the 7-day stream vest well over one base unit
+      await new Promise((resolve) => setTimeout(resolve, 3_000));
+      const amount = new BN(1);
+      const before = await getAccountData(provider, splVaultPda);
+      const totalBefore = new BN(before!.subarray(81, 89), "le");
+      const vaultTokensBefore = await tokenBalance(vaultTokenAccountPda);
+      const creatorBefore = await tokenBalance(creatorTokenAccount.publicKey);
+
+      await sendTx(provider, creatorWithdrawIx(creatorWallet.publicKey, amount), [creatorWallet]);
+
+      expect((await tokenBalance(creatorTokenAccount.publicKey)).sub(creatorBefore).eq(amount)).to.be.true;
+      expect(vaultTokensBefore.sub(await tokenBalance(vaultTokenAccountPda)).eq(amount)).to.be.true;
+      const after = await getAccountData(provider, splVaultPda);
+      // The treasury's own books are untouched; total_creator_paid (121) records it
+      expect(new BN(after!.subarray(81, 89), "le").eq(totalBefore)).to.be.true;
+      expect(new BN(after!.subarray(121, 129), "le").eq(amount)).to.be.true;
+    });
+
+    it("update_spl_token_rate: non-keeper signer is rejected", async () => {
+      try {
+        await sendTx(provider, rateIx(aeonAuthority, rate), [aeonAuthority]);
+        expect.fail("Should have thrown UnauthorizedKeeper");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.UnauthorizedKeeper);
+      }
+    });
+
+    it("update_spl_token_rate: rate outside ±20% of the governed rate is rejected", async () => {
+      for (const outOfBand of [rate.muln(79).divn(100), rate.muln(121).divn(100)]) {
+        try {
+          await sendTx(provider, rateIx(keeperAuthority, outOfBand), [keeperAuthority]);
+          expect.fail("Should have thrown TokenRateOutOfBand");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.TokenRateOutOfBand);
+        }
+      }
+    });
+
+    it("update_spl_token_rate: in-band update within an hour of the last one is rejected", async () => {
+      try {
+        await sendTx(provider, rateIx(keeperAuthority, rate.muln(110).divn(100)), [keeperAuthority]);
+        expect.fail("Should have thrown SplRateUpdateTooSoon");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.SplRateUpdateTooSoon);
+      }
+    });
+
+    it("withdraw_spl_treasury: reserve withdrawal that breaches A0-3 is rejected", async () => {
+      const vault = await getAccountData(provider, splVaultPda);
+      const reserved = new BN(vault!.subarray(89, 97), "le");
+      const proposal = await executedProposal(provider, aeonAuthority, 1, withdrawHash(reserved, true));
+      try {
+        await sendTx(provider, withdrawIx(proposal, reserved, true), [aeonAuthority]);
+        expect.fail("Should have thrown ReserveRatioBreach");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.ReserveRatioBreach);
+      }
+    });
+
+    it("withdraw_spl_treasury: reserve withdrawal on a Layer 2 proposal is rejected", async () => {
+      const amount = new BN(1);
+      const proposal = await executedProposal(provider, aeonAuthority, 2, withdrawHash(amount, true));
+      try {
+        await sendTx(provider, withdrawIx(proposal, amount, true), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidPolicyLayer");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+      }
+    });
+
+    it("withdraw_spl_treasury: executed Layer 2 proposal pays operations tokens out", async () => {
+      const before = await getAccountData(provider, splVaultPda);
+      const operations = new BN(before!.subarray(105, 113), "le");
+      const totalBefore = new BN(before!.subarray(81, 89), "le");
+      const proposal = await executedProposal(provider, aeonAuthority, 2, withdrawHash(operations, false));
+
+      const destBefore = await tokenBalance(destinationTokenAccount.publicKey);
+      const vaultTokensBefore = await tokenBalance(vaultTokenAccountPda);
+      await sendTx(provider, withdrawIx(proposal, operations, false), [aeonAuthority]);
+      expect((await tokenBalance(destinationTokenAccount.publicKey)).sub(destBefore).eq(operations)).to.be.true;
+
+      const after = await getAccountData(provider, splVaultPda);
+      expect(new BN(after!.subarray(105, 113), "le").isZero()).to.be.true;
+      expect(new BN(after!.subarray(81, 89), "le").eq(totalBefore.sub(operations))).to.be.true;
+      // total_withdrawn at 159
+      expect(new BN(after!.subarray(159, 167), "le").eq(operations)).to.be.true;
+      // The creator escrow stays in the token account
+      expect((await tokenBalance(vaultTokenAccountPda)).eq(vaultTokensBefore.sub(operations))).to.be.true;
+    });
+  });
+
+  describe("noumen_treasury agent revenue share", () => {
+    function revenueShareIx(proposal: GovernanceProposal, shareBps: number): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+          { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("set_agent_revenue_share"), encodeU16LE(shareBps)]),
+      });
+    }
+
+    function revenueShareHash(shareBps: number): Buffer {
+      return policyChangeHash("agent_revenue_share", encodeU16LE(shareBps));
+    }
+
+    it("set_agent_revenue_share: rejected without an executed policy proposal", async () => {
+      const proposalIdBuf = encodeU32LE(9_994);
+      const [proposalPda] = findPda([Buffer.from("proposal"), proposalIdBuf], CORE_PROGRAM_ID);
+      const [appliedPda] = findPda([Buffer.from("applied_proposal"), proposalIdBuf], TREASURY_PROGRAM_ID);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          { pubkey: proposalPda, isSigner: false, isWritable: false },
+          { pubkey: appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("set_agent_revenue_share"), encodeU16LE(2_000)]),
+      });
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have rejected the revenue share without a proposal");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.AccountNotInitialized);
+      }
+    });
+
+    it("set_agent_revenue_share: share above MAX_AGENT_REVENUE_SHARE_BPS is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, revenueShareHash(5_001));
+      try {
+        await sendTx(provider, revenueShareIx(proposal, 5_001), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidAgentRevenueShare");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidAgentRevenueShare);
+      }
+    });
+
+    it("set_agent_revenue_share: proposal committing to another share is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, revenueShareHash(2_500));
+      try {
+        await sendTx(provider, revenueShareIx(proposal, 2_000), [aeonAuthority]);
+        expect.fail("Should have thrown PolicyChangeHashMismatch");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+      }
+    });
+
+    it("set_agent_revenue_share: Layer 1 proposal is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 1, revenueShareHash(2_000));
+      try {
+        await sendTx(provider, revenueShareIx(proposal, 2_000), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidPolicyLayer");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+      }
+    });
+
+    it("set_agent_revenue_share: executed Layer 2 proposal sets the share; replay is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, revenueShareHash(2_000));
+      await sendTx(provider, revenueShareIx(proposal, 2_000), [aeonAuthority]);
+
+      // agent_revenue_share_bps follows referral_share_bps(178) and referral_lifetime_cap_lamports(180)
+      const config = await getAccountData(provider, treasuryConfigPda);
+      expect(config!.readUInt16LE(188)).to.equal(2_000);
+
+      try {
+        await sendTx(provider, revenueShareIx(proposal, 2_000), [aeonAuthority]);
+        expect.fail("Should have rejected a second use of the proposal");
+      } catch (err: any) {
+        expectProposalReplayRejected(err);
+      }
+    });
+  });
+
+  describe("noumen_treasury referrals", () => {
+    let referrerPda: PublicKey;
+
+    before(() => {
+      [referrerPda] = findPda(
+        [Buffer.from("referrer"), hermesAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    function referralPolicyHash(shareBps: number, lifetimeCap: BN): Buffer {
+      return policyChangeHash("referral_policy", Buffer.concat([encodeU16LE(shareBps), encodeU64LE(lifetimeCap)]));
+    }
+
+    function referralPolicyIx(proposal: GovernanceProposal, shareBps: number, lifetimeCap: BN): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+          { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("set_referral_policy"), encodeU16LE(shareBps), encodeU64LE(lifetimeCap)]),
+      });
+    }
+
+    function setReferrerActiveIx(signer: Keypair, isActive: boolean): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: signer.publicKey, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: referrerPda, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("set_referrer_active"), encodeBool(isActive)]),
+      });
+    }
+
+    it("register_referrer: creates an unapproved Referrer for the signing wallet", async () => {
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: hermesAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: referrerPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: ixDiscriminator("register_referrer"),
+      });
+      await sendTx(provider, ix, [hermesAuthority]);
+
+      const acct = await getAccountData(provider, referrerPda);
+      expect(acct!.subarray(0, 8).equals(accountDiscriminator("Referrer"))).to.be.true;
+      expect(new PublicKey(acct!.subarray(8, 40)).equals(hermesAuthority.publicKey)).to.be.true;
+      expect(acct!.readUInt8(40)).to.equal(0); // is_active: awaits AEON approval
+    });
+
+    it("set_referrer_active: non-AEON signer is rejected", async () => {
+      try {
+        await sendTx(provider, setReferrerActiveIx(keeperAuthority, true), [keeperAuthority]);
+        expect.fail("Should have thrown UnauthorizedAeon");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.UnauthorizedAeon);
+      }
+      expect((await getAccountData(provider, referrerPda))!.readUInt8(40)).to.equal(0);
+    });
+
+    it("set_referrer_active: AEON approves the referrer", async () => {
+      await sendTx(provider, setReferrerActiveIx(aeonAuthority, true), [aeonAuthority]);
+      expect((await getAccountData(provider, referrerPda))!.readUInt8(40)).to.equal(1);
+    });
+
+    it("claim_referral_rewards: rejected with nothing accrued", async () => {
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: hermesAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: referrerPda, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("claim_referral_rewards"),
+      });
+      try {
+        await sendTx(provider, ix, [hermesAuthority]);
+        expect.fail("Should have thrown ZeroAmount");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("set_referral_policy: rejected without an executed policy proposal", async () => {
+      const proposalIdBuf = encodeU32LE(9_995);
+      const [proposalPda] = findPda([Buffer.from("proposal"), proposalIdBuf], CORE_PROGRAM_ID);
+      const [appliedPda] = findPda([Buffer.from("applied_proposal"), proposalIdBuf], TREASURY_PROGRAM_ID);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+          { pubkey: proposalPda, isSigner: false, isWritable: false },
+          { pubkey: appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("set_referral_policy"),
+          encodeU16LE(500),
+          encodeU64LE(new BN(LAMPORTS_PER_SOL)),
+        ]),
+      });
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have rejected the referral policy without a proposal");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.AccountNotInitialized);
+      }
+    });
+
+    it("set_referral_policy: share above MAX_REFERRAL_SHARE_BPS is rejected", async () => {
+      const cap = new BN(LAMPORTS_PER_SOL);
+      const proposal = await executedProposal(provider, aeonAuthority, 2, referralPolicyHash(1_001, cap));
+      try {
+        await sendTx(provider, referralPolicyIx(proposal, 1_001, cap), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidReferralPolicy");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidReferralPolicy);
+      }
+    });
+
+    it("set_referral_policy: proposal committing to another policy is rejected", async () => {
+      const cap = new BN(LAMPORTS_PER_SOL);
+      const proposal = await executedProposal(provider, aeonAuthority, 2, referralPolicyHash(500, cap.addn(1)));
+      try {
+        await sendTx(provider, referralPolicyIx(proposal, 500, cap), [aeonAuthority]);
+        expect.fail("Should have thrown PolicyChangeHashMismatch");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+      }
+    });
+
+    it("set_referral_policy: Layer 1 proposal is rejected", async () => {
+      const cap = new BN(LAMPORTS_PER_SOL);
+      const proposal = await executedProposal(provider, aeonAuthority, 1, referralPolicyHash(500, cap));
+      try {
+        await sendTx(provider, referralPolicyIx(proposal, 500, cap), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidPolicyLayer");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+      }
+    });
+
+    it("set_referral_policy: executed Layer 2 proposal enables referrals; replay is rejected", async () => {
+      const cap = new BN(LAMPORTS_PER_SOL);
+      const proposal = await executedProposal(provider, aeonAuthority, 2, referralPolicyHash(500, cap));
+      await sendTx(provider, referralPolicyIx(proposal, 500, cap), [aeonAuthority]);
+
+      const config = await getAccountData(provider, treasuryConfigPda);
+      expect(config!.readUInt16LE(178)).to.equal(500); // referral_share_bps
+      expect(config!.readBigUInt64LE(180).toString()).to.equal(cap.toString()); // referral_lifetime_cap_lamports
+
+      try {
+        await sendTx(provider, referralPolicyIx(proposal, 500, cap), [aeonAuthority]);
+        expect.fail("Should have rejected a second use of the proposal");
+      } catch (err: any) {
+        expectProposalReplayRejected(err);
+      }
+    });
+  });
+
+  describe("noumen_treasury rent reclaim", () => {
+    const MIN_RETENTION = new BN(30 * 86_400); // MIN_RECEIPT_RETENTION_SECONDS
+
+    function retentionHash(retention: BN): Buffer {
+      return policyChangeHash("receipt_retention", encodeI64LE(retention));
+    }
+
+    function retentionIx(proposal: GovernanceProposal, retention: BN): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+          { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([ixDiscriminator("set_receipt_retention"), encodeI64LE(retention)]),
+      });
+    }
+
+    function archiveIx(nonce: number): TransactionInstruction {
+      const [receiptPda] = findPda(
+        [Buffer.from("donation_receipt"), encodeU64LE(new BN(nonce))],
+        TREASURY_PROGRAM_ID
+      );
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: receiptPda, isSigner: false, isWritable: true },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("archive_donation_receipt"),
+      });
+    }
+
+    it("set_receipt_retention: rejected without an executed policy proposal", async () => {
+      const proposalIdBuf = encodeU32LE(9_993);
+      const [proposalPda] = findPda([Buffer.from("proposal"), proposalIdBuf], CORE_PROGRAM_ID);
+      const [appliedPda] = findPda([Buffer.from("applied_proposal"), proposalIdBuf], TREASURY_PROGRAM_ID);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: proposalPda, isSigner: false, isWritable: false },
+          { pubkey: appliedPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("set_receipt_retention"),
+          encodeI64LE(new BN(90 * 86_400)),
+        ]),
+      });
+      try {
+        await sendTx(provider, ix, [aeonAuthority]);
+        expect.fail("Should have rejected the retention change without a proposal");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("archive_donation_receipt: rejected while archival is disabled", async () => {
+      const [receiptPda] = findPda(
+        [Buffer.from("donation_receipt"), encodeU64LE(new BN(0))],
+        TREASURY_PROGRAM_ID
+      );
+      try {
+        await sendTx(provider, archiveIx(0), []);
+        expect.fail("Should have thrown ReceiptArchivalDisabled");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+
+      // Receipt 0 still open; archive empty (count at 65, root at 73)
+      expect(await getAccountData(provider, receiptPda)).to.not.be.null;
+      const donAcct = await getAccountData(provider, donationVaultPda);
+      expect(new BN(donAcct!.subarray(65, 73), "le").isZero()).to.be.true;
+      expect(donAcct!.subarray(73, 105).equals(Buffer.alloc(32))).to.be.false;
+    });
+
+    it("set_receipt_retention: below the 30-day minimum is rejected", async () => {
+      const retention = MIN_RETENTION.subn(1);
+      const proposal = await executedProposal(provider, aeonAuthority, 2, retentionHash(retention));
+      try {
+        await sendTx(provider, retentionIx(proposal, retention), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidReceiptRetention");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidReceiptRetention);
+      }
+    });
+
+    it("set_receipt_retention: proposal for a different retention is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, retentionHash(MIN_RETENTION.muln(2)));
+      try {
+        await sendTx(provider, retentionIx(proposal, MIN_RETENTION), [aeonAuthority]);
+        expect.fail("Should have thrown PolicyChangeHashMismatch");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+      }
+    });
+
+    it("set_receipt_retention: Layer 1 proposal is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 1, retentionHash(MIN_RETENTION));
+      try {
+        await sendTx(provider, retentionIx(proposal, MIN_RETENTION), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidPolicyLayer");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+      }
+    });
+
+    it("set_receipt_retention: executed Layer 2 proposal enables archival; replay is rejected", async () => {
+      const proposal = await executedProposal(provider, aeonAuthority, 2, retentionHash(MIN_RETENTION));
+      await sendTx(provider, retentionIx(proposal, MIN_RETENTION), [aeonAuthority]);
+
+      // DonationVault: receipt_retention_seconds at 57
+      const donAcct = await getAccountData(provider, donationVaultPda);
+      expect(donAcct!.readBigInt64LE(57).toString()).to.equal(MIN_RETENTION.toString());
+
+      try {
+        await sendTx(provider, retentionIx(proposal, MIN_RETENTION), [aeonAuthority]);
+        expect.fail("Should have rejected a second use of the proposal");
+      } catch (err: any) {
+        expectProposalReplayRejected(err);
+      }
+    });
+
+    it("archive_donation_receipt: rejected before the receipt is past retention", async () => {
+      try {
+        await sendTx(provider, archiveIx(0), []);
+        expect.fail("Should have thrown ReceiptRetentionNotElapsed");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.ReceiptRetentionNotElapsed);
+      }
+    });
+
+    it("close_agent_budget: rejected while the agent is not Killed", async () => {
+      const agentIdBuf = encodeU16LE(1);
+      const [budgetPda] = findPda([Buffer.from("budget"), agentIdBuf], TREASURY_PROGRAM_ID);
+      const [agentManifestPda] = findPda([Buffer.from("agent"), agentIdBuf], CORE_PROGRAM_ID);
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+          { pubkey: budgetPda, isSigner: false, isWritable: true },
+          { pubkey: aeonAuthority.publicKey, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("close_agent_budget"), agentIdBuf]),
+      });
+      try {
+        await sendTx(provider, ix, []);
+        expect.fail("Should have thrown AgentNotKilled");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+      expect(await getAccountData(provider, budgetPda)).to.not.be.null;
+    });
+
+    it("close_agent_budget: closes a killed agent's budget and releases its outstanding allocation", async () => {
+      const agentIdBuf = encodeU16LE(3);
+      const [budgetPda] = findPda([Buffer.from("budget"), agentIdBuf], TREASURY_PROGRAM_ID);
+      const [agentManifestPda] = findPda([Buffer.from("agent"), agentIdBuf], CORE_PROGRAM_ID);
+
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: CORE_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonConfigPda, isSigner: false, isWritable: true },
+            { pubkey: agentManifestPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("create_agent"),
+            agentIdBuf,
+            encodePubkey(aeonAuthority.publicKey),
+            encodeU8(0),                       // Collector
+            encodeU8(1),                       // Limited
+            encodeU64LE(new BN(1_000_000_000)),
+            encodeU64LE(new BN(100_000_000)),
+            encodeI64LE(new BN(Math.floor(Date.now() / 1000) + 86400 * 365)),
+            encodeBytes32(randomHash()),
+          ]),
+        }),
+        [aeonAuthority]
+      );
+
+      // TreasuryVault: free_balance at 24, total_budget_outstanding at 89.
+      // Allocate 10% of free balance, within the aggregate cap.
+      let vault = await getAccountData(provider, treasuryVaultPda);
+      const free = new BN(vault!.subarray(24, 32), "le");
+      const outstanding = new BN(vault!.subarray(89, 97), "le");
+      const allocated = BN.max(BN.min(free.divn(10), free.sub(outstanding)), new BN(0));
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+            { pubkey: budgetPda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("allocate_agent_budget"),
+            agentIdBuf,
+            encodeU64LE(allocated),
+            encodeU64LE(allocated),
+          ]),
+        }),
+        [aeonAuthority]
+      );
+      vault = await getAccountData(provider, treasuryVaultPda);
+      const outstandingBefore = new BN(vault!.subarray(89, 97), "le");
+
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: CORE_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonConfigPda, isSigner: false, isWritable: true },
+            { pubkey: agentManifestPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("kill_agent"), agentIdBuf, encodeBytes32(randomHash())]),
+        }),
+        [aeonAuthority]
+      );
+
+      const budgetRent = await provider.connection.getBalance(budgetPda);
+      const aeonBefore = await provider.connection.getBalance(aeonAuthority.publicKey);
+      // Permissionless crank: the provider wallet pays the fee
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+            { pubkey: budgetPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: false, isWritable: true },
+          ],
+          data: Buffer.concat([ixDiscriminator("close_agent_budget"), agentIdBuf]),
+        }),
+        []
+      );
+
+      expect(await getAccountData(provider, budgetPda)).to.be.null;
+      expect(await provider.connection.getBalance(aeonAuthority.publicKey)).to.equal(aeonBefore + budgetRent);
+      vault = await getAccountData(provider, treasuryVaultPda);
+      const outstandingAfter = new BN(vault!.subarray(89, 97), "le");
+      expect(outstandingBefore.sub(outstandingAfter).eq(allocated)).to.be.true;
+    });
+
+    it("rejects allocate_agent_budget for an agent that is not Active", async () => {
+      // Agent 3 was killed and its budget closed by the previous test
+      const agentIdBuf = encodeU16LE(3);
+      const [budgetPda] = findPda([Buffer.from("budget"), agentIdBuf], TREASURY_PROGRAM_ID);
+      const [agentManifestPda] = findPda([Buffer.from("agent"), agentIdBuf], CORE_PROGRAM_ID);
+      try {
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+              { pubkey: agentManifestPda, isSigner: false, isWritable: false },
+              { pubkey: budgetPda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("allocate_agent_budget"),
+              agentIdBuf,
+              encodeU64LE(new BN(1)),
+              encodeU64LE(new BN(1)),
+            ]),
+          }),
+          [aeonAuthority]
+        );
+        expect.fail("Should have thrown AgentNotActive");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.AgentNotActive);
+      }
+      expect(await getAccountData(provider, budgetPda)).to.be.null;
+    });
+  });
+
+  describe("noumen_treasury prepaid balances", () => {
+    let prepaidPda: PublicKey;
+
+    before(() => {
+      [prepaidPda] = findPda(
+        [Buffer.from("prepaid"), superAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    function userIx(name: string, args: Buffer, withSystem: boolean): TransactionInstruction {
+      const keys = [
+        { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+        { pubkey: prepaidPda, isSigner: false, isWritable: true },
+      ];
+      if (withSystem) {
+        keys.push({ pubkey: SystemProgram.programId, isSigner: false, isWritable: false });
+      }
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys,
+        data: Buffer.concat([ixDiscriminator(name), args]),
+      });
+    }
+
+    it("open_prepaid_balance: zero daily ceiling is rejected", async () => {
+      try {
+        await sendTx(provider, userIx("open_prepaid_balance", encodeU64LE(new BN(0)), true), []);
+        expect.fail("Should have thrown ZeroAmount");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("open_prepaid_balance + deposit_prepaid: balance tracks the deposit", async () => {
+      const ceiling = new BN(LAMPORTS_PER_SOL);
+      await sendTx(provider, userIx("open_prepaid_balance", encodeU64LE(ceiling), true), []);
+
+      const deposit = new BN(LAMPORTS_PER_SOL / 10);
+      const before = await provider.connection.getBalance(prepaidPda);
+      await sendTx(provider, userIx("deposit_prepaid", encodeU64LE(deposit), true), []);
+      const after = await provider.connection.getBalance(prepaidPda);
+      expect(after - before).to.equal(deposit.toNumber());
+
+      const acct = await getAccountData(provider, prepaidPda);
+      expect(acct!.subarray(0, 8).equals(accountDiscriminator("PrepaidBalance"))).to.be.true;
+      // balance_lamports at 8 + 32 = 40, daily_ceiling_lamports at 48
+      expect(new BN(acct!.subarray(40, 48), "le").eq(deposit)).to.be.true;
+      expect(new BN(acct!.subarray(48, 56), "le").eq(ceiling)).to.be.true;
+    });
+
+    it("withdraw_prepaid: more than the balance is rejected", async () => {
+      try {
+        await sendTx(provider, userIx("withdraw_prepaid", encodeU64LE(new BN(LAMPORTS_PER_SOL)), false), []);
+        expect.fail("Should have thrown InsufficientPrepaidBalance");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("withdraw_prepaid: user withdraws unused balance", async () => {
+      const amount = new BN(LAMPORTS_PER_SOL / 20);
+      await sendTx(provider, userIx("withdraw_prepaid", encodeU64LE(amount), false), []);
+
+      const acct = await getAccountData(provider, prepaidPda);
+      expect(new BN(acct!.subarray(40, 48), "le").eq(new BN(LAMPORTS_PER_SOL / 20))).to.be.true;
+    });
+
+    let serviceEntryPda: PublicKey;
+    let volumeTrackerPda: PublicKey;
+    let agentPda: PublicKey;
+    let ownerBudgetPda: PublicKey;
+    let owningAgentId: number;
+    const queryId = randomHash();
+    let prepaidDrawPda: PublicKey;
+
+    before(async () => {
+      [serviceEntryPda] = findPda([Buffer.from("service"), encodeU16LE(1)], SERVICE_PROGRAM_ID);
+      const entry = await getAccountData(provider, serviceEntryPda);
+      owningAgentId = entry!.readUInt16LE(10);
+      [agentPda] = findPda([Buffer.from("agent"), encodeU16LE(owningAgentId)], CORE_PROGRAM_ID);
+      [ownerBudgetPda] = findPda([Buffer.from("budget"), encodeU16LE(owningAgentId)], TREASURY_PROGRAM_ID);
+      [volumeTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), superAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+      [prepaidDrawPda] = findPda(
+        [Buffer.from("prepaid_draw"), superAuthority.publicKey.toBuffer(), queryId],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    function drawIx(authority: PublicKey, manifest: PublicKey, query: Buffer, drawPda: PublicKey): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          { pubkey: prepaidPda, isSigner: false, isWritable: true },
+          { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+          { pubkey: manifest, isSigner: false, isWritable: false },
+          { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+          { pubkey: drawPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("draw_prepaid_query"), encodeU16LE(1), encodeBytes32(query)]),
+      });
+    }
+
+    function refundDrawIx(authority: PublicKey): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: authority, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: auditorConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          { pubkey: prepaidDrawPda, isSigner: false, isWritable: true },
+          { pubkey: prepaidPda, isSigner: false, isWritable: true },
+          { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+          { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.concat([ixDiscriminator("refund_prepaid_draw"), encodeBytes32(randomHash())]),
+      });
+    }
+
+    it("draw_prepaid_query: non-keeper, non-agent signer is rejected", async () => {
+      try {
+        await sendTx(provider, drawIx(creatorWallet.publicKey, agentPda, queryId, prepaidDrawPda), [creatorWallet]);
+        expect.fail("Should have thrown UnauthorizedPrepaidDraw");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.UnauthorizedPrepaidDraw);
+      }
+    });
+
+    it("draw_prepaid_query: manifest of an agent that does not own the service is rejected", async () => {
+      const [otherAgentPda] = findPda([Buffer.from("agent"), encodeU16LE(2)], CORE_PROGRAM_ID);
+      try {
+        await sendTx(provider, drawIx(aeonAuthority.publicKey, otherAgentPda, queryId, prepaidDrawPda), [aeonAuthority]);
+        expect.fail("Should have thrown ConstraintSeeds");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.ConstraintSeeds);
+      }
+    });
+
+    it("draw_prepaid_query: keeper bills one query, records the draw and attributes it to the owning agent", async () => {
+      await sendTx(provider, userIx("deposit_prepaid", encodeU64LE(new BN(LAMPORTS_PER_SOL / 5)), true), []);
+      const before = await getAccountData(provider, prepaidPda);
+      const balanceBefore = new BN(before!.subarray(40, 48), "le");
+      // BudgetAllocation: revenue_generated at 60, revenue_credited at 68
+      const budgetBefore = await getAccountData(provider, ownerBudgetPda);
+
+      await sendTx(provider, drawIx(keeperAuthority.publicKey, agentPda, queryId, prepaidDrawPda), [keeperAuthority]);
+
+      // PrepaidDraw: user(8) query_id(40) service_id(72) amount(74) ... drawn_by(106) drawn_at(138) refunded(146)
+      const draw = await getAccountData(provider, prepaidDrawPda);
+      expect(draw!.subarray(0, 8).equals(accountDiscriminator("PrepaidDraw"))).to.be.true;
+      expect(draw!.subarray(40, 72).equals(queryId)).to.be.true;
+      expect(draw!.readUInt16LE(72)).to.equal(1);
+      expect(new PublicKey(draw!.subarray(106, 138)).equals(keeperAuthority.publicKey)).to.be.true;
+      expect(draw!.readUInt8(146)).to.equal(0);
+      const amount = new BN(draw!.subarray(74, 82), "le");
+      expect(amount.gtn(0)).to.be.true;
+
+      const after = await getAccountData(provider, prepaidPda);
+      expect(new BN(after!.subarray(40, 48), "le").eq(balanceBefore.sub(amount))).to.be.true;
+
+      // owning_agent_id(148) agent_attributed(150) agent_credit(151): 2000 bps of operations
+      const operations = new BN(draw!.subarray(82, 90), "le");
+      const credit = operations.muln(2_000).divn(10_000);
+      expect(draw!.readUInt16LE(148)).to.equal(owningAgentId);
+      expect(draw!.readUInt8(150)).to.equal(1);
+      expect(new BN(draw!.subarray(151, 159), "le").eq(credit)).to.be.true;
+      const budgetAfter = await getAccountData(provider, ownerBudgetPda);
+      expect(
+        new BN(budgetAfter!.subarray(60, 68), "le").sub(new BN(budgetBefore!.subarray(60, 68), "le")).eq(amount)
+      ).to.be.true;
+      expect(
+        new BN(budgetAfter!.subarray(68, 76), "le").sub(new BN(budgetBefore!.subarray(68, 76), "le")).eq(credit)
+      ).to.be.true;
+    });
+
+    it("draw_prepaid_query: the owning agent's budget cannot be left out", async () => {
+      const query = randomHash();
+      const [drawPda] = findPda(
+        [Buffer.from("prepaid_draw"), superAuthority.publicKey.toBuffer(), query],
+        TREASURY_PROGRAM_ID
+      );
+      const ix = drawIx(keeperAuthority.publicKey, agentPda, query, drawPda);
+      ix.keys[ix.keys.length - 1] = { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false };
+      try {
+        await sendTx(provider, ix, [keeperAuthority]);
+        expect.fail("Should have thrown ConstraintSeeds");
+      } catch (err: any) {
+        expectCustomError(err, AnchorErr.ConstraintSeeds);
+      }
+    });
+
+    it("draw_prepaid_query: the same query cannot be billed twice", async () => {
+      try {
+        await sendTx(provider, drawIx(keeperAuthority.publicKey, agentPda, queryId, prepaidDrawPda), [keeperAuthority]);
+        expect.fail("Should have rejected a second draw for the same query");
+      } catch (err: any) {
+        expect(errorText(err)).to.contain("already in use");
+      }
+    });
+
+    it("refund_prepaid_draw: non-keeper, non-auditor signer is rejected", async () => {
+      try {
+        await sendTx(provider, refundDrawIx(creatorWallet.publicKey), [creatorWallet]);
+        expect.fail("Should have thrown UnauthorizedRefundAuthority");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.UnauthorizedRefundAuthority);
+      }
+    });
+
+    it("refund_prepaid_draw: keeper returns the draw to the prepaid balance", async () => {
+      const draw = await getAccountData(provider, prepaidDrawPda);
+      const amount = new BN(draw!.subarray(74, 82), "le");
+      const before = await getAccountData(provider, prepaidPda);
+      const balanceBefore = new BN(before!.subarray(40, 48), "le");
+      const trackerBefore = await getAccountData(provider, volumeTrackerPda);
+      const budgetBefore = await getAccountData(provider, ownerBudgetPda);
+
+      await sendTx(provider, refundDrawIx(keeperAuthority.publicKey), [keeperAuthority]);
+
+      // The agent's attribution and undisbursed credit are reversed
+      const budgetAfter = await getAccountData(provider, ownerBudgetPda);
+      expect(
+        new BN(budgetBefore!.subarray(60, 68), "le").sub(new BN(budgetAfter!.subarray(60, 68), "le")).eq(amount)
+      ).to.be.true;
+      expect(
+        new BN(budgetBefore!.subarray(68, 76), "le")
+          .sub(new BN(budgetAfter!.subarray(68, 76), "le"))
+          .eq(new BN(draw!.subarray(151, 159), "le"))
+      ).to.be.true;
+
+      const after = await getAccountData(provider, prepaidPda);
+      expect(new BN(after!.subarray(40, 48), "le").eq(balanceBefore.add(amount))).to.be.true;
+      expect((await getAccountData(provider, prepaidDrawPda))!.readUInt8(146)).to.equal(1);
+
+      // The query no longer counts toward the volume discount
+      const trackerAfter = await getAccountData(provider, volumeTrackerPda);
+      expect(trackerAfter!.readUInt16LE(40)).to.equal(trackerBefore!.readUInt16LE(40) - 1);
+
+      // Treasury books back to zero (see the cross-cutting A0-24 check)
+      const vaultAcct = await getAccountData(provider, treasuryVaultPda);
+      expect(new BN(vaultAcct!.subarray(8, 16), "le").isZero()).to.be.true;
+    });
+
+    it("refund_prepaid_draw: second refund of the same draw is rejected", async () => {
+      try {
+        await sendTx(provider, refundDrawIx(keeperAuthority.publicKey), [keeperAuthority]);
+        expect.fail("Should have thrown PaymentAlreadyRefunded");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.PaymentAlreadyRefunded);
+      }
+    });
+
+    it("close_prepaid_draw: rejected while the refund window is open", async () => {
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: prepaidDrawPda, isSigner: false, isWritable: true },
+          { pubkey: keeperAuthority.publicKey, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("close_prepaid_draw"),
+      });
+      try {
+        await sendTx(provider, ix, []);
+        expect.fail("Should have thrown RefundWindowOpen");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.RefundWindowOpen);
+      }
+    });
+  });
+
+  // ================================================================
+  //  TREASURY SUBSCRIPTIONS (no successful charge: treasury books stay at 0)
+  // ================================================================
+  describe("noumen_treasury subscriptions", () => {
+    const planId = 1;
+    let planPda: PublicKey;
+    let subscriptionPda: PublicKey;
+
+    before(() => {
+      [planPda] = findPda([Buffer.from("subscription_plan"), encodeU16LE(planId)], TREASURY_PROGRAM_ID);
+      [subscriptionPda] = findPda(
+        [Buffer.from("subscription"), superAuthority.publicKey.toBuffer(), encodeU16LE(planId)],
+        TREASURY_PROGRAM_ID
+      );
+    });
+
+    function createPlanIx(periodSeconds: number, graceSeconds = 259_200): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: planPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          ixDiscriminator("create_subscription_plan"),
+          encodeU16LE(planId),
+          encodeU8(1),                              // tier (Pro)
+          encodeU64LE(new BN(LAMPORTS_PER_SOL / 10)), // price_lamports
+          encodeI64LE(new BN(periodSeconds)),       // period_seconds
+          encodeI64LE(new BN(graceSeconds)),        // grace_seconds (3 days by default)
+          encodeU8(0),                              // owning_agent_id: None
+        ]),
+      });
+    }
+
+    function userIx(name: string, args: Buffer, keys: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[]): TransactionInstruction {
+      return new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [{ pubkey: superAuthority.publicKey, isSigner: true, isWritable: true }, ...keys],
+        data: Buffer.concat([ixDiscriminator(name), args]),
+      });
+    }
+
+    it("create_subscription_plan: period shorter than a day is rejected", async () => {
+      try {
+        await sendTx(provider, createPlanIx(3600), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidSubscriptionPeriod");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+    });
+
+    it("create_subscription_plan: a zero-grace plan is rejected", async () => {
+      // With no grace a due period would lapse before it could ever be charged
+      try {
+        await sendTx(provider, createPlanIx(2_592_000, 0), [aeonAuthority]);
+        expect.fail("Should have thrown InvalidSubscriptionPeriod");
+      } catch (err: any) {
+        expectCustomError(err, TreasuryErr.InvalidSubscriptionPeriod);
+      }
+      expect(await getAccountData(provider, planPda)).to.be.null;
+    });
+
+    it("create_subscription_plan: aeon creates a 30-day plan", async () => {
+      await sendTx(provider, createPlanIx(2_592_000), [aeonAuthority]);
+
+      const acct = await getAccountData(provider, planPda);
+      expect(acct!.subarray(0, 8).equals(accountDiscriminator("SubscriptionPlan"))).to.be.true;
+      // price_lamports at 8 + 2 + 1 = 11
+      expect(new BN(acct!.subarray(11, 19), "le").eq(new BN(LAMPORTS_PER_SOL / 10))).to.be.true;
+    });
+
+    it("subscribe: user approves an allowance; the first period is due immediately", async () => {
+      const price = new BN(LAMPORTS_PER_SOL / 10);
+      await sendTx(
+        provider,
+        userIx(
+          "subscribe",
+          Buffer.concat([encodeU16LE(planId), encodeU64LE(price), encodeU64LE(price.muln(12)), encodeU64LE(new BN(0))]),
+          [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: planPda, isSigner: false, isWritable: true },
+            { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ]
+        ),
+        []
+      );
+
+      const acct = await getAccountData(provider, subscriptionPda);
+      // status at 8 + 32 + 2 = 42; allowance_remaining at 51
+      expect(acct!.readUInt8(42)).to.equal(0);
+      expect(new BN(acct!.subarray(51, 59), "le").eq(price.muln(12))).to.be.true;
+    });
+
+    it("charge_subscription: unfunded escrow marks the subscription PastDue", async () => {
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+          { pubkey: planPda, isSigner: false, isWritable: true },
+          { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+          { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // owner_budget: plan has no owner
+        ],
+        data: ixDiscriminator("charge_subscription"),
+      });
+      // Permissionless: fee payer only
+      await sendTx(provider, ix, []);
+
+      const acct = await getAccountData(provider, subscriptionPda);
+      expect(acct!.readUInt8(42)).to.equal(1); // PastDue, still within grace
+    });
+
+    it("cancel_subscription + close_subscription: user cancels and reclaims rent", async () => {
+      await sendTx(provider, userIx("cancel_subscription", Buffer.alloc(0), [
+        { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+      ]), []);
+      const acct = await getAccountData(provider, subscriptionPda);
+      expect(acct!.readUInt8(42)).to.equal(3); // Cancelled
+
+      await sendTx(provider, userIx("close_subscription", Buffer.alloc(0), [
+        { pubkey: planPda, isSigner: false, isWritable: true },
+        { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+      ]), []);
+      expect(await getAccountData(provider, subscriptionPda)).to.be.null;
+    });
+  });
+
   // ================================================================
   //  CROSS-CUTTING AXIOM VALIDATION SUMMARY
   // ================================================================
@@ -1583,5 +4274,1268 @@ describe("NOUMEN Protocol Integration Tests", () => {
       const totalBalance = new BN(vaultAcct!.subarray(8, 16), "le");
       expect(totalBalance.eq(new BN(0))).to.be.true;
     });
+
+    it("reconcile_treasury: unsolicited transfer is booked as unattributed inflow", async () => {
+      const inflow = 1_000_000;
+      const transferIx = SystemProgram.transfer({
+        fromPubkey: superAuthority.publicKey,
+        toPubkey: treasuryVaultPda,
+        lamports: inflow,
+      });
+      await sendTx(provider, transferIx, []);
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("reconcile_treasury"),
+      });
+      // Permissionless: fee payer only
+      await sendTx(provider, ix, []);
+
+      const vaultAcct = await getAccountData(provider, treasuryVaultPda);
+      const totalBalance = new BN(vaultAcct!.subarray(8, 16), "le");
+      expect(totalBalance.eq(new BN(inflow))).to.be.true;
+      // free balance = total - reserved (reserve untouched)
+      const freeBalance = new BN(vaultAcct!.subarray(24, 32), "le");
+      expect(freeBalance.eq(new BN(inflow))).to.be.true;
+    });
+
+    it("close_treasury_period: rejected before the 30-day period has elapsed", async () => {
+      const [statementPda] = findPda(
+        [Buffer.from("treasury_statement"), encodeU32LE(0)],
+        TREASURY_PROGRAM_ID
+      );
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: ccsConfigPda, isSigner: false, isWritable: false },
+          { pubkey: statementPda, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: ixDiscriminator("close_treasury_period"),
+      });
+      try {
+        await sendTx(provider, ix, []);
+        expect.fail("Should have thrown PeriodNotElapsed");
+      } catch (err: any) {
+        expect(err.toString()).to.contain("custom program error");
+      }
+      expect(await getAccountData(provider, statementPda)).to.be.null;
+    });
+
+    it("sweep_donations: flagged receipts are swept as unattributed inflow, not donations (A0-24)", async () => {
+      // DonationVault: pending_sweep at 16, receipt_count at 37,
+      // pending_flagged and swept_receipt_count after the 20-level archive frontier
+      const frontierEnd = 105 + 32 * 20;
+      const donBefore = await getAccountData(provider, donationVaultPda);
+      const pending = new BN(donBefore!.subarray(16, 24), "le");
+      const pendingFlagged = new BN(donBefore!.subarray(frontierEnd, frontierEnd + 8), "le");
+      // The donation made right after a service payment was flagged at donation time
+      expect(pendingFlagged.gtn(0)).to.be.true;
+      expect(pendingFlagged.lt(pending)).to.be.true;
+
+      // TreasuryVault: total_donations_swept at 64, unattributed_inflow_lamports at 161
+      const vaultBefore = await getAccountData(provider, treasuryVaultPda);
+      const balanceBefore = new BN(vaultBefore!.subarray(8, 16), "le");
+      const sweptBefore = new BN(vaultBefore!.subarray(64, 72), "le");
+      const unattributedBefore = new BN(vaultBefore!.subarray(161, 169), "le");
+
+      const ix = new TransactionInstruction({
+        programId: TREASURY_PROGRAM_ID,
+        keys: [
+          { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: false },
+          { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+          { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+          { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+        ],
+        data: ixDiscriminator("sweep_donations"),
+      });
+      await sendTx(provider, ix, [keeperAuthority]);
+
+      const vaultAfter = await getAccountData(provider, treasuryVaultPda);
+      expect(new BN(vaultAfter!.subarray(8, 16), "le").eq(balanceBefore.add(pending))).to.be.true;
+      expect(new BN(vaultAfter!.subarray(64, 72), "le").eq(sweptBefore.add(pending.sub(pendingFlagged)))).to.be.true;
+      expect(new BN(vaultAfter!.subarray(161, 169), "le").eq(unattributedBefore.add(pendingFlagged))).to.be.true;
+
+      const donAfter = await getAccountData(provider, donationVaultPda);
+      expect(new BN(donAfter!.subarray(16, 24), "le").isZero()).to.be.true;
+      expect(new BN(donAfter!.subarray(frontierEnd, frontierEnd + 8), "le").isZero()).to.be.true;
+      const receiptCount = new BN(donAfter!.subarray(37, 45), "le");
+      expect(new BN(donAfter!.subarray(frontierEnd + 8, frontierEnd + 16), "le").eq(receiptCount)).to.be.true;
+    });
+  });
+
+  // ================================================================
+  //  FUNDED TREASURY: governed reserve and operations outflows
+  //  (runs last: leaves the vault with real operations and reserve balances)
+  // ================================================================
+  describe("noumen_treasury funded reserve and operations", () => {
+    // Service 2, owned by agent 1, priced so that half the resulting reserve
+    // (MAX_STAKED_RESERVE_BPS) still covers the stake program's minimum delegation
+    const serviceIdBuf = encodeU16LE(2);
+    const priceLamports = new BN(10 * LAMPORTS_PER_SOL);
+    let serviceEntryPda: PublicKey;
+    let volumeTrackerPda: PublicKey;
+    let ownerBudgetPda: PublicKey;
+
+    async function vaultU64(offset: number): Promise<BN> {
+      const acct = await getAccountData(provider, treasuryVaultPda);
+      return new BN(acct!.subarray(offset, offset + 8), "le");
+    }
+
+    before(async () => {
+      [serviceEntryPda] = findPda([Buffer.from("service"), serviceIdBuf], SERVICE_PROGRAM_ID);
+      [volumeTrackerPda] = findPda(
+        [Buffer.from("volume_tracker"), superAuthority.publicKey.toBuffer()],
+        TREASURY_PROGRAM_ID
+      );
+      [ownerBudgetPda] = findPda([Buffer.from("budget"), encodeU16LE(1)], TREASURY_PROGRAM_ID);
+
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: SERVICE_PROGRAM_ID,
+          keys: [
+            { pubkey: serviceConfigPda, isSigner: false, isWritable: true },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("register_service"),
+            serviceIdBuf,
+            encodeU16LE(1),                           // owning_agent_id
+            encodeU8(0),                              // Entry tier
+            encodeU64LE(priceLamports),
+            encodeU64LE(new BN(8 * LAMPORTS_PER_SOL)), // cost: 25% margin (A0-8)
+          ]),
+        }),
+        [aeonAuthority]
+      );
+      for (const level of [1, 2]) {
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: SERVICE_PROGRAM_ID,
+            keys: [
+              { pubkey: serviceConfigPda, isSigner: false, isWritable: false },
+              { pubkey: serviceEntryPda, isSigner: false, isWritable: true },
+              { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+            ],
+            data: Buffer.concat([ixDiscriminator("update_service_level"), serviceIdBuf, encodeU8(level)]),
+          }),
+          [aeonAuthority]
+        );
+      }
+
+      // Still below the first volume discount tier, so the list price applies
+      const tracker = await getAccountData(provider, volumeTrackerPda);
+      expect(tracker!.readUInt16LE(40)).to.be.below(10);
+
+      // Final payment: no referrer
+      const record = newPaymentRecord(superAuthority.publicKey);
+      await sendTx(
+        provider,
+        new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+            { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+            { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+            { pubkey: record.pda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+            { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+            { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referral_link: none
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("process_service_payment"),
+            serviceIdBuf,
+            encodeU64LE(priceLamports),
+            encodeU64LE(record.nonce),
+          ]),
+        }),
+        []
+      );
+    });
+
+    describe("release_reserve", () => {
+      function releaseHash(amount: BN): Buffer {
+        return policyChangeHash("reserve_release", encodeU64LE(amount));
+      }
+
+      function releaseIx(proposal: GovernanceProposal, amount: BN): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+            { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("release_reserve"), encodeU64LE(amount)]),
+        });
+      }
+
+      /** 5% of the reserve: under the 10% annual cap and the A0-3 excess. */
+      async function releaseAmount(): Promise<BN> {
+        return (await vaultU64(16)).divn(20);
+      }
+
+      it("proposal committing to another amount is rejected", async () => {
+        const amount = await releaseAmount();
+        const proposal = await executedProposal(provider, aeonAuthority, 1, releaseHash(amount.addn(1)));
+        try {
+          await sendTx(provider, releaseIx(proposal, amount), [aeonAuthority]);
+          expect.fail("Should have thrown PolicyChangeHashMismatch");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+        }
+      });
+
+      it("Layer 2 proposal is rejected", async () => {
+        const amount = await releaseAmount();
+        const proposal = await executedProposal(provider, aeonAuthority, 2, releaseHash(amount));
+        try {
+          await sendTx(provider, releaseIx(proposal, amount), [aeonAuthority]);
+          expect.fail("Should have thrown InvalidPolicyLayer");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+        }
+      });
+
+      it("executed Layer 1 proposal moves reserve into free balance; replay is rejected", async () => {
+        const amount = await releaseAmount();
+        expect(amount.gtn(0)).to.be.true;
+        const reservedBefore = await vaultU64(16);
+        const freeBefore = await vaultU64(24);
+        const releasedBefore = await vaultU64(153);
+
+        const proposal = await executedProposal(provider, aeonAuthority, 1, releaseHash(amount));
+        await sendTx(provider, releaseIx(proposal, amount), [aeonAuthority]);
+
+        // reserved (16) down, free (24) up, total_reserve_released (153) up; no lamports move
+        expect((await vaultU64(16)).eq(reservedBefore.sub(amount))).to.be.true;
+        expect((await vaultU64(24)).eq(freeBefore.add(amount))).to.be.true;
+        expect((await vaultU64(153)).eq(releasedBefore.add(amount))).to.be.true;
+
+        try {
+          await sendTx(provider, releaseIx(proposal, amount), [aeonAuthority]);
+          expect.fail("Should have rejected a second use of the proposal");
+        } catch (err: any) {
+          expectProposalReplayRejected(err);
+        }
+      });
+    });
+
+    describe("pay_operational_expense", () => {
+      const payee = Keypair.generate().publicKey;
+      let payeePda: PublicKey;
+
+      function payIx(amount: BN, invoiceHash: Buffer): TransactionInstruction {
+        const [expensePda] = findPda([Buffer.from("expense"), invoiceHash], TREASURY_PROGRAM_ID);
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: payeePda, isSigner: false, isWritable: true },
+            { pubkey: payee, isSigner: false, isWritable: true },
+            { pubkey: expensePda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("pay_operational_expense"),
+            encodePubkey(payee),
+            encodeU64LE(amount),
+            encodeBytes32(invoiceHash),
+          ]),
+        });
+      }
+
+      before(async () => {
+        [payeePda] = findPda([Buffer.from("payee"), payee.toBuffer()], TREASURY_PROGRAM_ID);
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: payee, isSigner: false, isWritable: false },
+              { pubkey: payeePda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([ixDiscriminator("register_expense_payee"), encodeBytes32(randomHash())]),
+          }),
+          [aeonAuthority]
+        );
+      });
+
+      it("pays an invoice from operations; the same invoice_hash cannot be paid twice", async () => {
+        // 1% of free balance: inside DAILY_SPEND_CAP_BPS and well above the
+        // rent-exempt minimum the fresh payee wallet needs
+        const amount = (await vaultU64(24)).divn(100);
+        const rentFloor = await provider.connection.getMinimumBalanceForRentExemption(0);
+        expect(amount.gtn(rentFloor)).to.be.true;
+
+        const operationsBefore = await vaultU64(72);
+        const totalBefore = await vaultU64(8);
+        const reservedBefore = await vaultU64(16);
+        expect(operationsBefore.gte(amount)).to.be.true;
+
+        const invoiceHash = randomHash();
+        await sendTx(provider, payIx(amount, invoiceHash), [aeonAuthority]);
+
+        // Lamports left the vault; operations and total shrink, the reserve does not
+        expect(await provider.connection.getBalance(payee)).to.equal(amount.toNumber());
+        expect((await vaultU64(72)).eq(operationsBefore.sub(amount))).to.be.true;
+        expect((await vaultU64(8)).eq(totalBefore.sub(amount))).to.be.true;
+        expect((await vaultU64(16)).eq(reservedBefore)).to.be.true;
+
+        // ExpenseRecord: invoice_hash, payee, amount, paid_at, operations_after
+        const [expensePda] = findPda([Buffer.from("expense"), invoiceHash], TREASURY_PROGRAM_ID);
+        const record = await getAccountData(provider, expensePda);
+        expect(record!.subarray(0, 8).equals(accountDiscriminator("ExpenseRecord"))).to.be.true;
+        expect(record!.subarray(8, 40).equals(invoiceHash)).to.be.true;
+        expect(new PublicKey(record!.subarray(40, 72)).equals(payee)).to.be.true;
+        expect(new BN(record!.subarray(72, 80), "le").eq(amount)).to.be.true;
+        expect(new BN(record!.subarray(88, 96), "le").eq(operationsBefore.sub(amount))).to.be.true;
+
+        // ExpensePayee: total_paid (73), payment_count (81)
+        const payeeAcct = await getAccountData(provider, payeePda);
+        expect(new BN(payeeAcct!.subarray(73, 81), "le").eq(amount)).to.be.true;
+        expect(payeeAcct!.readUInt32LE(81)).to.equal(1);
+
+        try {
+          await sendTx(provider, payIx(new BN(1), invoiceHash), [aeonAuthority]);
+          expect.fail("Should have rejected a second payment of the same invoice");
+        } catch (err: any) {
+          expect(errorText(err)).to.contain("already in use");
+        }
+        expect(await provider.connection.getBalance(payee)).to.equal(amount.toNumber());
+        expect((await vaultU64(72)).eq(operationsBefore.sub(amount))).to.be.true;
+      });
+    });
+
+    describe("reserve staking", () => {
+      const stakeAmount = new BN(1.5 * LAMPORTS_PER_SOL);
+      const [stakeAuthorityPda] = findPda([Buffer.from("stake_authority")], TREASURY_PROGRAM_ID);
+      let voteAccount: PublicKey;
+      let stakeIndex: number;
+      let stakeAccountPda: PublicKey;
+      let treasuryStakePda: PublicKey;
+
+      function delegateHash(vote: PublicKey, amount: BN): Buffer {
+        return policyChangeHash("stake_delegate", Buffer.concat([encodePubkey(vote), encodeU64LE(amount)]));
+      }
+
+      function delegateIx(proposal: GovernanceProposal, amount: BN): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: stakeAuthorityPda, isSigner: false, isWritable: false },
+            { pubkey: stakeAccountPda, isSigner: false, isWritable: true },
+            { pubkey: treasuryStakePda, isSigner: false, isWritable: true },
+            { pubkey: voteAccount, isSigner: false, isWritable: false },
+            { pubkey: proposal.proposalPda, isSigner: false, isWritable: false },
+            { pubkey: proposal.appliedPda, isSigner: false, isWritable: true },
+            { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_STAKE_HISTORY_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: new PublicKey("StakeConfig11111111111111111111111111111111"), isSigner: false, isWritable: false },
+            { pubkey: StakeProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("delegate_reserve_stake"), encodeU64LE(amount)]),
+        });
+      }
+
+      function deactivateIx(): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryStakePda, isSigner: false, isWritable: true },
+            { pubkey: stakeAccountPda, isSigner: false, isWritable: true },
+            { pubkey: stakeAuthorityPda, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: StakeProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("deactivate_reserve_stake"),
+        });
+      }
+
+      function withdrawIx(): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: treasuryStakePda, isSigner: false, isWritable: true },
+            { pubkey: stakeAccountPda, isSigner: false, isWritable: true },
+            { pubkey: stakeAuthorityPda, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: SYSVAR_STAKE_HISTORY_PUBKEY, isSigner: false, isWritable: false },
+            { pubkey: StakeProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("withdraw_reserve_stake"),
+        });
+      }
+
+      function reconcileIx(): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+          ],
+          data: ixDiscriminator("reconcile_treasury"),
+        });
+      }
+
+      before(async () => {
+        const { current } = await provider.connection.getVoteAccounts();
+        voteAccount = new PublicKey(current[0].votePubkey);
+        // Next stake index = TreasuryVault.stake_account_count (209)
+        const vault = await getAccountData(provider, treasuryVaultPda);
+        stakeIndex = vault!.readUInt32LE(209);
+        const indexBuf = encodeU32LE(stakeIndex);
+        [stakeAccountPda] = findPda([Buffer.from("stake_account"), indexBuf], TREASURY_PROGRAM_ID);
+        [treasuryStakePda] = findPda([Buffer.from("treasury_stake"), indexBuf], TREASURY_PROGRAM_ID);
+        // MAX_STAKED_RESERVE_BPS (half) of the reserve must cover the stake
+        expect((await vaultU64(16)).divn(2).gte(stakeAmount)).to.be.true;
+      });
+
+      it("proposal committing to another vote account or amount is rejected", async () => {
+        const proposal = await executedProposal(
+          provider, aeonAuthority, 1, delegateHash(voteAccount, stakeAmount.addn(1))
+        );
+        try {
+          await sendTx(provider, delegateIx(proposal, stakeAmount), [aeonAuthority]);
+          expect.fail("Should have thrown PolicyChangeHashMismatch");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.PolicyChangeHashMismatch);
+        }
+        expect(await getAccountData(provider, treasuryStakePda)).to.be.null;
+      });
+
+      it("Layer 2 proposal is rejected", async () => {
+        const proposal = await executedProposal(provider, aeonAuthority, 2, delegateHash(voteAccount, stakeAmount));
+        try {
+          await sendTx(provider, delegateIx(proposal, stakeAmount), [aeonAuthority]);
+          expect.fail("Should have thrown InvalidPolicyLayer");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.InvalidPolicyLayer);
+        }
+      });
+
+      it("stake above MAX_STAKED_RESERVE_BPS of the reserve is rejected", async () => {
+        const tooMuch = (await vaultU64(16)).divn(2).addn(1);
+        const proposal = await executedProposal(provider, aeonAuthority, 1, delegateHash(voteAccount, tooMuch));
+        try {
+          await sendTx(provider, delegateIx(proposal, tooMuch), [aeonAuthority]);
+          expect.fail("Should have thrown StakeCapExceeded");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.StakeCapExceeded);
+        }
+        expect(await getAccountData(provider, treasuryStakePda)).to.be.null;
+      });
+
+      it("delegate -> deactivate -> withdraw keeps the books whole and books rewards", async () => {
+        const totalBefore = await vaultU64(8);
+        const reservedBefore = await vaultU64(16);
+        const stakedBefore = await vaultU64(193);
+        const rewardsBefore = await vaultU64(201);
+        const unattributedBefore = await vaultU64(161);
+        const vaultLamportsBefore = await provider.connection.getBalance(treasuryVaultPda);
+
+        // Delegate and deactivate in one transaction: a stake deactivated in its
+        // activation epoch never becomes effective, so it is withdrawable at once
+        // and the test cannot race an epoch boundary.
+        const proposal = await executedProposal(provider, aeonAuthority, 1, delegateHash(voteAccount, stakeAmount));
+        await sendTx(provider, [delegateIx(proposal, stakeAmount), deactivateIx()], [aeonAuthority]);
+
+        // Staked lamports left the vault PDA but stay on the books as reserve
+        expect(await provider.connection.getBalance(treasuryVaultPda)).to.equal(
+          vaultLamportsBefore - stakeAmount.toNumber()
+        );
+        expect(await provider.connection.getBalance(stakeAccountPda)).to.equal(stakeAmount.toNumber());
+        expect((await vaultU64(8)).eq(totalBefore)).to.be.true;
+        expect((await vaultU64(16)).eq(reservedBefore)).to.be.true;
+        expect((await vaultU64(193)).eq(stakedBefore.add(stakeAmount))).to.be.true;
+        expect((await getAccountData(provider, treasuryVaultPda))!.readUInt32LE(209)).to.equal(stakeIndex + 1);
+
+        // TreasuryStake: index (8), stake_account (12), vote (44), principal (76),
+        // status (84: 1 = Deactivating), proposal_id (117)
+        let stake = await getAccountData(provider, treasuryStakePda);
+        expect(stake!.readUInt32LE(8)).to.equal(stakeIndex);
+        expect(new PublicKey(stake!.subarray(12, 44)).equals(stakeAccountPda)).to.be.true;
+        expect(new PublicKey(stake!.subarray(44, 76)).equals(voteAccount)).to.be.true;
+        expect(new BN(stake!.subarray(76, 84), "le").eq(stakeAmount)).to.be.true;
+        expect(stake!.readUInt8(84)).to.equal(1);
+        expect(stake!.readUInt32LE(117)).to.equal(proposal.proposalId);
+
+        // Reconcile while staked: staked lamports are not a deficit or a surplus
+        await sendTx(provider, reconcileIx(), []);
+        expect((await vaultU64(169)).isZero()).to.be.true;
+        expect((await vaultU64(161)).eq(unattributedBefore)).to.be.true;
+
+        // Replaying the delegation proposal is rejected
+        try {
+          await sendTx(provider, delegateIx(proposal, stakeAmount), [aeonAuthority]);
+          expect.fail("Should have rejected a second use of the proposal");
+        } catch (err: any) {
+          expectProposalReplayRejected(err);
+        }
+
+        // Anything above principal in the stake account is booked as reward
+        const reward = 5_000_000;
+        await sendTx(
+          provider,
+          SystemProgram.transfer({
+            fromPubkey: superAuthority.publicKey,
+            toPubkey: stakeAccountPda,
+            lamports: reward,
+          }),
+          []
+        );
+        await sendTx(provider, withdrawIx(), []);
+
+        expect(await provider.connection.getBalance(stakeAccountPda)).to.equal(0);
+        expect(await provider.connection.getBalance(treasuryVaultPda)).to.equal(vaultLamportsBefore + reward);
+        expect((await vaultU64(193)).eq(stakedBefore)).to.be.true;
+        expect((await vaultU64(8)).eq(totalBefore.addn(reward))).to.be.true;
+        expect((await vaultU64(16)).eq(reservedBefore)).to.be.true;
+        expect((await vaultU64(201)).eq(rewardsBefore.addn(reward))).to.be.true;
+        expect((await vaultU64(24)).eq(totalBefore.addn(reward).sub(reservedBefore))).to.be.true;
+
+        // status 2 = Withdrawn, withdrawn_lamports (109)
+        stake = await getAccountData(provider, treasuryStakePda);
+        expect(stake!.readUInt8(84)).to.equal(2);
+        expect(new BN(stake!.subarray(109, 117), "le").eq(stakeAmount.addn(reward))).to.be.true;
+
+        // The reward was booked, not left as unattributed surplus
+        await sendTx(provider, reconcileIx(), []);
+        expect((await vaultU64(169)).isZero()).to.be.true;
+        expect((await vaultU64(161)).eq(unattributedBefore)).to.be.true;
+      });
+    });
+
+    describe("subscription charge", () => {
+      // Plan 1 (0.1 SOL / 30 days) from the subscriptions describe; its first
+      // subscription was closed there, so the user can subscribe again
+      const planId = 1;
+      const price = new BN(LAMPORTS_PER_SOL / 10);
+      let planPda: PublicKey;
+      let subscriptionPda: PublicKey;
+
+      function chargeIx(): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+            { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+            { pubkey: planPda, isSigner: false, isWritable: true },
+            { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+            { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // owner_budget: plan has no owner
+          ],
+          data: ixDiscriminator("charge_subscription"),
+        });
+      }
+
+      before(async () => {
+        [planPda] = findPda([Buffer.from("subscription_plan"), encodeU16LE(planId)], TREASURY_PROGRAM_ID);
+        [subscriptionPda] = findPda(
+          [Buffer.from("subscription"), superAuthority.publicKey.toBuffer(), encodeU16LE(planId)],
+          TREASURY_PROGRAM_ID
+        );
+        // Escrow funded with exactly one period at subscribe time
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: planPda, isSigner: false, isWritable: true },
+              { pubkey: subscriptionPda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("subscribe"),
+              encodeU16LE(planId),
+              encodeU64LE(price),
+              encodeU64LE(price.muln(12)),
+              encodeU64LE(price),
+            ]),
+          }),
+          []
+        );
+      });
+
+      it("funded subscription is charged, split like a payment, and paid one period ahead", async () => {
+        const totalBefore = await vaultU64(8);
+        const reservedBefore = await vaultU64(16);
+        const operationsBefore = await vaultU64(72);
+        const escrowBefore = await vaultU64(213); // creator_escrow_lamports
+        const vaultLamportsBefore = await provider.connection.getBalance(treasuryVaultPda);
+        const before = await getAccountData(provider, subscriptionPda);
+        // Subscription: status 42, allowance 51, escrow 59, paid_through 67,
+        // periods_charged 75, total_charged 79
+        const paidThroughBefore = new BN(before!.subarray(67, 75), "le");
+
+        await sendTx(provider, chargeIx(), []);
+
+        // The price moved from the subscription escrow into the vault PDA
+        expect(await provider.connection.getBalance(treasuryVaultPda)).to.equal(
+          vaultLamportsBefore + price.toNumber()
+        );
+        const operations = (await vaultU64(72)).sub(operationsBefore);
+        const reserve = (await vaultU64(16)).sub(reservedBefore);
+        const creator = (await vaultU64(213)).sub(escrowBefore);
+        expect(operations.eq(price.muln(4000).divn(10_000))).to.be.true;
+        expect(creator.gtn(0)).to.be.true;
+        expect(operations.add(reserve).add(creator).eq(price)).to.be.true;
+        expect((await vaultU64(8)).sub(totalBefore).eq(operations.add(reserve))).to.be.true;
+
+        const after = await getAccountData(provider, subscriptionPda);
+        expect(after!.readUInt8(42)).to.equal(0); // Active
+        expect(new BN(after!.subarray(51, 59), "le").eq(price.muln(11))).to.be.true;
+        expect(new BN(after!.subarray(59, 67), "le").isZero()).to.be.true;
+        expect(new BN(after!.subarray(67, 75), "le").eq(paidThroughBefore.addn(2_592_000))).to.be.true;
+        expect(after!.readUInt32LE(75)).to.equal(1);
+        expect(new BN(after!.subarray(79, 87), "le").eq(price)).to.be.true;
+      });
+
+      it("the next period is not due yet", async () => {
+        try {
+          await sendTx(provider, chargeIx(), []);
+          expect.fail("Should have thrown SubscriptionNotDue");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.SubscriptionNotDue);
+        }
+      });
+    });
+
+    describe("agent attribution", () => {
+      const ownedPlanId = 2;
+      const planPrice = new BN(LAMPORTS_PER_SOL / 10);
+      let ownedPlanPda: PublicKey;
+      let ownedSubscriptionPda: PublicKey;
+
+      function chargeOwnedIx(withBudget: boolean): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+            { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+            { pubkey: ownedPlanPda, isSigner: false, isWritable: true },
+            { pubkey: ownedSubscriptionPda, isSigner: false, isWritable: true },
+            withBudget
+              ? { pubkey: ownerBudgetPda, isSigner: false, isWritable: true }
+              : { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("charge_subscription"),
+        });
+      }
+
+      before(async () => {
+        [ownedPlanPda] = findPda([Buffer.from("subscription_plan"), encodeU16LE(ownedPlanId)], TREASURY_PROGRAM_ID);
+        [ownedSubscriptionPda] = findPda(
+          [Buffer.from("subscription"), superAuthority.publicKey.toBuffer(), encodeU16LE(ownedPlanId)],
+          TREASURY_PROGRAM_ID
+        );
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: ownedPlanPda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("create_subscription_plan"),
+              encodeU16LE(ownedPlanId),
+              encodeU8(1),                        // tier (Pro)
+              encodeU64LE(planPrice),
+              encodeI64LE(new BN(2_592_000)),     // period_seconds
+              encodeI64LE(new BN(259_200)),       // grace_seconds
+              encodeU8(1),                        // owning_agent_id: Some(1)
+              encodeU16LE(1),
+            ]),
+          }),
+          [aeonAuthority]
+        );
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: ownedPlanPda, isSigner: false, isWritable: true },
+              { pubkey: ownedSubscriptionPda, isSigner: false, isWritable: true },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("subscribe"),
+              encodeU16LE(ownedPlanId),
+              encodeU64LE(planPrice),
+              encodeU64LE(planPrice),
+              encodeU64LE(planPrice),
+            ]),
+          }),
+          []
+        );
+      });
+
+      it("a service payment cannot leave out the owning agent's budget", async () => {
+        const record = newPaymentRecord(superAuthority.publicKey);
+        try {
+          await sendTx(
+            provider,
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: superAuthority.publicKey, isSigner: true, isWritable: true },
+                { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+                { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+                { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+                { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+                { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+                { pubkey: volumeTrackerPda, isSigner: false, isWritable: true },
+                { pubkey: record.pda, isSigner: false, isWritable: true },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // owner_budget left out
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referral_link: none
+              ],
+              data: Buffer.concat([
+                ixDiscriminator("process_service_payment"),
+                serviceIdBuf,
+                encodeU64LE(priceLamports),
+                encodeU64LE(record.nonce),
+              ]),
+            }),
+            []
+          );
+          expect.fail("Should have thrown ConstraintSeeds");
+        } catch (err: any) {
+          expectCustomError(err, AnchorErr.ConstraintSeeds);
+        }
+      });
+
+      it("charging an agent-owned plan without the owner's budget is rejected", async () => {
+        const plan = await getAccountData(provider, ownedPlanPda);
+        expect(plan!.readUInt8(65)).to.equal(1);   // agent_owned
+        expect(plan!.readUInt16LE(66)).to.equal(1); // owning_agent_id
+        try {
+          await sendTx(provider, chargeOwnedIx(false), []);
+          expect.fail("Should have thrown MissingAgentBudget");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.MissingAgentBudget);
+        }
+      });
+
+      it("a charge on an agent-owned plan is attributed to the owner's budget", async () => {
+        const operationsBefore = await vaultU64(72);
+        // BudgetAllocation: revenue_generated at 60, revenue_credited at 68
+        const budgetBefore = await getAccountData(provider, ownerBudgetPda);
+
+        await sendTx(provider, chargeOwnedIx(true), []);
+
+        const operations = (await vaultU64(72)).sub(operationsBefore);
+        const credit = operations.muln(2_000).divn(10_000);
+        const budgetAfter = await getAccountData(provider, ownerBudgetPda);
+        expect(
+          new BN(budgetAfter!.subarray(60, 68), "le").sub(new BN(budgetBefore!.subarray(60, 68), "le")).eq(planPrice)
+        ).to.be.true;
+        expect(
+          new BN(budgetAfter!.subarray(68, 76), "le").sub(new BN(budgetBefore!.subarray(68, 76), "le")).eq(credit)
+        ).to.be.true;
+        expect((await getAccountData(provider, ownedSubscriptionPda))!.readUInt32LE(75)).to.equal(1);
+      });
+    });
+
+    describe("referral links", () => {
+      // A fresh payer with a second wallet it controls, used to show that an
+      // unapproved or later-approved referrer cannot capture its payments
+      const payer = Keypair.generate();
+      const sybil = Keypair.generate();
+      let payerTrackerPda: PublicKey;
+      let referralLinkPda: PublicKey;
+      let hermesReferrerPda: PublicKey;
+      let sybilReferrerPda: PublicKey;
+      let payerReferrerPda: PublicKey;
+
+      function registerReferrerIx(wallet: PublicKey, referrer: PublicKey): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: wallet, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: referrer, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: ixDiscriminator("register_referrer"),
+        });
+      }
+
+      function referredPaymentIx(referrer: PublicKey, withLink = true): TransactionInstruction {
+        const record = newPaymentRecord(payer.publicKey);
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+            { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+            { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+            { pubkey: payerTrackerPda, isSigner: false, isWritable: true },
+            { pubkey: record.pda, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: referrer, isSigner: false, isWritable: true },
+            { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+            withLink
+              ? { pubkey: referralLinkPda, isSigner: false, isWritable: true }
+              : { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.concat([
+            ixDiscriminator("process_service_payment"),
+            serviceIdBuf,
+            encodeU64LE(priceLamports),
+            encodeU64LE(record.nonce),
+          ]),
+        });
+      }
+
+      async function accrued(referrer: PublicKey): Promise<BN> {
+        const acct = await getAccountData(provider, referrer);
+        return new BN(acct!.subarray(41, 49), "le");
+      }
+
+      before(async () => {
+        [payerTrackerPda] = findPda(
+          [Buffer.from("volume_tracker"), payer.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+        [referralLinkPda] = findPda(
+          [Buffer.from("referral_link"), payer.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+        [hermesReferrerPda] = findPda(
+          [Buffer.from("referrer"), hermesAuthority.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+        [sybilReferrerPda] = findPda([Buffer.from("referrer"), sybil.publicKey.toBuffer()], TREASURY_PROGRAM_ID);
+        [payerReferrerPda] = findPda([Buffer.from("referrer"), payer.publicKey.toBuffer()], TREASURY_PROGRAM_ID);
+
+        for (const [wallet, sol] of [[payer, 30], [sybil, 1]] as [Keypair, number][]) {
+          const sig = await provider.connection.requestAirdrop(wallet.publicKey, sol * LAMPORTS_PER_SOL);
+          await provider.connection.confirmTransaction(sig, "confirmed");
+        }
+        await sendTx(
+          provider,
+          [
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+                { pubkey: payerTrackerPda, isSigner: false, isWritable: true },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+              ],
+              data: ixDiscriminator("initialize_volume_tracker"),
+            }),
+            registerReferrerIx(payer.publicKey, payerReferrerPda),
+          ],
+          [payer]
+        );
+        await sendTx(provider, registerReferrerIx(sybil.publicKey, sybilReferrerPda), [sybil]);
+      });
+
+      it("a payer cannot refer its own payment", async () => {
+        try {
+          await sendTx(provider, referredPaymentIx(payerReferrerPda), [payer]);
+          expect.fail("Should have thrown SelfReferral");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.SelfReferral);
+        }
+      });
+
+      it("a referred payment without the payer's referral link is rejected", async () => {
+        try {
+          await sendTx(provider, referredPaymentIx(hermesReferrerPda, false), [payer]);
+          expect.fail("Should have thrown ReferrerMismatch");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.ReferrerMismatch);
+        }
+      });
+
+      it("an unapproved referrer earns nothing and does not bind the payer", async () => {
+        await sendTx(provider, referredPaymentIx(sybilReferrerPda), [payer]);
+
+        expect((await accrued(sybilReferrerPda)).isZero()).to.be.true;
+        const link = await getAccountData(provider, referralLinkPda);
+        expect(link!.subarray(0, 8).equals(accountDiscriminator("ReferralLink"))).to.be.true;
+        expect(new PublicKey(link!.subarray(8, 40)).equals(payer.publicKey)).to.be.true;
+        expect(new PublicKey(link!.subarray(40, 72)).equals(PublicKey.default)).to.be.true;
+      });
+
+      it("the first approved referrer binds the payer and earns the referral share", async () => {
+        const hermes = await getAccountData(provider, hermesReferrerPda);
+        const accruedBefore = new BN(hermes!.subarray(41, 49), "le");
+        const earnedBefore = new BN(hermes!.subarray(49, 57), "le");
+
+        await sendTx(provider, referredPaymentIx(hermesReferrerPda), [payer]);
+
+        // 500 bps under a 1 SOL lifetime cap, per the governed referral policy
+        const expected = BN.min(
+          priceLamports.muln(500).divn(10_000),
+          new BN(LAMPORTS_PER_SOL).sub(earnedBefore)
+        );
+        expect((await accrued(hermesReferrerPda)).sub(accruedBefore).eq(expected)).to.be.true;
+        const link = await getAccountData(provider, referralLinkPda);
+        expect(new PublicKey(link!.subarray(40, 72)).equals(hermesAuthority.publicKey)).to.be.true;
+        expect(new BN(link!.subarray(72, 80), "le").gtn(0)).to.be.true; // linked_at
+      });
+
+      it("a referrer approved later cannot take over a bound payer", async () => {
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: sybilReferrerPda, isSigner: false, isWritable: true },
+            ],
+            data: Buffer.concat([ixDiscriminator("set_referrer_active"), encodeBool(true)]),
+          }),
+          [aeonAuthority]
+        );
+        try {
+          await sendTx(provider, referredPaymentIx(sybilReferrerPda), [payer]);
+          expect.fail("Should have thrown ReferrerMismatch");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.ReferrerMismatch);
+        }
+        expect((await accrued(sybilReferrerPda)).isZero()).to.be.true;
+      });
+    });
+
+    // Last: freezes the treasury, evacuates it and brings it back
+    describe("evacuation and reinitialization", () => {
+      const payer = Keypair.generate();
+      const paymentNonce = new BN(1);
+      let payerTrackerPda: PublicKey;
+      let paymentRecordPda: PublicKey;
+      let hermesReferrerPda: PublicKey;
+
+      async function ccsU64(offset: number): Promise<BN> {
+        const acct = await getAccountData(provider, ccsConfigPda);
+        return new BN(acct!.subarray(offset, offset + 8), "le");
+      }
+
+      function coreModeIx(mode: number): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: CORE_PROGRAM_ID,
+          keys: [
+            { pubkey: aeonConfigPda, isSigner: false, isWritable: true },
+            { pubkey: aeonAuthority.publicKey, isSigner: true, isWritable: false },
+          ],
+          data: Buffer.concat([ixDiscriminator("trigger_circuit_breaker"), encodeU8(mode), encodeBytes32(randomHash())]),
+        });
+      }
+
+      function claimIx(): TransactionInstruction {
+        return new TransactionInstruction({
+          programId: TREASURY_PROGRAM_ID,
+          keys: [
+            { pubkey: hermesAuthority.publicKey, isSigner: true, isWritable: true },
+            { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+            { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+            { pubkey: hermesReferrerPda, isSigner: false, isWritable: true },
+          ],
+          data: ixDiscriminator("claim_referral_rewards"),
+        });
+      }
+
+      before(async () => {
+        [payerTrackerPda] = findPda(
+          [Buffer.from("volume_tracker"), payer.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+        [paymentRecordPda] = findPda(
+          [Buffer.from("payment"), payer.publicKey.toBuffer(), encodeU64LE(paymentNonce)],
+          TREASURY_PROGRAM_ID
+        );
+        [hermesReferrerPda] = findPda(
+          [Buffer.from("referrer"), hermesAuthority.publicKey.toBuffer()],
+          TREASURY_PROGRAM_ID
+        );
+
+        // A refundable payment made before the evacuation
+        const sig = await provider.connection.requestAirdrop(payer.publicKey, 11 * LAMPORTS_PER_SOL);
+        await provider.connection.confirmTransaction(sig, "confirmed");
+        await sendTx(
+          provider,
+          [
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+                { pubkey: payerTrackerPda, isSigner: false, isWritable: true },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+              ],
+              data: ixDiscriminator("initialize_volume_tracker"),
+            }),
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+                { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+                { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+                { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+                { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+                { pubkey: serviceEntryPda, isSigner: false, isWritable: false },
+                { pubkey: payerTrackerPda, isSigner: false, isWritable: true },
+                { pubkey: paymentRecordPda, isSigner: false, isWritable: true },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+                { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referral_link: none
+              ],
+              data: Buffer.concat([
+                ixDiscriminator("process_service_payment"),
+                serviceIdBuf,
+                encodeU64LE(priceLamports),
+                encodeU64LE(paymentNonce),
+              ]),
+            }),
+          ],
+          [payer]
+        );
+
+        // A donation left unswept, to be swept and evacuated with the vault
+        const donAcct = await getAccountData(provider, donationVaultPda);
+        const [receiptPda] = findPda(
+          [Buffer.from("donation_receipt"), donAcct!.subarray(37, 45)],
+          TREASURY_PROGRAM_ID
+        );
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+              { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+              { pubkey: receiptPda, isSigner: false, isWritable: true },
+              { pubkey: payerTrackerPda, isSigner: false, isWritable: false },
+              { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("donate"),
+              encodeU64LE(new BN(LAMPORTS_PER_SOL / 2)),
+              encodeBytes32(randomHash()),
+              encodeU8(0),
+            ]),
+          }),
+          [payer]
+        );
+      });
+
+      it("evacuate_treasury moves the vault to the cold wallet and closes the SOL liabilities", async () => {
+        // CCSConfig: creator_accumulated at 86, creator_unvested at 95
+        expect((await ccsU64(86)).add(await ccsU64(95)).gtn(0)).to.be.true;
+        const hermes = await getAccountData(provider, hermesReferrerPda);
+        expect(new BN(hermes!.subarray(41, 49), "le").gtn(0)).to.be.true; // accrued_lamports
+
+        await sendTx(provider, coreModeIx(3), [aeonAuthority]); // Halted
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+              { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+            ],
+            data: ixDiscriminator("arm_treasury_evacuation"),
+          }),
+          []
+        );
+        // EVACUATION_DELAY_SECONDS is 2s in the short-policy-delays build
+        await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+        // DonationVault: pending_sweep at 16 is swept in and evacuated too
+        const donBefore = await getAccountData(provider, donationVaultPda);
+        const pendingDonations = new BN(donBefore!.subarray(16, 24), "le");
+        expect(pendingDonations.gtn(0)).to.be.true;
+
+        const vaultInfo = await provider.connection.getAccountInfo(treasuryVaultPda);
+        const rent = await provider.connection.getMinimumBalanceForRentExemption(vaultInfo!.data.length);
+        const expected = vaultInfo!.lamports - rent + pendingDonations.toNumber();
+        const coldBefore = await provider.connection.getBalance(coldWallet.publicKey);
+
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+              { pubkey: coldWallet.publicKey, isSigner: true, isWritable: true },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+              { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+              { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+              { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+              { pubkey: donationVaultPda, isSigner: false, isWritable: true },
+              { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+            ],
+            data: ixDiscriminator("evacuate_treasury"),
+          }),
+          [coldWallet]
+        );
+
+        expect((await provider.connection.getBalance(coldWallet.publicKey)) - coldBefore).to.equal(expected);
+        const donAfter = await getAccountData(provider, donationVaultPda);
+        expect(new BN(donAfter!.subarray(16, 24), "le").isZero()).to.be.true;
+        expect((await vaultU64(8)).eq(await vaultU64(193))).to.be.true; // total == staked
+        expect((await vaultU64(72)).isZero()).to.be.true;
+        expect((await vaultU64(213)).isZero()).to.be.true;
+        expect((await ccsU64(86)).isZero()).to.be.true;
+        expect((await ccsU64(95)).isZero()).to.be.true;
+
+        const config = await getAccountData(provider, treasuryConfigPda);
+        expect(config!.readUInt8(136)).to.equal(0); // is_initialized: frozen
+        // evacuated_at(230) evacuation_epoch(238) last_evacuation_at(242)
+        expect(config!.readUInt32LE(238)).to.equal(1);
+        expect(config!.readBigInt64LE(242)).to.equal(config!.readBigInt64LE(230));
+      });
+
+      it("reinitialize_treasury unfreezes once noumen-core leaves Halted", async () => {
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: CORE_PROGRAM_ID,
+            keys: [
+              { pubkey: aeonConfigPda, isSigner: false, isWritable: true },
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+            ],
+            data: Buffer.concat([ixDiscriminator("reset_circuit_breaker"), encodeBytes32(randomHash())]),
+          }),
+          []
+        );
+        await sendTx(
+          provider,
+          new TransactionInstruction({
+            programId: TREASURY_PROGRAM_ID,
+            keys: [
+              { pubkey: superAuthority.publicKey, isSigner: true, isWritable: false },
+              { pubkey: coldWallet.publicKey, isSigner: true, isWritable: false },
+              { pubkey: treasuryConfigPda, isSigner: false, isWritable: true },
+              { pubkey: aeonConfigPda, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.concat([
+              ixDiscriminator("reinitialize_treasury"),
+              encodePubkey(aeonAuthority.publicKey),
+              encodePubkey(keeperAuthority.publicKey),
+            ]),
+          }),
+          [coldWallet]
+        );
+
+        const config = await getAccountData(provider, treasuryConfigPda);
+        expect(config!.readUInt8(136)).to.equal(1);
+        expect(new BN(config!.subarray(230, 238), "le").isZero()).to.be.true; // evacuated_at
+        expect(config!.readUInt32LE(238)).to.equal(1); // epoch survives
+      });
+
+      it("the evacuated creator stream cannot be withdrawn again", async () => {
+        try {
+          await sendTx(
+            provider,
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: creatorWallet.publicKey, isSigner: true, isWritable: true },
+                { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+                { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+                { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+              ],
+              data: Buffer.concat([ixDiscriminator("withdraw_creator_split"), encodeU64LE(new BN(1))]),
+            }),
+            [creatorWallet]
+          );
+          expect.fail("Should have thrown InsufficientCreatorBalance");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.InsufficientCreatorBalance);
+        }
+      });
+
+      it("a referral accrual from before the evacuation cannot be claimed", async () => {
+        try {
+          await sendTx(provider, claimIx(), [hermesAuthority]);
+          expect.fail("Should have thrown ZeroAmount");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.ZeroAmount);
+        }
+      });
+
+      it("a payment made before the evacuation cannot be refunded", async () => {
+        try {
+          await sendTx(
+            provider,
+            new TransactionInstruction({
+              programId: TREASURY_PROGRAM_ID,
+              keys: [
+                { pubkey: keeperAuthority.publicKey, isSigner: true, isWritable: false },
+                { pubkey: treasuryConfigPda, isSigner: false, isWritable: false },
+                { pubkey: auditorConfigPda, isSigner: false, isWritable: false },
+                { pubkey: treasuryVaultPda, isSigner: false, isWritable: true },
+                { pubkey: ccsConfigPda, isSigner: false, isWritable: true },
+                { pubkey: revenueHistoryPda, isSigner: false, isWritable: true },
+                { pubkey: paymentRecordPda, isSigner: false, isWritable: true },
+                { pubkey: payer.publicKey, isSigner: false, isWritable: true },
+                { pubkey: TREASURY_PROGRAM_ID, isSigner: false, isWritable: false }, // referrer: none
+                { pubkey: ownerBudgetPda, isSigner: false, isWritable: true },
+                { pubkey: payerTrackerPda, isSigner: false, isWritable: true },
+              ],
+              data: Buffer.concat([ixDiscriminator("refund_service_payment"), encodeBytes32(randomHash())]),
+            }),
+            [keeperAuthority]
+          );
+          expect.fail("Should have thrown SettledByEvacuation");
+        } catch (err: any) {
+          expectCustomError(err, TreasuryErr.SettledByEvacuation);
+        }
+      });
+    });
   });
 });